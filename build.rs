@@ -0,0 +1,53 @@
+//! Generates typed bindings for the stored procedures defined under `migrations/*/routines`.
+//!
+//! Every `create procedure` becomes a function of `shared::database::procedures` with the same
+//! name and one typed argument per parameter, so that calling a procedure that does not exist
+//! or with the wrong arguments fails to compile.
+
+#[path = "build/procedure_parser.rs"]
+mod procedure_parser;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+
+fn main() {
+    let migrations = Path::new("migrations");
+    println!("cargo:rerun-if-changed={}", migrations.display());
+
+    let mut files = Vec::new();
+    if let Ok(modules) = fs::read_dir(migrations) {
+        for module in modules.flatten() {
+            collect_sql_files(&module.path().join("routines"), &mut files);
+        }
+    }
+    files.sort();
+
+    let sources = files.iter()
+        .map(|file| {
+            println!("cargo:rerun-if-changed={}", file.display());
+            let sql = fs::read_to_string(file).unwrap_or_else(|e| panic!("cannot read {}: {}", file.display(), e));
+            (file.display().to_string(), sql)
+        })
+        .collect::<Vec<_>>();
+    let procedures = procedure_parser::collect_procedures(sources.iter().map(|(source, sql)| (source.as_str(), sql.as_str())))
+        .unwrap_or_else(|e| panic!("{}", e));
+
+    let out = PathBuf::from(std::env::var("OUT_DIR").expect("OUT_DIR is set by cargo")).join("procedures.rs");
+    fs::write(&out, procedure_parser::generate(procedures.values())).unwrap_or_else(|e| panic!("cannot write {}: {}", out.display(), e));
+}
+
+
+fn collect_sql_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_sql_files(&path, files);
+        } else if path.extension().is_some_and(|extension| extension == "sql") {
+            files.push(path);
+        }
+    }
+}
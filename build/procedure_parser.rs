@@ -0,0 +1,248 @@
+//! Parser of the `create procedure` statements of the routines, and generator of their bindings.
+//!
+//! Shared by `build.rs` and `tests/procedure_parser.rs` through `#[path]`, so it only depends on std.
+//! A parameter followed by `/* nullable */` takes an `Option`, every other one a plain value.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+
+
+/// Rust keywords a parameter name may collide with, bound as raw identifiers.
+const KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern", "fn", "for",
+    "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return", "static", "struct",
+    "trait", "type", "unsafe", "use", "where", "while",
+];
+
+/// Comment marking a parameter that accepts `NULL`.
+const NULLABLE: &str = "nullable";
+
+pub struct Procedure {
+    pub name: String,
+    pub params: Vec<Param>,
+    pub source: String,
+}
+
+pub struct Param {
+    pub name: String,
+    pub sql_type: String,
+    pub rust_type: &'static str,
+    pub nullable: bool,
+}
+
+
+/// Procedures of every `(source, sql)` file, by name; a name defined twice is an error.
+pub fn collect_procedures<'a>(files: impl IntoIterator<Item = (&'a str, &'a str)>) -> Result<BTreeMap<String, Procedure>, String> {
+    let mut procedures = BTreeMap::new();
+    for (source, sql) in files {
+        for procedure in parse_procedures(sql, source)? {
+            if let Some(previous) = procedures.get(&procedure.name) {
+                let previous: &Procedure = previous;
+                return Err(format!("procedure {} is defined in both {} and {}", procedure.name, previous.source, procedure.source));
+            }
+            procedures.insert(procedure.name.clone(), procedure);
+        }
+    }
+    Ok(procedures)
+}
+
+pub fn parse_procedures(sql: &str, source: &str) -> Result<Vec<Procedure>, String> {
+    // offsets in `blanked` and `code` point at the same bytes of `sql`; `code` also leaves out
+    // quoted identifiers, which may spell keywords
+    let blanked = blank_comments_and_strings(sql, false);
+    let code = blank_comments_and_strings(sql, true).to_ascii_lowercase();
+    let mut procedures = Vec::new();
+    let mut offset = 0;
+
+    while let Some(start) = find_create_procedure(&code, offset) {
+        let open = start + code[start..].find('(').ok_or_else(|| format!("{}: missing parameter list", source))?;
+        let name = sql[start..open].trim().trim_matches('`').to_string();
+
+        let mut depth = 0;
+        let close = code[open..].char_indices()
+            .find_map(|(index, c)| {
+                match c {
+                    '(' => depth += 1,
+                    ')' => depth -= 1,
+                    _ => {},
+                }
+                (depth == 0).then_some(open + index)
+            })
+            .ok_or_else(|| format!("{}: unbalanced parameter list of {}", source, name))?;
+
+        let params = split_top_level(&code, open + 1, close)
+            .into_iter()
+            .filter(|(from, to)| !code[*from..*to].trim().is_empty())
+            .map(|(from, to)| parse_param(&blanked[from..to], &sql[from..to], &name, source))
+            .collect::<Result<_, _>>()?;
+
+        procedures.push(Procedure { name, params, source: source.to_string() });
+        offset = close;
+    }
+    Ok(procedures)
+}
+
+pub fn generate<'a>(procedures: impl Iterator<Item = &'a Procedure>) -> String {
+    let mut code = String::from("// Generated by build.rs from migrations/*/routines, do not edit.\n");
+    for procedure in procedures {
+        let signature = procedure.params.iter()
+            .map(|param| format!("{} {}", param.name.trim_start_matches("r#"), param.sql_type))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let arguments = procedure.params.iter()
+            .map(|param| match param.nullable {
+                true => format!("{}: impl Into<Option<{}>>", param.name, param.rust_type),
+                false => format!("{}: impl Into<{}>", param.name, param.rust_type),
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        let values = procedure.params.iter()
+            .map(|param| format!("MySqlParam::from({}.into())", param.name))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        writeln!(code).unwrap();
+        writeln!(code, "/// `{}({})`, from `{}`", procedure.name, signature, procedure.source.replace('\\', "/")).unwrap();
+        writeln!(code, "pub fn {}({}) -> ProcedureCall {{", procedure.name, arguments).unwrap();
+        writeln!(code, "    ProcedureCall::new(\"{}\", vec![{}])", procedure.name, values).unwrap();
+        writeln!(code, "}}").unwrap();
+    }
+    code
+}
+
+
+/// `sql` with its comments and string literals, and its quoted identifiers with `identifiers`,
+/// replaced by spaces byte for byte.
+fn blank_comments_and_strings(sql: &str, identifiers: bool) -> String {
+    let bytes = sql.as_bytes();
+    let mut code = bytes.to_vec();
+    let mut index = 0;
+    while index < bytes.len() {
+        let next = bytes.get(index + 1).copied();
+        let end = match bytes[index] {
+            b'-' if next == Some(b'-') && bytes.get(index + 2).is_none_or(u8::is_ascii_whitespace) => line_end(bytes, index),
+            b'#' => line_end(bytes, index),
+            b'/' if next == Some(b'*') => find(bytes, index + 2, b"*/").map_or(bytes.len(), |end| end + 2),
+            quote @ (b'\'' | b'"') => quoted_end(bytes, index, quote),
+            b'`' if identifiers => quoted_end(bytes, index, b'`'),
+            // quotes inside a kept identifier do not open a string
+            b'`' => {
+                index = quoted_end(bytes, index, b'`');
+                continue;
+            },
+            _ => {
+                index += 1;
+                continue;
+            },
+        };
+        code[index..end].fill(b' ');
+        index = end;
+    }
+    // blanked ranges start and end on ASCII delimiters, so no character is cut
+    String::from_utf8(code).expect("blanking keeps UTF-8 valid")
+}
+
+fn line_end(bytes: &[u8], from: usize) -> usize {
+    find(bytes, from, b"\n").unwrap_or(bytes.len())
+}
+
+fn find(bytes: &[u8], from: usize, needle: &[u8]) -> Option<usize> {
+    bytes[from..].windows(needle.len()).position(|window| window == needle).map(|position| from + position)
+}
+
+/// End of the literal opened at `from`, after its closing quote; backslashes and doubled quotes escape.
+fn quoted_end(bytes: &[u8], from: usize, quote: u8) -> usize {
+    let mut index = from + 1;
+    while index < bytes.len() {
+        match bytes[index] {
+            b'\\' if quote != b'`' => index += 2,
+            c if c == quote && bytes.get(index + 1) == Some(&quote) => index += 2,
+            c if c == quote => return index + 1,
+            _ => index += 1,
+        }
+    }
+    bytes.len()
+}
+
+/// Offset right after the next `create procedure` of the blanked, lowercase `code`.
+fn find_create_procedure(code: &str, from: usize) -> Option<usize> {
+    let is_word = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    code[from..].match_indices("create").map(|(index, _)| from + index).find_map(|index| {
+        let before = code[..index].chars().next_back();
+        let rest = &code[index + "create".len()..];
+        let after_space = rest.trim_start();
+        let separated = after_space.len() < rest.len();
+        let procedure = after_space.strip_prefix("procedure").filter(|tail| !tail.starts_with(is_word))?;
+        (separated && !before.is_some_and(is_word)).then(|| code.len() - procedure.len())
+    })
+}
+
+/// Ranges of the comma separated items of `code[from..to]`, ignoring the commas of nested parentheses.
+fn split_top_level(code: &str, from: usize, to: usize) -> Vec<(usize, usize)> {
+    let mut parts = Vec::new();
+    let (mut depth, mut start) = (0, from);
+    for (index, c) in code[from..to].char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push((start, from + index));
+                start = from + index + 1;
+            },
+            _ => {},
+        }
+    }
+    parts.push((start, to));
+    parts
+}
+
+/// `code` is the blanked parameter, `original` the same bytes with their comments.
+fn parse_param(code: &str, original: &str, procedure: &str, source: &str) -> Result<Param, String> {
+    let mut words: Vec<&str> = code.split_whitespace().collect();
+    match words.first().map(|word| word.to_ascii_lowercase()).as_deref() {
+        Some("in") => {
+            words.remove(0);
+        },
+        Some("out") | Some("inout") => return Err(format!("{}: OUT parameters of {} are not supported", source, procedure)),
+        _ => {},
+    }
+    if words.len() < 2 {
+        return Err(format!("{}: cannot read parameter `{}` of {}", source, original.trim(), procedure));
+    }
+
+    let mut name = words[0].trim_matches('`').trim_start_matches('_').to_string();
+    if KEYWORDS.contains(&name.as_str()) {
+        name.insert_str(0, "r#");
+    }
+    let sql_type = words[1..].join(" ").to_ascii_lowercase();
+    let rust_type = rust_type(&sql_type)
+        .ok_or_else(|| format!("{}: unsupported type `{}` of {}.{}", source, sql_type, procedure, name))?;
+    Ok(Param { name, sql_type, rust_type, nullable: is_nullable(original) })
+}
+
+fn is_nullable(original: &str) -> bool {
+    original.split("/*").skip(1)
+        .filter_map(|comment| comment.split_once("*/"))
+        .any(|(comment, _)| comment.trim().eq_ignore_ascii_case(NULLABLE))
+}
+
+fn rust_type(sql_type: &str) -> Option<&'static str> {
+    let base = sql_type.split(['(', ' ']).next().unwrap_or_default();
+    let unsigned = sql_type.contains("unsigned");
+    Some(match base {
+        "tinyint" if sql_type.starts_with("tinyint(1)") => "bool",
+        "bool" | "boolean" => "bool",
+        "tinyint" => if unsigned { "u8" } else { "i8" },
+        "smallint" => if unsigned { "u16" } else { "i16" },
+        "mediumint" | "int" | "integer" => if unsigned { "u32" } else { "i32" },
+        "bigint" => if unsigned { "u64" } else { "i64" },
+        "float" => "f32",
+        "double" | "real" => "f64",
+        "decimal" | "numeric" => "Decimal",
+        "char" | "varchar" | "tinytext" | "text" | "mediumtext" | "longtext" | "enum" | "json" => "String",
+        "binary" | "varbinary" | "tinyblob" | "blob" | "mediumblob" | "longblob" => "Vec<u8>",
+        "date" => "NaiveDate",
+        "datetime" | "timestamp" => "DateTime<Utc>",
+        _ => return None,
+    })
+}
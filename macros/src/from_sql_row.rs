@@ -0,0 +1,122 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Data, DeriveInput, Field, Fields, LitStr, Path};
+
+
+enum Source {
+    Column { name: String, default: Option<Fallback> },
+    Nested { prefix: String },
+    Skip,
+}
+
+enum Fallback {
+    Trait,
+    Function(Path),
+}
+
+
+pub fn expand(input: DeriveInput) -> syn::Result<TokenStream> {
+    let name = &input.ident;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => return Err(syn::Error::new_spanned(name, "FromSqlRow can only be derived for structs with named fields")),
+        },
+        _ => return Err(syn::Error::new_spanned(name, "FromSqlRow can only be derived for structs")),
+    };
+
+    let assignments = fields.iter()
+        .map(|field| {
+            let ident = field.ident.as_ref().expect("named field");
+            let value = field_value(field, parse_source(field)?);
+            Ok(quote! { #ident: #value })
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    Ok(quote! {
+        impl #impl_generics ::e_commerce_system::shared::database::mysql::FromSqlRow for #name #type_generics #where_clause {
+            fn map_row_with_prefix(
+                row: &::sqlx::mysql::MySqlRow,
+                index_map: &::std::collections::HashMap<::std::string::String, usize>,
+                prefix: &str,
+            ) -> ::std::result::Result<Self, ::sqlx::Error> {
+                ::std::result::Result::Ok(Self {
+                    #(#assignments,)*
+                })
+            }
+        }
+    })
+}
+
+
+fn parse_source(field: &Field) -> syn::Result<Source> {
+    let mut column = field.ident.as_ref().expect("named field").to_string();
+    let mut default = None;
+    let mut nested = false;
+    let mut prefix = String::new();
+    let mut skip = false;
+
+    for attr in field.attrs.iter().filter(|attr| attr.path().is_ident("sql")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename") {
+                column = meta.value()?.parse::<LitStr>()?.value();
+            } else if meta.path.is_ident("default") {
+                default = Some(match meta.input.peek(syn::Token![=]) {
+                    true => Fallback::Function(meta.value()?.parse::<LitStr>()?.parse()?),
+                    false => Fallback::Trait,
+                });
+            } else if meta.path.is_ident("nested") {
+                nested = true;
+            } else if meta.path.is_ident("prefix") {
+                prefix = meta.value()?.parse::<LitStr>()?.value();
+            } else if meta.path.is_ident("skip") {
+                skip = true;
+            } else {
+                return Err(meta.error("expected `rename`, `default`, `nested`, `prefix` or `skip`"));
+            }
+            Ok(())
+        })?;
+    }
+
+    if skip {
+        return Ok(Source::Skip);
+    }
+    if nested {
+        return Ok(Source::Nested { prefix });
+    }
+    if !prefix.is_empty() {
+        return Err(syn::Error::new_spanned(field, "`prefix` only applies to `nested` fields"));
+    }
+    Ok(Source::Column { name: column.to_lowercase(), default })
+}
+
+fn field_value(field: &Field, source: Source) -> TokenStream {
+    let ty = &field.ty;
+    match source {
+        Source::Skip => quote! { ::std::default::Default::default() },
+        Source::Nested { prefix } => quote! {
+            <#ty as ::e_commerce_system::shared::database::mysql::FromSqlRow>::map_row_with_prefix(
+                row,
+                index_map,
+                &::std::format!("{}{}", prefix, #prefix),
+            )?
+        },
+        Source::Column { name, default } => {
+            let missing = match default {
+                None => quote! {
+                    return ::std::result::Result::Err(::sqlx::Error::ColumnNotFound(::std::format!("{}{}", prefix, #name)))
+                },
+                Some(Fallback::Trait) => quote! { ::std::default::Default::default() },
+                Some(Fallback::Function(path)) => quote! { #path() },
+            };
+            quote! {
+                match ::e_commerce_system::shared::database::mysql::column_index(index_map, prefix, #name) {
+                    ::std::option::Option::Some(index) => ::sqlx::Row::try_get(row, index)?,
+                    ::std::option::Option::None => #missing,
+                }
+            }
+        },
+    }
+}
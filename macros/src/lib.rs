@@ -0,0 +1,28 @@
+//! Derive macros of the e-commerce system.
+
+use proc_macro::TokenStream;
+use syn::{parse_macro_input, DeriveInput};
+
+mod from_sql_row;
+
+
+/// Derives `FromSqlRow` by reading every field from the column of the same name.
+///
+/// Field attributes:
+/// - `#[sql(rename = "column")]` reads another column.
+/// - `#[sql(default)]` or `#[sql(default = "path::to::fn")]` falls back to `Default::default()`
+///   or to the function when the column is missing from the result set.
+/// - `#[sql(nested)]` or `#[sql(nested, prefix = "auth_")]` maps the field with its own
+///   `FromSqlRow` implementation, reading its columns with the given prefix.
+/// - `#[sql(skip)]` never reads the field and sets it to `Default::default()`.
+///
+/// A missing column without a default fails with `sqlx::Error::ColumnNotFound`.
+/// The generated code refers to `e_commerce_system::shared::database::mysql`, so the macro is
+/// meant for the application crate and its tests.
+#[proc_macro_derive(FromSqlRow, attributes(sql))]
+pub fn derive_from_sql_row(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    from_sql_row::expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
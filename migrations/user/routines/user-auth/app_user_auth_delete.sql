@@ -1,8 +1,11 @@
-create procedure app_user_auth_delete(IN __user_auth_id bigint, IN __meta_user bigint)
+create procedure app_user_auth_delete(IN __user_auth_id bigint, IN __user_auth_version bigint, IN __meta_user bigint /* nullable */)
 begin
 
 delete from user_auth
-where id = __user_auth_id;
+where id = __user_auth_id
+  and version = __user_auth_version;
+
+select row_count() as affected_rows;
 
 end;
 
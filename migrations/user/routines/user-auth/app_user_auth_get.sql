@@ -1,4 +1,4 @@
-create procedure app_user_auth_get(IN __user_auth_id bigint, IN __meta_user bigint)
+create procedure app_user_auth_get(IN __user_auth_id bigint, IN __meta_user bigint /* nullable */)
 begin
 
     select *
@@ -1,4 +1,4 @@
-create procedure app_user_auth_get_all(IN __meta_user bigint)
+create procedure app_user_auth_get_all(IN __meta_user bigint /* nullable */)
 begin
 
     select *
@@ -1,4 +1,4 @@
-create procedure app_user_auth_get_by_name(IN __user_auth_name varchar(200), IN __meta_user bigint)
+create procedure app_user_auth_get_by_name(IN __user_auth_name varchar(200), IN __meta_user bigint /* nullable */)
 begin
 
     select *
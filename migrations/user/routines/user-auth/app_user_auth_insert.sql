@@ -1,6 +1,6 @@
 create procedure app_user_auth_insert(IN __user_auth_name varchar(200),
-                                      IN __user_auth_description longtext,
-                                      IN __meta_user bigint)
+                                      IN __user_auth_description longtext /* nullable */,
+                                      IN __meta_user bigint /* nullable */)
 begin
 
     insert into user_auth
@@ -1,15 +1,20 @@
 create procedure app_user_auth_update(IN __user_auth_id bigint,
+                                      IN __user_auth_version bigint,
                                       IN __user_auth_name varchar(200),
-                                      IN __user_auth_description longtext,
-                                      IN __meta_user bigint)
+                                      IN __user_auth_description longtext /* nullable */,
+                                      IN __meta_user bigint /* nullable */)
 begin
 
     update user_auth
     set name = __user_auth_name,
-        description = __user_auth_description
-    where id = __user_auth_id;
+        description = __user_auth_description,
+        version = version + 1
+    where id = __user_auth_id
+      and version = __user_auth_version;
 
-    call app_user_auth_get(last_insert_id(), __meta_user);
+    if row_count() > 0 then
+        call app_user_auth_get(__user_auth_id, __meta_user);
+    end if;
 
 end;
 
@@ -1,8 +1,11 @@
-create procedure app_user_status_delete(IN __user_status_id bigint, IN __meta_user bigint)
+create procedure app_user_status_delete(IN __user_status_id bigint, IN __user_status_version bigint, IN __meta_user bigint /* nullable */)
 begin
 
     delete from user_status
-    where id = __user_status_id;
+    where id = __user_status_id
+      and version = __user_status_version;
+
+    select row_count() as affected_rows;
 
 end;
 
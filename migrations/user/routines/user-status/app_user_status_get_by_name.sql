@@ -1,4 +1,4 @@
-create procedure app_user_status_get_by_name(IN __user_status_name varchar(200), IN __meta_user bigint)
+create procedure app_user_status_get_by_name(IN __user_status_name varchar(200), IN __meta_user bigint /* nullable */)
 begin
 
     select *
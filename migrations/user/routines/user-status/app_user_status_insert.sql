@@ -1,6 +1,6 @@
 create procedure app_user_status_insert(IN __user_status_name varchar(200),
-                                        IN __user_status_description longtext,
-                                        IN __meta_user bigint)
+                                        IN __user_status_description longtext /* nullable */,
+                                        IN __meta_user bigint /* nullable */)
 begin
 
     insert into user_status
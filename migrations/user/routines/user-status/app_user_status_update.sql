@@ -1,15 +1,20 @@
 create procedure app_user_status_update(IN __user_status_id bigint,
+                                        IN __user_status_version bigint,
                                         IN __user_status_name varchar(200),
-                                        IN __user_status_description longtext,
-                                        IN __meta_user bigint)
+                                        IN __user_status_description longtext /* nullable */,
+                                        IN __meta_user bigint /* nullable */)
 begin
 
     update user_status
     set name = __user_status_name,
-        description = __user_status_description
-    where id = __user_status_id;
+        description = __user_status_description,
+        version = version + 1
+    where id = __user_status_id
+      and version = __user_status_version;
 
-    call app_user_status_get(__user_status_id, __meta_user);
+    if row_count() > 0 then
+        call app_user_status_get(__user_status_id, __meta_user);
+    end if;
 
 end;
 
@@ -1,8 +1,14 @@
-create procedure app_user_delete(IN __user_id bigint, IN __meta_user bigint)
+create procedure app_user_delete(IN __user_id bigint, IN __user_status bigint, IN __user_version bigint, IN __meta_user bigint /* nullable */)
 begin
 
-    delete from user
-    where id = __user_id;
+    -- soft delete: the row stays with the deleted status until it is restored
+    update user
+    set status = __user_status,
+        updated_at = now(),
+        version = version + 1
+    where id = __user_id
+      and version = __user_version;
 
-end;
+    select row_count() as affected_rows;
 
+end;
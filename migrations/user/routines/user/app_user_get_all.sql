@@ -1,4 +1,4 @@
-create procedure app_user_get_all(IN __limit int, IN __offset int, IN __meta_user bigint)
+create procedure app_user_get_all(IN __limit int /* nullable */, IN __offset int /* nullable */, IN __meta_user bigint /* nullable */)
 begin
 
     if(__limit is not null and __offset is not null ) then
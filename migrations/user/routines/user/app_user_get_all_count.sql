@@ -1,4 +1,4 @@
-create procedure app_user_get_all_count(IN __meta_user bigint)
+create procedure app_user_get_all_count(IN __meta_user bigint /* nullable */)
 begin
 
     select count(*) as count
@@ -0,0 +1,8 @@
+create procedure app_user_get_by_ids(IN __user_ids json, IN __meta_user bigint /* nullable */)
+begin
+
+    select user_view.*
+    from user_view
+    join json_table(__user_ids, '$[*]' columns (id bigint path '$')) as ids on ids.id = user_view.id;
+
+end;
@@ -1,4 +1,4 @@
-create procedure app_user_get_by_username(IN __user_username varchar(300), IN __meta_user bigint)
+create procedure app_user_get_by_username(IN __user_username varchar(300), IN __meta_user bigint /* nullable */)
 begin
 
     select *
@@ -0,0 +1,16 @@
+create procedure app_user_get_for_update(IN __user_id bigint, IN __meta_user bigint /* nullable */)
+begin
+
+    declare __locked_id bigint;
+
+    -- only the user row is locked, not the shared auth and status rows of the view
+    select id into __locked_id
+    from user
+    where id = __user_id
+    for update;
+
+    select *
+    from user_view
+    where id = __user_id;
+
+end;
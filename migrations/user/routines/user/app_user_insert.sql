@@ -2,15 +2,15 @@ create procedure app_user_insert(IN __user_first_name varchar(200),
                                  IN __user_last_name varchar(300),
                                  IN __user_username varchar(300),
                                  IN __user_password varchar(500),
-                                 IN __user_profile_pic_url longtext,
+                                 IN __user_profile_pic_url longtext /* nullable */,
                                  IN __user_auth bigint,
                                  IN __user_status bigint,
-                                 IN __user_hired_date datetime,
-                                 IN __user_title varchar(200),
-                                 IN __user_address varchar(500),
-                                 IN __user_country varchar(20),
-                                 IN __user_phone varchar(20),
-                                 IN __meta_user bigint)
+                                 IN __user_hired_date datetime /* nullable */,
+                                 IN __user_title varchar(200) /* nullable */,
+                                 IN __user_address varchar(500) /* nullable */,
+                                 IN __user_country varchar(20) /* nullable */,
+                                 IN __user_phone varchar(20) /* nullable */,
+                                 IN __meta_user bigint /* nullable */)
 begin
 
     insert into user
@@ -1,12 +1,13 @@
 create procedure app_user_update(IN __user_id bigint,
+                                 IN __user_version bigint,
                                  IN __user_first_name varchar(200),
                                  IN __user_last_name varchar(300),
-                                 IN __user_hired_date datetime,
-                                 IN __user_title varchar(200),
-                                 IN __user_address varchar(500),
-                                 IN __user_country varchar(20),
-                                 IN __user_phone varchar(20),
-                                 IN __meta_user bigint)
+                                 IN __user_hired_date datetime /* nullable */,
+                                 IN __user_title varchar(200) /* nullable */,
+                                 IN __user_address varchar(500) /* nullable */,
+                                 IN __user_country varchar(20) /* nullable */,
+                                 IN __user_phone varchar(20) /* nullable */,
+                                 IN __meta_user bigint /* nullable */)
 begin
 
     update user
@@ -17,11 +18,15 @@ begin
         address = __user_address,
         country = __user_country,
         phone = __user_phone,
-        updated_at = now()
-    where id = __user_id;
+        updated_at = now(),
+        version = version + 1
+    where id = __user_id
+      and version = __user_version;
 
 
-    call app_user_get(__user_id, __meta_user);
+    if row_count() > 0 then
+        call app_user_get(__user_id, __meta_user);
+    end if;
 
 end;
 
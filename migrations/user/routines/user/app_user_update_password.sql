@@ -1,11 +1,12 @@
 create procedure app_user_update_password(IN __user_id bigint,
-                                          IN __user_password varchar(500),
-                                          IN __meta_user bigint)
+                                          IN __user_password varchar(500) /* nullable */,
+                                          IN __meta_user bigint /* nullable */)
 begin
 
     update user
     set password = __user_password,
-        updated_at = now()
+        updated_at = now(),
+        version = version + 1
     where id = __user_id;
 
 
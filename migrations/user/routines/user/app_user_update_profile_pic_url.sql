@@ -1,11 +1,12 @@
 create procedure app_user_update_profile_pic_url(IN __user_id bigint,
-                                                 IN __user_profile_pic_url longtext,
-                                                 IN __meta_user bigint)
+                                                 IN __user_profile_pic_url longtext /* nullable */,
+                                                 IN __meta_user bigint /* nullable */)
 begin
 
     update user
     set profile_pic_url = __user_profile_pic_url,
-        updated_at = now()
+        updated_at = now(),
+        version = version + 1
     where id = __user_id;
 
 
@@ -1,9 +1,10 @@
-create procedure app_user_update_status(IN __user_id bigint, IN __user_status bigint, IN __meta_user bigint)
+create procedure app_user_update_status(IN __user_id bigint, IN __user_status bigint, IN __meta_user bigint /* nullable */)
 begin
 
     update user
     set status = __user_status,
-        updated_at = now()
+        updated_at = now(),
+        version = version + 1
     where id = __user_id;
 
 
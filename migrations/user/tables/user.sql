@@ -17,6 +17,7 @@ create table if not exists user
     phone           varchar(20)                        null,
     created_at      datetime default CURRENT_TIMESTAMP not null,
     updated_at      datetime default CURRENT_TIMESTAMP not null,
+    version         bigint   default 1                 not null,
     constraint user_pk_2
         unique (username),
     constraint user_user_auth_id_fk
@@ -4,7 +4,8 @@ create table if not exists user_auth
     id          bigint auto_increment
         primary key,
     name        varchar(200) not null,
-    description longtext     null
+    description longtext     null,
+    version     bigint       default 1 not null
 );
 
 create index user_auth_name_index
@@ -0,0 +1,35 @@
+-- Adds the optimistic locking version of each table to databases created before it existed.
+-- The tables scripts only create missing tables, so they leave those databases without it.
+
+set @statement = (
+    select if(count(*) = 0, 'alter table user_auth add column version bigint default 1 not null', 'do 0')
+    from information_schema.columns
+    where table_schema = database()
+      and table_name = 'user_auth'
+      and column_name = 'version'
+);
+prepare add_version from @statement;
+execute add_version;
+deallocate prepare add_version;
+
+set @statement = (
+    select if(count(*) = 0, 'alter table user_status add column version bigint default 1 not null', 'do 0')
+    from information_schema.columns
+    where table_schema = database()
+      and table_name = 'user_status'
+      and column_name = 'version'
+);
+prepare add_version from @statement;
+execute add_version;
+deallocate prepare add_version;
+
+set @statement = (
+    select if(count(*) = 0, 'alter table user add column version bigint default 1 not null', 'do 0')
+    from information_schema.columns
+    where table_schema = database()
+      and table_name = 'user'
+      and column_name = 'version'
+);
+prepare add_version from @statement;
+execute add_version;
+deallocate prepare add_version;
@@ -1,4 +1,5 @@
-create view user_view as
+-- `or replace` lets the script update the view of existing databases
+create or replace view user_view as
 select
     user.id,
     first_name,
@@ -7,18 +8,23 @@ select
     password,
     profile_pic_url,
     auth,
+    user_auth.id as auth_id,
     user_auth.name as auth_name,
     user_auth.description as auth_description,
+    user_auth.version as auth_version,
     status,
+    user_status.id as status_id,
     user_status.name as status_name,
     user_status.description as status_description,
+    user_status.version as status_version,
     hired_date,
     title,
     address,
     country,
     phone,
     created_at,
-    updated_at
+    updated_at,
+    user.version
 from user
     left join user_auth on user.auth = user_auth.id
     left join user_status on user.status = user_status.id
@@ -1,24 +1,94 @@
-use axum::{middleware, Router, routing::get, http::Method};
+use axum::{middleware, Router, routing::get, extract::{Path, State}, http::{header, StatusCode}, response::IntoResponse};
+use std::future::IntoFuture;
 use std::net::SocketAddr;
 use std::str::FromStr;
-use tower_http::{trace::TraceLayer, cors::{Any, CorsLayer}, compression::CompressionLayer};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tracing::{info, warn};
+use tower_http::compression::CompressionLayer;
 
-use utoipa::OpenApi;
-use utoipa_axum::router::OpenApiRouter;
 use utoipa_swagger_ui::SwaggerUi;
 
 use crate::shared::configuration::AppConfig;
-use crate::shared::openapi::spec::ApiDoc;
+use crate::shared::openapi::versions::api_doc;
 use crate::shared::state::AppState;
 use crate::services::user::routes::user_routes;
+use crate::shared::health::controller::routes as health_routes;
+use crate::shared::http::security::{
+    content_security_policy, with_security_layers, API_CONTENT_SECURITY_POLICY, SWAGGER_CONTENT_SECURITY_POLICY,
+};
+use crate::shared::http::versioning::{deprecation_middleware, Deprecation, API_VERSIONS, LATEST_API_VERSION, UNVERSIONED};
 use crate::shared::metrics::metrics_logger::metrics_and_logging_middleware;
+use crate::shared::idempotency::middleware::idempotency_middleware;
+use crate::shared::rate_limit::middleware::rate_limit_middleware;
+use crate::shared::shutdown;
 
 pub fn create_api_router() -> Router<AppState> {
     Router::new()
         .nest("/user", user_routes::routes())
+        .nest("/manager", user_routes::manager_routes())
+        .nest("/customer", user_routes::customer_routes())
 }
 
-pub struct App { pub addr: SocketAddr, pub router: Router }
+/// Same as [`create_api_router`], the routes returning users in the v2 shape.
+pub fn create_api_v2_router() -> Router<AppState> {
+    Router::new()
+        .nest("/user", user_routes::v2_routes())
+        .nest("/manager", user_routes::manager_v2_routes())
+        .nest("/customer", user_routes::customer_v2_routes())
+}
+
+/// `router` sending the deprecation headers of `version` when `api.deprecations` lists it,
+/// pointing clients to the same route of `successor`.
+fn with_deprecation(router: Router<AppState>, version: &str, successor: &str, config: &AppConfig) -> Router<AppState> {
+    match config.api.deprecations.get(version) {
+        Some(deprecation_config) => {
+            let deprecation = Arc::new(Deprecation::from_config(version, successor, deprecation_config));
+            router.layer(middleware::from_fn_with_state(deprecation, deprecation_middleware))
+        },
+        None => router,
+    }
+}
+
+pub struct App { pub addr: SocketAddr, pub router: Router, pub state: AppState }
+
+impl App {
+    /// Serves on `listener` until SIGINT or SIGTERM, then drains and closes the state.
+    ///
+    /// On the signal readiness reports draining for the configured delay, then the listener closes and
+    /// in-flight requests get the drain timeout to finish before they are dropped.
+    pub async fn serve(self, listener: TcpListener) -> anyhow::Result<()> {
+        let shutdown = self.state.shutdown.clone();
+        let readiness_delay = Duration::from_secs(self.state.config.shutdown.readiness_delay_seconds);
+        let drain_timeout = Duration::from_secs(self.state.config.shutdown.drain_timeout_seconds);
+
+        let signalled = shutdown.clone();
+        tokio::spawn(async move {
+            shutdown::signal().await;
+            signalled.start_draining();
+            info!("[SHUTDOWN] Draining, readiness reports unavailable for {}s", readiness_delay.as_secs());
+            tokio::time::sleep(readiness_delay).await;
+            info!("[SHUTDOWN] Closing the listener, waiting up to {}s for in-flight requests", drain_timeout.as_secs());
+            signalled.stop();
+        });
+
+        let stopped = shutdown.clone();
+        let server = axum::serve(listener, self.router.into_make_service_with_connect_info::<SocketAddr>())
+            .with_graceful_shutdown(async move { stopped.stopped().await })
+            .into_future();
+        tokio::select! {
+            served = server => served?,
+            _ = async { shutdown.stopped().await; tokio::time::sleep(drain_timeout).await } => {
+                warn!("[SHUTDOWN] Drain timeout elapsed, dropping the remaining requests");
+            },
+        }
+
+        self.state.close().await;
+        info!("[SHUTDOWN] Stopped");
+        Ok(())
+    }
+}
 
 pub async fn build_app(cfg: AppConfig) -> anyhow::Result<App> {
 
@@ -27,50 +97,62 @@ pub async fn build_app(cfg: AppConfig) -> anyhow::Result<App> {
     // Create application state
     let app_state = AppState::new(cfg.clone()).await?;
 
-    // CORS configuration
-    let cors = CorsLayer::new()
-        .allow_methods([Method::OPTIONS, Method::GET, Method::POST, Method::PUT, Method::DELETE])
-        .allow_headers(Any)
-        .allow_origin(Any);
+    Ok(App { addr: SocketAddr::from_str(bind)?, router: build_router(app_state.clone()), state: app_state })
+}
+
+/// Routes, middlewares and API documentation of the application over `app_state`.
+pub fn build_router(app_state: AppState) -> Router {
+    let config = app_state.config.clone();
 
-    let (router, api) = OpenApiRouter::with_openapi(ApiDoc::openapi())
+    let router = Router::new()
         // API routes
-        .nest("/api", OpenApiRouter::from(create_api_router()))
+        .nest("/api/v1", with_deprecation(create_api_router(), "v1", LATEST_API_VERSION, &config))
+        .nest("/api/v2", with_deprecation(create_api_v2_router(), "v2", LATEST_API_VERSION, &config))
+        // v1 from before the versioning, for the clients still calling it
+        .nest("/api", with_deprecation(create_api_router(), UNVERSIONED, "v1", &config))
 
+        .layer(middleware::from_fn_with_state(app_state.clone(), idempotency_middleware))
+        .layer(middleware::from_fn_with_state(app_state.clone(), rate_limit_middleware))
         .layer(middleware::from_fn(metrics_and_logging_middleware))
 
         .layer(CompressionLayer::new())
-        .layer(CorsLayer::permissive())
-        .layer(TraceLayer::new_for_http())
 
         // Prometheus metrics endpoint
         // .route("/metrics", get(metrics_handler))
-        .route("/healthz", get(|| async { "ok" }))
+        .merge(health_routes())
+        .route("/media/{*key}", get(get_media))
 
-        // Layers
-        .layer(cors)
-        .layer(TraceLayer::new_for_http())
         // .layer(axum::middleware::from_fn_with_state(app_state.clone(), track_metrics))
-        .with_state(app_state)
-
-        .split_for_parts();
-
-    let router = router.merge(SwaggerUi::new("/swagger-ui").url("/apidoc/openapi.json", api));
-
-
-    Ok(App { addr: SocketAddr::from_str(bind)?, router })
+        .with_state(app_state);
+
+    let mut router = router.layer(content_security_policy(API_CONTENT_SECURITY_POLICY));
+    if config.http.swagger_ui {
+        let mut swagger_ui = SwaggerUi::new("/swagger-ui");
+        for version in API_VERSIONS {
+            let deprecated = config.api.deprecations.contains_key(version);
+            swagger_ui = swagger_ui.url(format!("/apidoc/{}/openapi.json", version), api_doc(version, deprecated));
+        }
+        let swagger_ui = Router::from(swagger_ui)
+            .layer(content_security_policy(SWAGGER_CONTENT_SECURITY_POLICY));
+        router = router.merge(swagger_ui);
+    }
+
+    with_security_layers(router, &config)
 }
 
 
-#[utoipa::path(
-    method(get, head),
-    path = "/api/health",
-    responses(
-        (status = OK, description = "Success", body = str, content_type = "text/plain")
-    )
-)]
-async fn health_check() -> &'static str {
-    "OK"
+/// Serves uploaded files from the configured blob store, used when `storage.public_base_url`
+/// points back at this service instead of a CDN or public bucket.
+pub async fn get_media(
+    Path(key): Path<String>,
+    State(state): State<AppState>
+) -> Result<impl IntoResponse, StatusCode> {
+    let blob = state.blob_store.get(&key).await.map_err(|_| StatusCode::NOT_FOUND)?;
+    match blob {
+        Some(blob) => {
+            let content_type = blob.content_type.unwrap_or_else(|| "application/octet-stream".to_string());
+            Ok(([(header::CONTENT_TYPE, content_type)], blob.bytes))
+        },
+        None => Err(StatusCode::NOT_FOUND),
+    }
 }
-
-
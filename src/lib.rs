@@ -0,0 +1,6 @@
+// lets the code generated by `e_commerce_system_macros` name this crate the same way inside and outside of it
+extern crate self as e_commerce_system;
+
+pub mod shared;
+pub mod services;
+pub mod app;
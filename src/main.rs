@@ -1,31 +1,41 @@
 use tokio::net::TcpListener;
-use crate::app::build_app;
-use crate::shared::configuration::AppConfig;
-use crate::shared::logging::log;
-
-mod shared;
-mod services;
-mod app;
+use e_commerce_system::app::build_app;
+use e_commerce_system::shared::configuration::AppConfig;
+use e_commerce_system::shared::configuration::cli::{CliOptions, USAGE};
+use e_commerce_system::shared::logging::log;
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
 // async fn main() {
 
+    let options = CliOptions::parse(std::env::args().skip(1))?;
+    if options.help {
+        println!("{}", USAGE);
+        return Ok(());
+    }
+
+    // Load configuration
+    let config = AppConfig::load(&options.sources)?;
+    if options.print_config {
+        println!("{}", serde_json::to_string_pretty(&config.redacted())?);
+        return Ok(());
+    }
+
     // Initialize tracing
     // tracing_subscriber::fmt::init();
     tracing_subscriber::fmt().with_max_level(tracing::Level::INFO).init();
 
-    // Load configuration
-    let config = AppConfig::default()?;
-
-    log::init_from_config(config.is_prod);
+    log::init_from_config(config.is_prod());
+    tracing::info!("Configuration of the {} environment: {:?}", config.environment, config.redacted());
 
     let app = build_app(config).await?;
 
     tracing::info!("Server running on http://{}", app.addr);
     log::info2(&format!("Server running on http://{}", app.addr));
-    tracing::info!("Swagger UI available at http://{}/swagger-ui", app.addr);
-    log::info2(&format!("Swagger UI available at http://{}/swagger-ui", app.addr));
+    if app.state.config.http.swagger_ui {
+        tracing::info!("Swagger UI available at http://{}/swagger-ui", app.addr);
+        log::info2(&format!("Swagger UI available at http://{}/swagger-ui", app.addr));
+    }
     tracing::info!("Metrics available at http://{}/metrics", app.addr);
     log::info2(&format!("Metrics available at http://{}/metrics", app.addr));
 
@@ -35,7 +45,7 @@ async fn main() -> anyhow::Result<()> {
     */
 
     let listener = TcpListener::bind(app.addr).await?;
-    axum::serve(listener, app.router).await?;
+    app.serve(listener).await?;
 
     Ok(())
 }
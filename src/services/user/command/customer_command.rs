@@ -0,0 +1,14 @@
+use serde::{Serialize, Deserialize};
+use utoipa::ToSchema;
+
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct CustomerCreateCommand {
+    pub first_name: String,
+    pub last_name: String,
+    pub username: String,
+    pub password: String,
+    pub address: String,
+    pub country: String,
+    pub phone: String,
+}
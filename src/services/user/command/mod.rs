@@ -1,3 +1,5 @@
 pub mod user_auth_command;
 pub mod user_status_command;
-pub mod user_command;
\ No newline at end of file
+pub mod user_command;
+pub mod manager_command;
+pub mod customer_command;
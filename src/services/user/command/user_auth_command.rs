@@ -1,6 +1,8 @@
 use serde::{Serialize, Deserialize};
 use utoipa::ToSchema;
 use crate::shared::models::response::PaginationRequest;
+use crate::shared::http::etag::IfMatch;
+use crate::shared::models::patch::Patch;
 
 
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
@@ -17,13 +19,25 @@ pub struct UserAuthCreateCommand{
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct UserAuthUpdateCommand {
     pub id: i64,
+    pub if_match: IfMatch,
     pub name: String,
     pub description: Option<String>,
 }
 
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct UserAuthPatchCommand {
+    pub id: i64,
+    pub if_match: IfMatch,
+    #[schema(value_type = Option<String>)]
+    pub name: Patch<String>,
+    #[schema(value_type = Option<String>)]
+    pub description: Patch<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct UserAuthDeleteCommand {
     pub id: i64,
+    pub if_match: IfMatch,
 }
 
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
@@ -2,6 +2,8 @@ use chrono::{DateTime, Utc};
 use serde::{Serialize, Deserialize};
 use utoipa::ToSchema;
 use crate::shared::models::response::PaginationRequest;
+use crate::shared::http::etag::IfMatch;
+use crate::shared::models::patch::Patch;
 
 
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
@@ -9,6 +11,11 @@ pub struct UserGetCommand {
     pub id: i64,
 }
 
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct UserGetByIdsCommand {
+    pub ids: Vec<i64>,
+}
+
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct UserGetByUsernameCommand {
     pub username: String,
@@ -35,7 +42,7 @@ pub struct UserCreateCommand {
     pub first_name: String,
     pub last_name: String,
     pub username: String,
-    pub password: Option<String>,
+    pub password: String,
     pub auth: i64,
     pub status: i64,
     pub hired_date: Option<DateTime<Utc>>,
@@ -48,6 +55,7 @@ pub struct UserCreateCommand {
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct UserUpdateCommand {
     pub id: i64,
+    pub if_match: IfMatch,
     pub first_name: String,
     pub last_name: String,
     pub hired_date: Option<DateTime<Utc>>,
@@ -58,11 +66,35 @@ pub struct UserUpdateCommand {
 }
 
 
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct UserPatchCommand {
+    pub id: i64,
+    pub if_match: IfMatch,
+    #[schema(value_type = Option<String>)]
+    pub first_name: Patch<String>,
+    #[schema(value_type = Option<String>)]
+    pub last_name: Patch<String>,
+    #[schema(value_type = Option<DateTime<Utc>>)]
+    pub hired_date: Patch<DateTime<Utc>>,
+    #[schema(value_type = Option<String>)]
+    pub title: Patch<String>,
+    #[schema(value_type = Option<String>)]
+    pub address: Patch<String>,
+    #[schema(value_type = Option<String>)]
+    pub country: Patch<String>,
+    #[schema(value_type = Option<String>)]
+    pub phone: Patch<String>,
+}
+
+
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct UserUpdatePasswordCommand {
-    pub code: String,
-    pub username: String,
-    pub password: Option<String>,
+    pub id: i64,
+    /// Id of the authenticated user changing the password
+    pub caller_id: i64,
+    /// Required when users change their own password, checked against the stored hash
+    pub current_password: Option<String>,
+    pub password: String,
 }
 
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
@@ -77,10 +109,22 @@ pub struct UserUpdateStatusCommand {
     pub status: i64,
 }
 
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct UserUploadProfilePicCommand {
+    pub id: i64,
+    pub content: Vec<u8>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct UserRestoreCommand {
+    pub id: i64,
+}
+
 
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct UserDeleteCommand {
     pub id: i64,
+    pub if_match: IfMatch,
 }
 
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
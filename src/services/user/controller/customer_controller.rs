@@ -0,0 +1,102 @@
+use axum::{Router, routing::{get}, extract::{Path, State}, Json, http::StatusCode};
+use axum::extract::Query;
+use crate::shared::state::AppState;
+use crate::services::user::command::customer_command::CustomerCreateCommand;
+use crate::services::user::dto::user_dto::{CustomerCreateRequest, CustomerUserResponse};
+use crate::services::user::controller::user_role_controller;
+use crate::services::user::service::user_role_service::Customer;
+use crate::shared::models::response::PaginationRequest;
+use crate::shared::security::authenticated_user::AuthenticatedUser;
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(get_customers).post(post_customer))
+        .route("/{user_id}", get(get_customer_by_id))
+}
+
+
+#[utoipa::path(
+    get,
+    path = "/api/customer",
+    params(
+        PaginationRequest
+    ),
+    responses(
+        (status = StatusCode::OK, description = "List of Customer", body = Vec<CustomerUserResponse>),
+        (status = StatusCode::UNAUTHORIZED, description = "Missing or invalid bearer token"),
+        (status = StatusCode::FORBIDDEN, description = "Only managers can list customers"),
+        (status = StatusCode::INTERNAL_SERVER_ERROR)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Customer"
+)]
+pub async fn get_customers(
+    State(state): State<AppState>,
+    caller: AuthenticatedUser,
+    Query(pagination): Query<PaginationRequest>
+) -> Result<Json<Vec<CustomerUserResponse>>, StatusCode> {
+    if !caller.is_manager() {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    user_role_controller::get_all::<Customer>(state.services.customer.as_ref(), pagination).await
+}
+
+
+/// Customer sign-up, open to anonymous callers.
+#[utoipa::path(
+    post,
+    path = "/api/customer",
+    params(
+        ("Idempotency-Key" = Option<String>, Header, description = "Retries with the same key replay the first response instead of creating again")
+    ),
+    request_body = CustomerCreateRequest,
+    responses(
+        (status = StatusCode::OK, description = "Customer successfully created", body = CustomerUserResponse),
+        (status = StatusCode::CONFLICT, description = "Username already taken"),
+        (status = StatusCode::UNPROCESSABLE_ENTITY, description = "Missing or invalid customer field"),
+        (status = StatusCode::INTERNAL_SERVER_ERROR)
+    ),
+    tag = "Customer"
+)]
+pub async fn post_customer(
+    State(state): State<AppState>,
+    Json(customer_create_request): Json<CustomerCreateRequest>
+) -> Result<Json<CustomerUserResponse>, StatusCode> {
+    let customer_create_command = CustomerCreateCommand {
+        first_name: customer_create_request.first_name,
+        last_name: customer_create_request.last_name,
+        username: customer_create_request.username,
+        password: customer_create_request.password,
+        address: customer_create_request.address,
+        country: customer_create_request.country,
+        phone: customer_create_request.phone,
+    };
+    user_role_controller::create::<Customer>(state.services.customer.as_ref(), customer_create_command).await
+}
+
+
+#[utoipa::path(
+    get,
+    path = "/api/customer/{user_id}",
+    responses(
+        (status = StatusCode::OK, description = "Customer found successfully", body = CustomerUserResponse),
+        (status = StatusCode::UNAUTHORIZED, description = "Missing or invalid bearer token"),
+        (status = StatusCode::FORBIDDEN, description = "Customers can only read themselves"),
+        (status = StatusCode::NOT_FOUND, description = "Customer not found"),
+        (status = StatusCode::INTERNAL_SERVER_ERROR)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Customer"
+)]
+pub async fn get_customer_by_id(
+    Path(user_id): Path<i64>,
+    State(state): State<AppState>,
+    caller: AuthenticatedUser
+) -> Result<Json<CustomerUserResponse>, StatusCode> {
+    if !caller.can_manage(user_id) {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    user_role_controller::get_by_id::<Customer>(state.services.customer.as_ref(), user_id).await
+}
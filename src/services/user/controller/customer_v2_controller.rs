@@ -0,0 +1,88 @@
+use axum::{Router, routing::{get}, extract::{Path, State}, Json, http::StatusCode};
+use axum::extract::Query;
+use crate::shared::state::AppState;
+use crate::services::user::controller::customer_controller;
+use crate::services::user::dto::user_dto::CustomerCreateRequest;
+use crate::services::user::dto::user_v2_dto::CustomerUserV2Response;
+use crate::shared::models::response::PaginationRequest;
+use crate::shared::security::authenticated_user::AuthenticatedUser;
+
+/// Routes of `/api/v2/customer`: the v1 handlers, with the customers they return as [`CustomerUserV2Response`].
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(get_customers).post(post_customer))
+        .route("/{user_id}", get(get_customer_by_id))
+}
+
+
+#[utoipa::path(
+    get,
+    path = "/api/v2/customer",
+    params(
+        PaginationRequest
+    ),
+    responses(
+        (status = StatusCode::OK, description = "List of Customer", body = Vec<CustomerUserV2Response>),
+        (status = StatusCode::UNAUTHORIZED, description = "Missing or invalid bearer token"),
+        (status = StatusCode::FORBIDDEN, description = "Only managers can list customers"),
+        (status = StatusCode::INTERNAL_SERVER_ERROR)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Customer"
+)]
+pub async fn get_customers(
+    state: State<AppState>,
+    caller: AuthenticatedUser,
+    pagination: Query<PaginationRequest>
+) -> Result<Json<Vec<CustomerUserV2Response>>, StatusCode> {
+    let Json(customers) = customer_controller::get_customers(state, caller, pagination).await?;
+    Ok(Json(customers.into_iter().map(CustomerUserV2Response::from).collect()))
+}
+
+
+/// Customer sign-up, open to anonymous callers.
+#[utoipa::path(
+    post,
+    path = "/api/v2/customer",
+    params(
+        ("Idempotency-Key" = Option<String>, Header, description = "Retries with the same key replay the first response instead of creating again")
+    ),
+    request_body = CustomerCreateRequest,
+    responses(
+        (status = StatusCode::OK, description = "Customer successfully created", body = CustomerUserV2Response),
+        (status = StatusCode::CONFLICT, description = "Username already taken"),
+        (status = StatusCode::UNPROCESSABLE_ENTITY, description = "Missing or invalid customer field"),
+        (status = StatusCode::INTERNAL_SERVER_ERROR)
+    ),
+    tag = "Customer"
+)]
+pub async fn post_customer(
+    state: State<AppState>,
+    customer_create_request: Json<CustomerCreateRequest>
+) -> Result<Json<CustomerUserV2Response>, StatusCode> {
+    let Json(customer) = customer_controller::post_customer(state, customer_create_request).await?;
+    Ok(Json(CustomerUserV2Response::from(customer)))
+}
+
+
+#[utoipa::path(
+    get,
+    path = "/api/v2/customer/{user_id}",
+    responses(
+        (status = StatusCode::OK, description = "Customer found successfully", body = CustomerUserV2Response),
+        (status = StatusCode::UNAUTHORIZED, description = "Missing or invalid bearer token"),
+        (status = StatusCode::FORBIDDEN, description = "Customers can only read themselves"),
+        (status = StatusCode::NOT_FOUND, description = "Customer not found"),
+        (status = StatusCode::INTERNAL_SERVER_ERROR)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Customer"
+)]
+pub async fn get_customer_by_id(
+    user_id: Path<i64>,
+    state: State<AppState>,
+    caller: AuthenticatedUser
+) -> Result<Json<CustomerUserV2Response>, StatusCode> {
+    let Json(customer) = customer_controller::get_customer_by_id(user_id, state, caller).await?;
+    Ok(Json(CustomerUserV2Response::from(customer)))
+}
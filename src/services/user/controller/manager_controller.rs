@@ -0,0 +1,108 @@
+use axum::{Router, routing::{get}, extract::{Path, State}, Json, http::StatusCode};
+use axum::extract::Query;
+use crate::shared::state::AppState;
+use crate::services::user::command::manager_command::ManagerCreateCommand;
+use crate::services::user::dto::user_dto::{ManagerCreateRequest, ManagerUserResponse};
+use crate::services::user::controller::user_role_controller;
+use crate::services::user::service::user_role_service::Manager;
+use crate::shared::models::response::PaginationRequest;
+use crate::shared::security::authenticated_user::AuthenticatedUser;
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(get_managers).post(post_manager))
+        .route("/{user_id}", get(get_manager_by_id))
+}
+
+
+#[utoipa::path(
+    get,
+    path = "/api/manager",
+    params(
+        PaginationRequest
+    ),
+    responses(
+        (status = StatusCode::OK, description = "List of Manager", body = Vec<ManagerUserResponse>),
+        (status = StatusCode::UNAUTHORIZED, description = "Missing or invalid bearer token"),
+        (status = StatusCode::FORBIDDEN, description = "Only managers can list managers"),
+        (status = StatusCode::INTERNAL_SERVER_ERROR)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Manager"
+)]
+pub async fn get_managers(
+    State(state): State<AppState>,
+    caller: AuthenticatedUser,
+    Query(pagination): Query<PaginationRequest>
+) -> Result<Json<Vec<ManagerUserResponse>>, StatusCode> {
+    if !caller.is_manager() {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    user_role_controller::get_all::<Manager>(state.services.manager.as_ref(), pagination).await
+}
+
+
+#[utoipa::path(
+    post,
+    path = "/api/manager",
+    params(
+        ("Idempotency-Key" = Option<String>, Header, description = "Retries with the same key replay the first response instead of creating again")
+    ),
+    request_body = ManagerCreateRequest,
+    responses(
+        (status = StatusCode::OK, description = "Manager successfully created", body = ManagerUserResponse),
+        (status = StatusCode::UNAUTHORIZED, description = "Missing or invalid bearer token"),
+        (status = StatusCode::FORBIDDEN, description = "Only managers can create managers"),
+        (status = StatusCode::CONFLICT, description = "Username already taken"),
+        (status = StatusCode::UNPROCESSABLE_ENTITY, description = "Missing or invalid manager field"),
+        (status = StatusCode::INTERNAL_SERVER_ERROR)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Manager"
+)]
+pub async fn post_manager(
+    State(state): State<AppState>,
+    caller: AuthenticatedUser,
+    Json(manager_create_request): Json<ManagerCreateRequest>
+) -> Result<Json<ManagerUserResponse>, StatusCode> {
+    if !caller.is_manager() {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    let manager_create_command = ManagerCreateCommand {
+        first_name: manager_create_request.first_name,
+        last_name: manager_create_request.last_name,
+        username: manager_create_request.username,
+        password: manager_create_request.password,
+        hired_date: manager_create_request.hired_date,
+        title: manager_create_request.title,
+    };
+    user_role_controller::create::<Manager>(state.services.manager.as_ref(), manager_create_command).await
+}
+
+
+#[utoipa::path(
+    get,
+    path = "/api/manager/{user_id}",
+    responses(
+        (status = StatusCode::OK, description = "Manager found successfully", body = ManagerUserResponse),
+        (status = StatusCode::UNAUTHORIZED, description = "Missing or invalid bearer token"),
+        (status = StatusCode::FORBIDDEN, description = "Only managers can read managers"),
+        (status = StatusCode::NOT_FOUND, description = "Manager not found"),
+        (status = StatusCode::INTERNAL_SERVER_ERROR)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Manager"
+)]
+pub async fn get_manager_by_id(
+    Path(user_id): Path<i64>,
+    State(state): State<AppState>,
+    caller: AuthenticatedUser
+) -> Result<Json<ManagerUserResponse>, StatusCode> {
+    if !caller.is_manager() {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    user_role_controller::get_by_id::<Manager>(state.services.manager.as_ref(), user_id).await
+}
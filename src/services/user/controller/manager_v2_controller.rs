@@ -0,0 +1,91 @@
+use axum::{Router, routing::{get}, extract::{Path, State}, Json, http::StatusCode};
+use axum::extract::Query;
+use crate::shared::state::AppState;
+use crate::services::user::controller::manager_controller;
+use crate::services::user::dto::user_dto::ManagerCreateRequest;
+use crate::services::user::dto::user_v2_dto::ManagerUserV2Response;
+use crate::shared::models::response::PaginationRequest;
+use crate::shared::security::authenticated_user::AuthenticatedUser;
+
+/// Routes of `/api/v2/manager`: the v1 handlers, with the managers they return as [`ManagerUserV2Response`].
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(get_managers).post(post_manager))
+        .route("/{user_id}", get(get_manager_by_id))
+}
+
+
+#[utoipa::path(
+    get,
+    path = "/api/v2/manager",
+    params(
+        PaginationRequest
+    ),
+    responses(
+        (status = StatusCode::OK, description = "List of Manager", body = Vec<ManagerUserV2Response>),
+        (status = StatusCode::UNAUTHORIZED, description = "Missing or invalid bearer token"),
+        (status = StatusCode::FORBIDDEN, description = "Only managers can list managers"),
+        (status = StatusCode::INTERNAL_SERVER_ERROR)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Manager"
+)]
+pub async fn get_managers(
+    state: State<AppState>,
+    caller: AuthenticatedUser,
+    pagination: Query<PaginationRequest>
+) -> Result<Json<Vec<ManagerUserV2Response>>, StatusCode> {
+    let Json(managers) = manager_controller::get_managers(state, caller, pagination).await?;
+    Ok(Json(managers.into_iter().map(ManagerUserV2Response::from).collect()))
+}
+
+
+#[utoipa::path(
+    post,
+    path = "/api/v2/manager",
+    params(
+        ("Idempotency-Key" = Option<String>, Header, description = "Retries with the same key replay the first response instead of creating again")
+    ),
+    request_body = ManagerCreateRequest,
+    responses(
+        (status = StatusCode::OK, description = "Manager successfully created", body = ManagerUserV2Response),
+        (status = StatusCode::UNAUTHORIZED, description = "Missing or invalid bearer token"),
+        (status = StatusCode::FORBIDDEN, description = "Only managers can create managers"),
+        (status = StatusCode::CONFLICT, description = "Username already taken"),
+        (status = StatusCode::UNPROCESSABLE_ENTITY, description = "Missing or invalid manager field"),
+        (status = StatusCode::INTERNAL_SERVER_ERROR)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Manager"
+)]
+pub async fn post_manager(
+    state: State<AppState>,
+    caller: AuthenticatedUser,
+    manager_create_request: Json<ManagerCreateRequest>
+) -> Result<Json<ManagerUserV2Response>, StatusCode> {
+    let Json(manager) = manager_controller::post_manager(state, caller, manager_create_request).await?;
+    Ok(Json(ManagerUserV2Response::from(manager)))
+}
+
+
+#[utoipa::path(
+    get,
+    path = "/api/v2/manager/{user_id}",
+    responses(
+        (status = StatusCode::OK, description = "Manager found successfully", body = ManagerUserV2Response),
+        (status = StatusCode::UNAUTHORIZED, description = "Missing or invalid bearer token"),
+        (status = StatusCode::FORBIDDEN, description = "Only managers can read managers"),
+        (status = StatusCode::NOT_FOUND, description = "Manager not found"),
+        (status = StatusCode::INTERNAL_SERVER_ERROR)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Manager"
+)]
+pub async fn get_manager_by_id(
+    user_id: Path<i64>,
+    state: State<AppState>,
+    caller: AuthenticatedUser
+) -> Result<Json<ManagerUserV2Response>, StatusCode> {
+    let Json(manager) = manager_controller::get_manager_by_id(user_id, state, caller).await?;
+    Ok(Json(ManagerUserV2Response::from(manager)))
+}
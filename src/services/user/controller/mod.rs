@@ -1,3 +1,9 @@
 pub mod user_auth_controller;
 pub mod user_status_controller;
-pub mod user_controller;
\ No newline at end of file
+pub mod user_controller;
+pub mod manager_controller;
+pub mod customer_controller;
+pub mod user_role_controller;
+pub mod user_v2_controller;
+pub mod manager_v2_controller;
+pub mod customer_v2_controller;
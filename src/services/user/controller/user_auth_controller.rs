@@ -1,13 +1,14 @@
-use axum::{Router, routing::{get}, extract::{Path, State}, Json, http::StatusCode};
+use axum::{Router, routing::{get}, extract::{Path, State}, Json, http::{HeaderMap, StatusCode}};
 use crate::shared::state::AppState;
-use crate::services::user::command::user_auth_command::{UserAuthCreateCommand, UserAuthDeleteCommand, UserAuthGetCommand, UserAuthListCommand, UserAuthUpdateCommand};
-use crate::services::user::dto::user_auth_dto::{UserAuthCreateRequest, UserAuthResponse, UserAuthUpdateRequest};
-use crate::services::user::service::user_auth_service::{UserAuthService, UserAuthServiceInterface};
+use crate::shared::errors::status_code_of;
+use crate::shared::http::etag::{etag_headers, parse_if_match};
+use crate::services::user::command::user_auth_command::{UserAuthCreateCommand, UserAuthDeleteCommand, UserAuthGetCommand, UserAuthListCommand, UserAuthPatchCommand, UserAuthUpdateCommand};
+use crate::services::user::dto::user_auth_dto::{UserAuthCreateRequest, UserAuthPatchRequest, UserAuthResponse, UserAuthUpdateRequest};
 
 pub fn routes() -> Router<AppState> {
     Router::new()
         .route("/", get(get_user_auths).post(post_user_auth))
-        .route("/{user_auth_id}", get(get_user_auth_by_id).put(put_user_auth).delete(delete_user_auth))
+        .route("/{user_auth_id}", get(get_user_auth_by_id).put(put_user_auth).patch(patch_user_auth).delete(delete_user_auth))
 }
 
 
@@ -22,7 +23,7 @@ pub fn routes() -> Router<AppState> {
 )]
 pub async fn get_user_auths(State(state): State<AppState>) -> Result<Json<Vec<UserAuthResponse>>, StatusCode> {
     let user_auth_list_command = UserAuthListCommand { pagination: None };
-    let user_auth_service = UserAuthService::from_app_state(&state);
+    let user_auth_service = &state.services.user_auth;
     let user_auths = user_auth_service.get_all(user_auth_list_command).await;
     match user_auths {
         Ok(user_auths) => Ok(Json(user_auths)),
@@ -34,6 +35,9 @@ pub async fn get_user_auths(State(state): State<AppState>) -> Result<Json<Vec<Us
 #[utoipa::path(
     post,
     path = "/api/user/auth",
+    params(
+        ("Idempotency-Key" = Option<String>, Header, description = "Retries with the same key replay the first response instead of creating again")
+    ),
     responses(
         (status = StatusCode::OK, description = "User Auth successfully created", body = UserAuthResponse),
         (status = StatusCode::BAD_REQUEST),
@@ -46,7 +50,7 @@ pub async fn post_user_auth(
     Json(user_auth_create_request): Json<UserAuthCreateRequest>
 ) -> Result<Json<UserAuthResponse>, StatusCode> {
     let user_auth_create_command = UserAuthCreateCommand {name: user_auth_create_request.name, description: user_auth_create_request.description};
-    let user_auth_service = UserAuthService::from_app_state(&state);
+    let user_auth_service = &state.services.user_auth;
     let user_auth = user_auth_service.create(user_auth_create_command).await;
     match user_auth {
         Ok(user_auth) => Ok(Json(user_auth)),
@@ -59,7 +63,8 @@ pub async fn post_user_auth(
     get,
     path = "/api/user/auth/{user_auth_id}",
     responses(
-        (status = StatusCode::OK, description = "User Auth found successfully", body = UserAuthResponse),
+        (status = StatusCode::OK, description = "User Auth found successfully", body = UserAuthResponse,
+            headers(("ETag" = String, description = "Current version of the user auth"))),
         (status = StatusCode::NOT_FOUND, description = "User Auth not found"),
         (status = StatusCode::INTERNAL_SERVER_ERROR)
     ),
@@ -68,14 +73,14 @@ pub async fn post_user_auth(
 pub async fn get_user_auth_by_id(
     Path(user_auth_id): Path<i64>,
     State(state): State<AppState>
-) -> Result<Json<UserAuthResponse>, StatusCode> {
+) -> Result<(HeaderMap, Json<UserAuthResponse>), StatusCode> {
     let user_auth_get_command = UserAuthGetCommand{ id: user_auth_id };
-    let user_auth_service = UserAuthService::from_app_state(&state);
+    let user_auth_service = &state.services.user_auth;
     let user_auth = user_auth_service.get(user_auth_get_command).await;
     match user_auth {
         Ok(user_auth) => {
             match user_auth {
-                Some(user_auth) => Ok(Json(user_auth)),
+                Some(user_auth) => Ok((etag_headers(user_auth.version), Json(user_auth))),
                 None => Err(StatusCode::NOT_FOUND),
             }
         },
@@ -87,9 +92,15 @@ pub async fn get_user_auth_by_id(
 #[utoipa::path(
     put,
     path = "/api/user/auth/{user_auth_id}",
+    params(
+        ("If-Match" = String, Header, description = "ETag of the user auth as last read by the client")
+    ),
     responses(
-        (status = StatusCode::OK, description = "User Auth successfully modified", body = UserAuthResponse),
+        (status = StatusCode::OK, description = "User Auth successfully modified", body = UserAuthResponse,
+            headers(("ETag" = String, description = "New version of the user auth"))),
         (status = StatusCode::NOT_FOUND, description = "User Auth not found"),
+        (status = StatusCode::PRECONDITION_FAILED, description = "User Auth was modified since it was read"),
+        (status = StatusCode::PRECONDITION_REQUIRED, description = "Missing If-Match header"),
         (status = StatusCode::INTERNAL_SERVER_ERROR)
     ),
     tag = "UserAuth"
@@ -97,18 +108,61 @@ pub async fn get_user_auth_by_id(
 pub async fn put_user_auth(
     Path(user_auth_id): Path<i64>,
     State(state): State<AppState>,
+    headers: HeaderMap,
     Json(user_auth_update_request): Json<UserAuthUpdateRequest>
-) -> Result<Json<UserAuthResponse>, StatusCode> {
+) -> Result<(HeaderMap, Json<UserAuthResponse>), StatusCode> {
+    let if_match = parse_if_match(&headers)?;
     let user_auth_update_command = UserAuthUpdateCommand {
         id: user_auth_id,
+        if_match,
         name: user_auth_update_request.name,
         description: user_auth_update_request.description
     };
-    let user_auth_service = UserAuthService::from_app_state(&state);
+    let user_auth_service = &state.services.user_auth;
     let user_auth = user_auth_service.update(user_auth_update_command).await;
     match user_auth {
-        Ok(user_auth) => Ok(Json(user_auth)),
-        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+        Ok(user_auth) => Ok((etag_headers(user_auth.version), Json(user_auth))),
+        Err(e) => Err(status_code_of(&e)),
+    }
+}
+
+
+#[utoipa::path(
+    patch,
+    path = "/api/user/auth/{user_auth_id}",
+    params(
+        ("If-Match" = String, Header, description = "ETag of the user auth as last read by the client")
+    ),
+    request_body(content = UserAuthPatchRequest, content_type = "application/merge-patch+json"),
+    responses(
+        (status = StatusCode::OK, description = "User Auth successfully modified", body = UserAuthResponse,
+            headers(("ETag" = String, description = "New version of the user auth"))),
+        (status = StatusCode::NOT_FOUND, description = "User Auth not found"),
+        (status = StatusCode::PRECONDITION_FAILED, description = "User Auth was modified since it was read"),
+        (status = StatusCode::PRECONDITION_REQUIRED, description = "Missing If-Match header"),
+        (status = StatusCode::UNPROCESSABLE_ENTITY, description = "Invalid patch document"),
+        (status = StatusCode::INTERNAL_SERVER_ERROR)
+    ),
+    tag = "UserAuth"
+)]
+pub async fn patch_user_auth(
+    Path(user_auth_id): Path<i64>,
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(user_auth_patch_request): Json<UserAuthPatchRequest>
+) -> Result<(HeaderMap, Json<UserAuthResponse>), StatusCode> {
+    let if_match = parse_if_match(&headers)?;
+    let user_auth_patch_command = UserAuthPatchCommand {
+        id: user_auth_id,
+        if_match,
+        name: user_auth_patch_request.name,
+        description: user_auth_patch_request.description
+    };
+    let user_auth_service = &state.services.user_auth;
+    let user_auth = user_auth_service.patch(user_auth_patch_command).await;
+    match user_auth {
+        Ok(user_auth) => Ok((etag_headers(user_auth.version), Json(user_auth))),
+        Err(e) => Err(status_code_of(&e)),
     }
 }
 
@@ -116,23 +170,30 @@ pub async fn put_user_auth(
 #[utoipa::path(
     delete,
     path = "/api/user/auth/{user_auth_id}",
+    params(
+        ("If-Match" = String, Header, description = "ETag of the user auth as last read by the client")
+    ),
     responses(
         (status = StatusCode::OK, description = "User Auth successfully deleted"),
         (status = StatusCode::NOT_FOUND, description = "User Auth not found"),
+        (status = StatusCode::PRECONDITION_FAILED, description = "User Auth was modified since it was read"),
+        (status = StatusCode::PRECONDITION_REQUIRED, description = "Missing If-Match header"),
         (status = StatusCode::INTERNAL_SERVER_ERROR)
     ),
     tag = "UserAuth"
 )]
 pub async fn delete_user_auth(
     Path(user_auth_id): Path<i64>,
-    State(state): State<AppState>
+    State(state): State<AppState>,
+    headers: HeaderMap
 ) -> Result<StatusCode, StatusCode> {
-    let user_auth_delete_command = UserAuthDeleteCommand{ id: user_auth_id };
-    let user_auth_service = UserAuthService::from_app_state(&state);
+    let if_match = parse_if_match(&headers)?;
+    let user_auth_delete_command = UserAuthDeleteCommand{ id: user_auth_id, if_match };
+    let user_auth_service = &state.services.user_auth;
     let result = user_auth_service.delete(user_auth_delete_command).await;
     match result {
         Ok(_) => Ok(StatusCode::OK),
-        Err(_) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(status_code_of(&e)),
     }
 }
 
@@ -1,15 +1,24 @@
-use axum::{Router, routing::{get}, extract::{Path, State}, Json, http::StatusCode};
-use axum::extract::Query;
+use axum::{Router, routing::{get, post, put}, extract::{Path, State}, Json, http::{HeaderMap, StatusCode}};
+use axum::extract::{DefaultBodyLimit, Multipart, Query};
 use crate::shared::state::AppState;
-use crate::services::user::command::user_command::{UserCreateCommand, UserDeleteCommand, UserGetByCountryCommand, UserGetBySearchCommand, UserGetByTitleCommand, UserGetByUsernameCommand, UserGetCommand, UserListCommand, UserUpdateCommand, UserUpdatePasswordCommand};
-use crate::services::user::dto::user_dto::{SearchCountryRequest, SearchTitleRequest, UserCreateRequest, UserResponse, UserUpdateRequest};
-use crate::services::user::service::user_service::{UserService, UserServiceInterface};
+use crate::services::user::command::user_command::{UserCreateCommand, UserDeleteCommand, UserGetByCountryCommand, UserGetByIdsCommand, UserGetBySearchCommand, UserGetByTitleCommand, UserGetCommand, UserListCommand, UserPatchCommand, UserRestoreCommand, UserUpdateCommand, UserUpdatePasswordCommand, UserUpdateProfilePicUrlCommand, UserUpdateStatusCommand, UserUploadProfilePicCommand};
+use crate::services::user::dto::user_dto::{SearchCountryRequest, SearchTitleRequest, UserCreateRequest, UserPatchRequest, UserProfilePicUploadRequest, UserResponse, UserUpdatePasswordRequest, UserUpdateProfilePicUrlRequest, UserUpdateRequest, UserUpdateStatusRequest};
+use crate::shared::errors::status_code_of;
+use crate::shared::http::etag::{etag_headers, parse_if_match};
 use crate::shared::models::response::PaginationRequest;
+use crate::shared::models::utils_model::IdListModel;
+use crate::shared::security::authenticated_user::AuthenticatedUser;
 
 pub fn routes() -> Router<AppState> {
     Router::new()
         .route("/", get(get_users).post(post_user))
-        .route("/{user_id}", get(get_user_by_id).put(put_user).delete(delete_user))
+        .route("/batch", post(get_users_by_ids))
+        .route("/{user_id}", get(get_user_by_id).put(put_user).patch(patch_user).delete(delete_user))
+        .route("/{user_id}/status", put(update_user_status))
+        // the upload size is enforced by the handler from the storage configuration
+        .route("/{user_id}/profile-pic", put(update_user_profile_pic).post(upload_user_profile_pic).layer(DefaultBodyLimit::disable()))
+        .route("/{user_id}/password", put(update_user_password))
+        .route("/{user_id}/restore", post(restore_user))
 }
 
 
@@ -34,7 +43,7 @@ pub async fn get_users(
     Query(search_title): Query<SearchTitleRequest>
 ) -> Result<Json<Vec<UserResponse>>, StatusCode> {
     let user_list_command = UserListCommand { pagination: Some(pagination) };
-    let user_service = UserService::from_app_state(&state);
+    let user_service = &state.services.user;
 
     if search_country.country.is_some() && search_title.title.is_some() {
         let user_get_by_search = UserGetBySearchCommand{ country: search_country.country, title: search_title.title };
@@ -42,26 +51,26 @@ pub async fn get_users(
         let users = user_service.get_by_search(user_get_by_search, user_list_command).await;
 
         match users {
-            Ok(users) => return Ok(Json(users)),
-            Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR),
+            Ok(users) => Ok(Json(users)),
+            Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
         }
-    } else if search_country.country.is_some() {
-        let user_get_by_country_command = UserGetByCountryCommand{ country: search_country.country.unwrap() };
+    } else if let Some(country) = search_country.country {
+        let user_get_by_country_command = UserGetByCountryCommand{ country };
 
         let users = user_service.get_by_country(user_get_by_country_command, user_list_command).await;
 
         match users {
-            Ok(users) => return Ok(Json(users)),
-            Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR),
+            Ok(users) => Ok(Json(users)),
+            Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
         }
-    } else if search_title.title.is_some() {
-        let user_get_by_title_command = UserGetByTitleCommand{ title: search_title.title.unwrap() };
+    } else if let Some(title) = search_title.title {
+        let user_get_by_title_command = UserGetByTitleCommand{ title };
 
         let users = user_service.get_by_title(user_get_by_title_command, user_list_command).await;
 
         match users {
-            Ok(users) => return Ok(Json(users)),
-            Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR),
+            Ok(users) => Ok(Json(users)),
+            Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
         }
     } else {
         let users = user_service.get_all(user_list_command).await;
@@ -77,9 +86,14 @@ pub async fn get_users(
 #[utoipa::path(
     post,
     path = "/api/user",
+    params(
+        ("Idempotency-Key" = Option<String>, Header, description = "Retries with the same key replay the first response instead of creating again")
+    ),
     responses(
         (status = StatusCode::OK, description = "User successfully created", body = UserResponse),
         (status = StatusCode::BAD_REQUEST),
+        (status = StatusCode::CONFLICT, description = "Username already taken or a request with the same Idempotency-Key is still running"),
+        (status = StatusCode::UNPROCESSABLE_ENTITY, description = "Password too short or Idempotency-Key already used with another request"),
         (status = StatusCode::INTERNAL_SERVER_ERROR)
     ),
     tag = "User"
@@ -101,11 +115,36 @@ pub async fn post_user(
         country: user_create_request.country,
         phone: user_create_request.phone,
     };
-    let user_service = UserService::from_app_state(&state);
+    let user_service = &state.services.user;
     let user = user_service.create(user_create_command).await;
     match user {
         Ok(user) => Ok(Json(user)),
-        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+        Err(e) => Err(status_code_of(&e)),
+    }
+}
+
+
+#[utoipa::path(
+    post,
+    path = "/api/user/batch",
+    request_body = IdListModel,
+    responses(
+        (status = StatusCode::OK, description = "Users of the ids in the same order, unknown ids skipped", body = Vec<UserResponse>),
+        (status = StatusCode::UNPROCESSABLE_ENTITY, description = "More than 100 ids"),
+        (status = StatusCode::INTERNAL_SERVER_ERROR)
+    ),
+    tag = "User"
+)]
+pub async fn get_users_by_ids(
+    State(state): State<AppState>,
+    Json(id_list): Json<IdListModel>
+) -> Result<Json<Vec<UserResponse>>, StatusCode> {
+    let user_get_by_ids_command = UserGetByIdsCommand { ids: id_list.ids };
+    let user_service = &state.services.user;
+    let users = user_service.get_by_ids(user_get_by_ids_command).await;
+    match users {
+        Ok(users) => Ok(Json(users)),
+        Err(e) => Err(status_code_of(&e)),
     }
 }
 
@@ -114,7 +153,8 @@ pub async fn post_user(
     get,
     path = "/api/user/{user_id}",
     responses(
-        (status = StatusCode::OK, description = "User found successfully", body = UserResponse),
+        (status = StatusCode::OK, description = "User found successfully", body = UserResponse,
+            headers(("ETag" = String, description = "Current version of the user"))),
         (status = StatusCode::NOT_FOUND, description = "User not found"),
         (status = StatusCode::INTERNAL_SERVER_ERROR)
     ),
@@ -123,14 +163,14 @@ pub async fn post_user(
 pub async fn get_user_by_id(
     Path(user_id): Path<i64>,
     State(state): State<AppState>
-) -> Result<Json<UserResponse>, StatusCode> {
+) -> Result<(HeaderMap, Json<UserResponse>), StatusCode> {
     let user_get_command = UserGetCommand{ id: user_id };
-    let user_service = UserService::from_app_state(&state);
+    let user_service = &state.services.user;
     let user = user_service.get(user_get_command).await;
     match user {
         Ok(user) => {
             match user {
-                Some(user) => Ok(Json(user)),
+                Some(user) => Ok((etag_headers(user.version), Json(user))),
                 None => Err(StatusCode::NOT_FOUND),
             }
         },
@@ -142,8 +182,15 @@ pub async fn get_user_by_id(
 #[utoipa::path(
     put,
     path = "/api/user/{user_id}",
+    params(
+        ("If-Match" = String, Header, description = "ETag of the user as last read by the client")
+    ),
     responses(
-        (status = StatusCode::OK, description = "User not found"),
+        (status = StatusCode::OK, description = "User successfully modified", body = UserResponse,
+            headers(("ETag" = String, description = "New version of the user"))),
+        (status = StatusCode::NOT_FOUND, description = "User not found"),
+        (status = StatusCode::PRECONDITION_FAILED, description = "User was modified since it was read"),
+        (status = StatusCode::PRECONDITION_REQUIRED, description = "Missing If-Match header"),
         (status = StatusCode::INTERNAL_SERVER_ERROR)
     ),
     tag = "User"
@@ -151,10 +198,13 @@ pub async fn get_user_by_id(
 pub async fn put_user(
     Path(user_id): Path<i64>,
     State(state): State<AppState>,
+    headers: HeaderMap,
     Json(user_update_request): Json<UserUpdateRequest>
-) -> Result<Json<UserResponse>, StatusCode> {
+) -> Result<(HeaderMap, Json<UserResponse>), StatusCode> {
+    let if_match = parse_if_match(&headers)?;
     let user_update_command = UserUpdateCommand {
         id: user_id,
+        if_match,
         first_name: user_update_request.first_name,
         last_name: user_update_request.last_name,
         hired_date: user_update_request.hired_date,
@@ -163,16 +213,66 @@ pub async fn put_user(
         country: user_update_request.country,
         phone: user_update_request.phone,
     };
-    let user_service = UserService::from_app_state(&state);
+    let user_service = &state.services.user;
     let user = user_service.update(user_update_command).await;
     match user {
         Ok(user) => {
             match user {
-                Some(user) => Ok(Json(user)),
+                Some(user) => Ok((etag_headers(user.version), Json(user))),
                 None => Err(StatusCode::NOT_FOUND),
             }
         },
-        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+        Err(e) => Err(status_code_of(&e)),
+    }
+}
+
+
+#[utoipa::path(
+    patch,
+    path = "/api/user/{user_id}",
+    params(
+        ("If-Match" = String, Header, description = "ETag of the user as last read by the client")
+    ),
+    request_body(content = UserPatchRequest, content_type = "application/merge-patch+json"),
+    responses(
+        (status = StatusCode::OK, description = "User successfully modified", body = UserResponse,
+            headers(("ETag" = String, description = "New version of the user"))),
+        (status = StatusCode::NOT_FOUND, description = "User not found"),
+        (status = StatusCode::PRECONDITION_FAILED, description = "User was modified since it was read"),
+        (status = StatusCode::PRECONDITION_REQUIRED, description = "Missing If-Match header"),
+        (status = StatusCode::UNPROCESSABLE_ENTITY, description = "Invalid patch document"),
+        (status = StatusCode::INTERNAL_SERVER_ERROR)
+    ),
+    tag = "User"
+)]
+pub async fn patch_user(
+    Path(user_id): Path<i64>,
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(user_patch_request): Json<UserPatchRequest>
+) -> Result<(HeaderMap, Json<UserResponse>), StatusCode> {
+    let if_match = parse_if_match(&headers)?;
+    let user_patch_command = UserPatchCommand {
+        id: user_id,
+        if_match,
+        first_name: user_patch_request.first_name,
+        last_name: user_patch_request.last_name,
+        hired_date: user_patch_request.hired_date,
+        title: user_patch_request.title,
+        address: user_patch_request.address,
+        country: user_patch_request.country,
+        phone: user_patch_request.phone,
+    };
+    let user_service = &state.services.user;
+    let user = user_service.patch(user_patch_command).await;
+    match user {
+        Ok(user) => {
+            match user {
+                Some(user) => Ok((etag_headers(user.version), Json(user))),
+                None => Err(StatusCode::NOT_FOUND),
+            }
+        },
+        Err(e) => Err(status_code_of(&e)),
     }
 }
 
@@ -180,23 +280,267 @@ pub async fn put_user(
 #[utoipa::path(
     delete,
     path = "/api/user/{user_id}",
+    params(
+        ("If-Match" = String, Header, description = "ETag of the user as last read by the client")
+    ),
     responses(
-        (status = StatusCode::OK, description = "User successfully deleted"),
+        (status = StatusCode::OK, description = "User moved to the deleted status, until it is restored"),
         (status = StatusCode::NOT_FOUND, description = "User not found"),
+        (status = StatusCode::CONFLICT, description = "User is already deleted"),
+        (status = StatusCode::PRECONDITION_FAILED, description = "User was modified since it was read"),
+        (status = StatusCode::PRECONDITION_REQUIRED, description = "Missing If-Match header"),
         (status = StatusCode::INTERNAL_SERVER_ERROR)
     ),
     tag = "User"
 )]
 pub async fn delete_user(
     Path(user_id): Path<i64>,
-    State(state): State<AppState>
+    State(state): State<AppState>,
+    headers: HeaderMap
 ) -> Result<StatusCode, StatusCode> {
-    let user_delete_command = UserDeleteCommand{ id: user_id };
-    let user_service = UserService::from_app_state(&state);
+    let if_match = parse_if_match(&headers)?;
+    let user_delete_command = UserDeleteCommand{ id: user_id, if_match };
+    let user_service = &state.services.user;
     let result = user_service.delete(user_delete_command).await;
     match result {
         Ok(_) => Ok(StatusCode::OK),
-        Err(_) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(status_code_of(&e)),
+    }
+}
+
+
+#[utoipa::path(
+    put,
+    path = "/api/user/{user_id}/status",
+    request_body = UserUpdateStatusRequest,
+    responses(
+        (status = StatusCode::OK, description = "User status successfully changed", body = UserResponse,
+            headers(("ETag" = String, description = "New version of the user"))),
+        (status = StatusCode::UNAUTHORIZED, description = "Missing or invalid bearer token"),
+        (status = StatusCode::FORBIDDEN, description = "Only managers can change the status of a user"),
+        (status = StatusCode::NOT_FOUND, description = "User not found"),
+        (status = StatusCode::CONFLICT, description = "Deleted users must be restored first"),
+        (status = StatusCode::UNPROCESSABLE_ENTITY, description = "Unknown user status or the deleted status"),
+        (status = StatusCode::INTERNAL_SERVER_ERROR)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "User"
+)]
+pub async fn update_user_status(
+    Path(user_id): Path<i64>,
+    State(state): State<AppState>,
+    caller: AuthenticatedUser,
+    Json(user_update_status_request): Json<UserUpdateStatusRequest>
+) -> Result<(HeaderMap, Json<UserResponse>), StatusCode> {
+    if !caller.is_manager() {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    let user_update_status_command = UserUpdateStatusCommand {
+        id: user_id,
+        status: user_update_status_request.status,
+    };
+    let user_service = &state.services.user;
+    let user = user_service.update_status(user_update_status_command).await;
+    match user {
+        Ok(user) => {
+            match user {
+                Some(user) => Ok((etag_headers(user.version), Json(user))),
+                None => Err(StatusCode::NOT_FOUND),
+            }
+        },
+        Err(e) => Err(status_code_of(&e)),
+    }
+}
+
+
+#[utoipa::path(
+    put,
+    path = "/api/user/{user_id}/profile-pic",
+    request_body = UserUpdateProfilePicUrlRequest,
+    responses(
+        (status = StatusCode::OK, description = "Profile picture successfully changed", body = UserResponse,
+            headers(("ETag" = String, description = "New version of the user"))),
+        (status = StatusCode::UNAUTHORIZED, description = "Missing or invalid bearer token"),
+        (status = StatusCode::FORBIDDEN, description = "Users can only change their own profile picture"),
+        (status = StatusCode::NOT_FOUND, description = "User not found"),
+        (status = StatusCode::INTERNAL_SERVER_ERROR)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "User"
+)]
+pub async fn update_user_profile_pic(
+    Path(user_id): Path<i64>,
+    State(state): State<AppState>,
+    caller: AuthenticatedUser,
+    Json(user_update_profile_pic_url_request): Json<UserUpdateProfilePicUrlRequest>
+) -> Result<(HeaderMap, Json<UserResponse>), StatusCode> {
+    if !caller.can_manage(user_id) {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    let user_update_profile_pic_url_command = UserUpdateProfilePicUrlCommand {
+        id: user_id,
+        profile_pic_url: user_update_profile_pic_url_request.profile_pic_url,
+    };
+    let user_service = &state.services.user;
+    let user = user_service.update_profile_pic_url(user_update_profile_pic_url_command).await;
+    match user {
+        Ok(user) => {
+            match user {
+                Some(user) => Ok((etag_headers(user.version), Json(user))),
+                None => Err(StatusCode::NOT_FOUND),
+            }
+        },
+        Err(e) => Err(status_code_of(&e)),
+    }
+}
+
+
+#[utoipa::path(
+    post,
+    path = "/api/user/{user_id}/profile-pic",
+    request_body(content = UserProfilePicUploadRequest, content_type = "multipart/form-data"),
+    responses(
+        (status = StatusCode::OK, description = "Profile picture successfully uploaded", body = UserResponse,
+            headers(("ETag" = String, description = "New version of the user"))),
+        (status = StatusCode::BAD_REQUEST, description = "Malformed multipart body or missing file part"),
+        (status = StatusCode::UNAUTHORIZED, description = "Missing or invalid bearer token"),
+        (status = StatusCode::FORBIDDEN, description = "Users can only change their own profile picture"),
+        (status = StatusCode::NOT_FOUND, description = "User not found"),
+        (status = StatusCode::PAYLOAD_TOO_LARGE, description = "Picture exceeds the upload limit"),
+        (status = StatusCode::UNSUPPORTED_MEDIA_TYPE, description = "File is not a PNG, JPEG, GIF or WebP image"),
+        (status = StatusCode::UNPROCESSABLE_ENTITY, description = "Image cannot be decoded"),
+        (status = StatusCode::INTERNAL_SERVER_ERROR)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "User"
+)]
+pub async fn upload_user_profile_pic(
+    Path(user_id): Path<i64>,
+    State(state): State<AppState>,
+    caller: AuthenticatedUser,
+    mut multipart: Multipart
+) -> Result<(HeaderMap, Json<UserResponse>), StatusCode> {
+    if !caller.can_manage(user_id) {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    let max_upload_bytes = state.config.storage.max_upload_bytes;
+    let mut content = None;
+    while let Some(mut field) = multipart.next_field().await.map_err(|_| StatusCode::BAD_REQUEST)? {
+        if field.name() != Some("file") {
+            continue;
+        }
+
+        let mut bytes = Vec::new();
+        while let Some(chunk) = field.chunk().await.map_err(|_| StatusCode::BAD_REQUEST)? {
+            if bytes.len() + chunk.len() > max_upload_bytes {
+                return Err(StatusCode::PAYLOAD_TOO_LARGE);
+            }
+            bytes.extend_from_slice(&chunk);
+        }
+        content = Some(bytes);
+        break;
+    }
+
+    let user_upload_profile_pic_command = UserUploadProfilePicCommand {
+        id: user_id,
+        content: content.ok_or(StatusCode::BAD_REQUEST)?,
+    };
+    let user_service = &state.services.user;
+    let user = user_service.upload_profile_pic(user_upload_profile_pic_command).await;
+    match user {
+        Ok(user) => {
+            match user {
+                Some(user) => Ok((etag_headers(user.version), Json(user))),
+                None => Err(StatusCode::NOT_FOUND),
+            }
+        },
+        Err(e) => Err(status_code_of(&e)),
+    }
+}
+
+
+#[utoipa::path(
+    put,
+    path = "/api/user/{user_id}/password",
+    request_body = UserUpdatePasswordRequest,
+    responses(
+        (status = StatusCode::NO_CONTENT, description = "Password successfully changed"),
+        (status = StatusCode::UNAUTHORIZED, description = "Missing or invalid bearer token"),
+        (status = StatusCode::FORBIDDEN, description = "Not allowed to change this password or current_password does not match"),
+        (status = StatusCode::NOT_FOUND, description = "User not found"),
+        (status = StatusCode::UNPROCESSABLE_ENTITY, description = "Missing current_password or password too short"),
+        (status = StatusCode::INTERNAL_SERVER_ERROR)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "User"
+)]
+pub async fn update_user_password(
+    Path(user_id): Path<i64>,
+    State(state): State<AppState>,
+    caller: AuthenticatedUser,
+    Json(user_update_password_request): Json<UserUpdatePasswordRequest>
+) -> Result<StatusCode, StatusCode> {
+    if !caller.can_manage(user_id) {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    let user_update_password_command = UserUpdatePasswordCommand {
+        id: user_id,
+        caller_id: caller.id,
+        current_password: user_update_password_request.current_password,
+        password: user_update_password_request.password,
+    };
+    let user_service = &state.services.user;
+    let user = user_service.update_password(user_update_password_command).await;
+    match user {
+        Ok(user) => {
+            match user {
+                Some(_) => Ok(StatusCode::NO_CONTENT),
+                None => Err(StatusCode::NOT_FOUND),
+            }
+        },
+        Err(e) => Err(status_code_of(&e)),
     }
 }
 
+
+#[utoipa::path(
+    post,
+    path = "/api/user/{user_id}/restore",
+    responses(
+        (status = StatusCode::OK, description = "User successfully restored", body = UserResponse,
+            headers(("ETag" = String, description = "New version of the user"))),
+        (status = StatusCode::UNAUTHORIZED, description = "Missing or invalid bearer token"),
+        (status = StatusCode::FORBIDDEN, description = "Only managers can restore users"),
+        (status = StatusCode::NOT_FOUND, description = "User not found"),
+        (status = StatusCode::CONFLICT, description = "User is not deleted"),
+        (status = StatusCode::INTERNAL_SERVER_ERROR)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "User"
+)]
+pub async fn restore_user(
+    Path(user_id): Path<i64>,
+    State(state): State<AppState>,
+    caller: AuthenticatedUser
+) -> Result<(HeaderMap, Json<UserResponse>), StatusCode> {
+    if !caller.is_manager() {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    let user_restore_command = UserRestoreCommand { id: user_id };
+    let user_service = &state.services.user;
+    let user = user_service.restore(user_restore_command).await;
+    match user {
+        Ok(user) => {
+            match user {
+                Some(user) => Ok((etag_headers(user.version), Json(user))),
+                None => Err(StatusCode::NOT_FOUND),
+            }
+        },
+        Err(e) => Err(status_code_of(&e)),
+    }
+}
@@ -0,0 +1,45 @@
+use axum::{Json, http::StatusCode};
+use crate::services::user::command::user_command::{UserGetCommand, UserListCommand};
+use crate::services::user::service::user_role_service::{UserRole, UserRoleServiceInterface};
+use crate::shared::errors::status_code_of;
+use crate::shared::models::response::PaginationRequest;
+
+// Handler bodies shared by the manager and customer controllers, which only add their own
+// authorization and OpenAPI documentation.
+
+
+pub async fn get_all<R: UserRole>(
+    service: &dyn UserRoleServiceInterface<R>,
+    pagination: PaginationRequest
+) -> Result<Json<Vec<R::Response>>, StatusCode> {
+    let user_list_command = UserListCommand { pagination: Some(pagination) };
+    let users = service.get_all(user_list_command).await;
+    match users {
+        Ok(users) => Ok(Json(users)),
+        Err(e) => Err(status_code_of(&e)),
+    }
+}
+
+pub async fn create<R: UserRole>(
+    service: &dyn UserRoleServiceInterface<R>,
+    create_command: R::CreateCommand
+) -> Result<Json<R::Response>, StatusCode> {
+    let user = service.create(create_command).await;
+    match user {
+        Ok(user) => Ok(Json(user)),
+        Err(e) => Err(status_code_of(&e)),
+    }
+}
+
+pub async fn get_by_id<R: UserRole>(
+    service: &dyn UserRoleServiceInterface<R>,
+    user_id: i64
+) -> Result<Json<R::Response>, StatusCode> {
+    let user_get_command = UserGetCommand { id: user_id };
+    let user = service.get(user_get_command).await;
+    match user {
+        Ok(Some(user)) => Ok(Json(user)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(status_code_of(&e)),
+    }
+}
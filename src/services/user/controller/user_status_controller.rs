@@ -1,13 +1,14 @@
-use axum::{Router, routing::{get}, extract::{Path, State}, Json, http::StatusCode};
+use axum::{Router, routing::{get}, extract::{Path, State}, Json, http::{HeaderMap, StatusCode}};
 use crate::shared::state::AppState;
-use crate::services::user::command::user_status_command::{UserStatusCreateCommand, UserStatusDeleteCommand, UserStatusGetCommand, UserStatusListCommand, UserStatusUpdateCommand};
-use crate::services::user::dto::user_status_dto::{UserStatusCreateRequest, UserStatusResponse};
-use crate::services::user::service::user_status_service::{UserStatusService, UserStatusServiceInterface};
+use crate::shared::errors::status_code_of;
+use crate::shared::http::etag::{etag_headers, parse_if_match};
+use crate::services::user::command::user_status_command::{UserStatusCreateCommand, UserStatusDeleteCommand, UserStatusGetCommand, UserStatusListCommand, UserStatusPatchCommand, UserStatusUpdateCommand};
+use crate::services::user::dto::user_status_dto::{UserStatusCreateRequest, UserStatusPatchRequest, UserStatusResponse};
 
 pub fn routes() -> Router<AppState> {
     Router::new()
         .route("/", get(get_user_statuses).post(post_user_status))
-        .route("/{user_status_id}", get(get_user_status_by_id).put(put_user_status).delete(delete_user_status))
+        .route("/{user_status_id}", get(get_user_status_by_id).put(put_user_status).patch(patch_user_status).delete(delete_user_status))
 }
 
 
@@ -22,7 +23,7 @@ pub fn routes() -> Router<AppState> {
 )]
 pub async fn get_user_statuses(State(state): State<AppState>) -> Result<Json<Vec<UserStatusResponse>>, StatusCode> {
     let user_status_list_command = UserStatusListCommand { pagination: None };
-    let user_status_service = UserStatusService::from_app_state(&state);
+    let user_status_service = &state.services.user_status;
     let user_statuses = user_status_service.get_all(user_status_list_command).await;
     match user_statuses {
         Ok(user_statuses) => Ok(Json(user_statuses)),
@@ -34,6 +35,9 @@ pub async fn get_user_statuses(State(state): State<AppState>) -> Result<Json<Vec
 #[utoipa::path(
     post,
     path = "/api/user/status",
+    params(
+        ("Idempotency-Key" = Option<String>, Header, description = "Retries with the same key replay the first response instead of creating again")
+    ),
     responses(
         (status = StatusCode::CREATED, description = "User Status successfully created", body = UserStatusResponse),
         (status = StatusCode::BAD_REQUEST),
@@ -49,7 +53,7 @@ pub async fn post_user_status(
         name: user_status_create_request.name,
         description: user_status_create_request.description
     };
-    let user_status_service = UserStatusService::from_app_state(&state);
+    let user_status_service = &state.services.user_status;
     let user_status = user_status_service.create(user_status_create_command).await;
     match user_status {
         Ok(user_status) => Ok(Json(user_status)),
@@ -62,7 +66,8 @@ pub async fn post_user_status(
     get,
     path = "/api/user/status/{user_status_id}",
     responses(
-        (status = StatusCode::OK, description = "User Status", body = UserStatusResponse),
+        (status = StatusCode::OK, description = "User Status", body = UserStatusResponse,
+            headers(("ETag" = String, description = "Current version of the user status"))),
         (status = StatusCode::NOT_FOUND, description = "User Status not found"),
         (status = StatusCode::INTERNAL_SERVER_ERROR)
     ),
@@ -71,14 +76,14 @@ pub async fn post_user_status(
 pub async fn get_user_status_by_id(
     Path(user_status_id): Path<i64>,
     State(state): State<AppState>
-) -> Result<Json<UserStatusResponse>, StatusCode> {
+) -> Result<(HeaderMap, Json<UserStatusResponse>), StatusCode> {
     let user_status_get_command = UserStatusGetCommand{ id: user_status_id };
-    let user_status_service = UserStatusService::from_app_state(&state);
+    let user_status_service = &state.services.user_status;
     let user_status = user_status_service.get(user_status_get_command).await;
     match user_status {
         Ok(user_status) => {
             match user_status {
-                Some(user_status) => Ok(Json(user_status)),
+                Some(user_status) => Ok((etag_headers(user_status.version), Json(user_status))),
                 None => Err(StatusCode::NOT_FOUND),
             }
         },
@@ -90,9 +95,15 @@ pub async fn get_user_status_by_id(
 #[utoipa::path(
     put,
     path = "/api/user/status/{user_status_id}",
+    params(
+        ("If-Match" = String, Header, description = "ETag of the user status as last read by the client")
+    ),
     responses(
-        (status = StatusCode::OK, description = "User Status successfully modified", body = UserStatusResponse),
+        (status = StatusCode::OK, description = "User Status successfully modified", body = UserStatusResponse,
+            headers(("ETag" = String, description = "New version of the user status"))),
         (status = StatusCode::NOT_FOUND, description = "User Status not found"),
+        (status = StatusCode::PRECONDITION_FAILED, description = "User Status was modified since it was read"),
+        (status = StatusCode::PRECONDITION_REQUIRED, description = "Missing If-Match header"),
         (status = StatusCode::INTERNAL_SERVER_ERROR)
     ),
     tag = "UserStatus"
@@ -100,18 +111,61 @@ pub async fn get_user_status_by_id(
 pub async fn put_user_status(
     Path(user_status_id): Path<i64>,
     State(state): State<AppState>,
+    headers: HeaderMap,
     Json(user_status_update_request): Json<UserStatusCreateRequest>
-) -> Result<Json<UserStatusResponse>, StatusCode> {
+) -> Result<(HeaderMap, Json<UserStatusResponse>), StatusCode> {
+    let if_match = parse_if_match(&headers)?;
     let user_status_update_command = UserStatusUpdateCommand {
         id: user_status_id,
+        if_match,
         name: user_status_update_request.name,
         description: user_status_update_request.description
     };
-    let user_status_service = UserStatusService::from_app_state(&state);
+    let user_status_service = &state.services.user_status;
     let user_status = user_status_service.update(user_status_update_command).await;
     match user_status {
-        Ok(user_status) => Ok(Json(user_status)),
-        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+        Ok(user_status) => Ok((etag_headers(user_status.version), Json(user_status))),
+        Err(e) => Err(status_code_of(&e)),
+    }
+}
+
+
+#[utoipa::path(
+    patch,
+    path = "/api/user/status/{user_status_id}",
+    params(
+        ("If-Match" = String, Header, description = "ETag of the user status as last read by the client")
+    ),
+    request_body(content = UserStatusPatchRequest, content_type = "application/merge-patch+json"),
+    responses(
+        (status = StatusCode::OK, description = "User Status successfully modified", body = UserStatusResponse,
+            headers(("ETag" = String, description = "New version of the user status"))),
+        (status = StatusCode::NOT_FOUND, description = "User Status not found"),
+        (status = StatusCode::PRECONDITION_FAILED, description = "User Status was modified since it was read"),
+        (status = StatusCode::PRECONDITION_REQUIRED, description = "Missing If-Match header"),
+        (status = StatusCode::UNPROCESSABLE_ENTITY, description = "Invalid patch document"),
+        (status = StatusCode::INTERNAL_SERVER_ERROR)
+    ),
+    tag = "UserStatus"
+)]
+pub async fn patch_user_status(
+    Path(user_status_id): Path<i64>,
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(user_status_patch_request): Json<UserStatusPatchRequest>
+) -> Result<(HeaderMap, Json<UserStatusResponse>), StatusCode> {
+    let if_match = parse_if_match(&headers)?;
+    let user_status_patch_command = UserStatusPatchCommand {
+        id: user_status_id,
+        if_match,
+        name: user_status_patch_request.name,
+        description: user_status_patch_request.description
+    };
+    let user_status_service = &state.services.user_status;
+    let user_status = user_status_service.patch(user_status_patch_command).await;
+    match user_status {
+        Ok(user_status) => Ok((etag_headers(user_status.version), Json(user_status))),
+        Err(e) => Err(status_code_of(&e)),
     }
 }
 
@@ -119,23 +173,30 @@ pub async fn put_user_status(
 #[utoipa::path(
     delete,
     path = "/api/user/status/{user_status_id}",
+    params(
+        ("If-Match" = String, Header, description = "ETag of the user status as last read by the client")
+    ),
     responses(
         (status = StatusCode::OK, description = "User Status successfully deleted"),
         (status = StatusCode::NOT_FOUND, description = "User Status not found"),
+        (status = StatusCode::PRECONDITION_FAILED, description = "User Status was modified since it was read"),
+        (status = StatusCode::PRECONDITION_REQUIRED, description = "Missing If-Match header"),
         (status = StatusCode::INTERNAL_SERVER_ERROR)
     ),
     tag = "UserStatus"
 )]
 pub async fn delete_user_status(
     Path(user_status_id): Path<i64>,
-    State(state): State<AppState>
+    State(state): State<AppState>,
+    headers: HeaderMap
 ) -> Result<StatusCode, StatusCode> {
-    let user_status_delete_command = UserStatusDeleteCommand{ id: user_status_id };
-    let user_status_service = UserStatusService::from_app_state(&state);
+    let if_match = parse_if_match(&headers)?;
+    let user_status_delete_command = UserStatusDeleteCommand{ id: user_status_id, if_match };
+    let user_status_service = &state.services.user_status;
     let result = user_status_service.delete(user_status_delete_command).await;
     match result {
         Ok(_) => Ok(StatusCode::OK),
-        Err(_) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(status_code_of(&e)),
     }
 }
 
@@ -0,0 +1,273 @@
+use axum::{Router, routing::{get, post, put}, extract::{Path, State}, Json, http::{HeaderMap, StatusCode}};
+use axum::extract::{DefaultBodyLimit, Multipart, Query};
+use crate::shared::state::AppState;
+use crate::services::user::controller::user_controller::{self, delete_user, update_user_password};
+use crate::services::user::dto::user_dto::{SearchCountryRequest, SearchTitleRequest, UserCreateRequest, UserPatchRequest, UserProfilePicUploadRequest, UserResponse, UserUpdateProfilePicUrlRequest, UserUpdateRequest, UserUpdateStatusRequest};
+use crate::services::user::dto::user_v2_dto::UserV2Response;
+use crate::shared::models::response::PaginationRequest;
+use crate::shared::models::utils_model::IdListModel;
+use crate::shared::security::authenticated_user::AuthenticatedUser;
+
+/// Routes of `/api/v2/user`: the v1 handlers, with the users they return as [`UserV2Response`].
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(get_users).post(post_user))
+        .route("/batch", post(get_users_by_ids))
+        .route("/{user_id}", get(get_user_by_id).put(put_user).patch(patch_user).delete(delete_user))
+        .route("/{user_id}/status", put(update_user_status))
+        // the upload size is enforced by the handler from the storage configuration
+        .route("/{user_id}/profile-pic", put(update_user_profile_pic).post(upload_user_profile_pic).layer(DefaultBodyLimit::disable()))
+        .route("/{user_id}/password", put(update_user_password))
+        .route("/{user_id}/restore", post(restore_user))
+}
+
+fn into_v2((headers, Json(user)): (HeaderMap, Json<UserResponse>)) -> (HeaderMap, Json<UserV2Response>) {
+    (headers, Json(UserV2Response::from(user)))
+}
+
+
+#[utoipa::path(
+    get,
+    path = "/api/v2/user",
+    params(
+        PaginationRequest,
+        SearchCountryRequest,
+        SearchTitleRequest
+    ),
+    responses(
+        (status = StatusCode::OK, description = "List of User", body = Vec<UserV2Response>),
+        (status = StatusCode::INTERNAL_SERVER_ERROR)
+    ),
+    tag = "User"
+)]
+pub async fn get_users(
+    state: State<AppState>,
+    pagination: Query<PaginationRequest>,
+    search_country: Query<SearchCountryRequest>,
+    search_title: Query<SearchTitleRequest>
+) -> Result<Json<Vec<UserV2Response>>, StatusCode> {
+    let Json(users) = user_controller::get_users(state, pagination, search_country, search_title).await?;
+    Ok(Json(users.into_iter().map(UserV2Response::from).collect()))
+}
+
+
+#[utoipa::path(
+    post,
+    path = "/api/v2/user",
+    params(
+        ("Idempotency-Key" = Option<String>, Header, description = "Retries with the same key replay the first response instead of creating again")
+    ),
+    responses(
+        (status = StatusCode::OK, description = "User successfully created", body = UserV2Response),
+        (status = StatusCode::BAD_REQUEST),
+        (status = StatusCode::CONFLICT, description = "Username already taken or a request with the same Idempotency-Key is still running"),
+        (status = StatusCode::UNPROCESSABLE_ENTITY, description = "Password too short or Idempotency-Key already used with another request"),
+        (status = StatusCode::INTERNAL_SERVER_ERROR)
+    ),
+    tag = "User"
+)]
+pub async fn post_user(
+    state: State<AppState>,
+    user_create_request: Json<UserCreateRequest>
+) -> Result<Json<UserV2Response>, StatusCode> {
+    let Json(user) = user_controller::post_user(state, user_create_request).await?;
+    Ok(Json(UserV2Response::from(user)))
+}
+
+
+#[utoipa::path(
+    post,
+    path = "/api/v2/user/batch",
+    request_body = IdListModel,
+    responses(
+        (status = StatusCode::OK, description = "Users of the ids in the same order, unknown ids skipped", body = Vec<UserV2Response>),
+        (status = StatusCode::UNPROCESSABLE_ENTITY, description = "More than 100 ids"),
+        (status = StatusCode::INTERNAL_SERVER_ERROR)
+    ),
+    tag = "User"
+)]
+pub async fn get_users_by_ids(
+    state: State<AppState>,
+    id_list: Json<IdListModel>
+) -> Result<Json<Vec<UserV2Response>>, StatusCode> {
+    let Json(users) = user_controller::get_users_by_ids(state, id_list).await?;
+    Ok(Json(users.into_iter().map(UserV2Response::from).collect()))
+}
+
+
+#[utoipa::path(
+    get,
+    path = "/api/v2/user/{user_id}",
+    responses(
+        (status = StatusCode::OK, description = "User found successfully", body = UserV2Response,
+            headers(("ETag" = String, description = "Current version of the user"))),
+        (status = StatusCode::NOT_FOUND, description = "User not found"),
+        (status = StatusCode::INTERNAL_SERVER_ERROR)
+    ),
+    tag = "User"
+)]
+pub async fn get_user_by_id(
+    user_id: Path<i64>,
+    state: State<AppState>
+) -> Result<(HeaderMap, Json<UserV2Response>), StatusCode> {
+    user_controller::get_user_by_id(user_id, state).await.map(into_v2)
+}
+
+
+#[utoipa::path(
+    put,
+    path = "/api/v2/user/{user_id}",
+    params(
+        ("If-Match" = String, Header, description = "ETag of the user as last read by the client")
+    ),
+    responses(
+        (status = StatusCode::OK, description = "User successfully modified", body = UserV2Response,
+            headers(("ETag" = String, description = "New version of the user"))),
+        (status = StatusCode::NOT_FOUND, description = "User not found"),
+        (status = StatusCode::PRECONDITION_FAILED, description = "User was modified since it was read"),
+        (status = StatusCode::PRECONDITION_REQUIRED, description = "Missing If-Match header"),
+        (status = StatusCode::INTERNAL_SERVER_ERROR)
+    ),
+    tag = "User"
+)]
+pub async fn put_user(
+    user_id: Path<i64>,
+    state: State<AppState>,
+    headers: HeaderMap,
+    user_update_request: Json<UserUpdateRequest>
+) -> Result<(HeaderMap, Json<UserV2Response>), StatusCode> {
+    user_controller::put_user(user_id, state, headers, user_update_request).await.map(into_v2)
+}
+
+
+#[utoipa::path(
+    patch,
+    path = "/api/v2/user/{user_id}",
+    params(
+        ("If-Match" = String, Header, description = "ETag of the user as last read by the client")
+    ),
+    request_body(content = UserPatchRequest, content_type = "application/merge-patch+json"),
+    responses(
+        (status = StatusCode::OK, description = "User successfully modified", body = UserV2Response,
+            headers(("ETag" = String, description = "New version of the user"))),
+        (status = StatusCode::NOT_FOUND, description = "User not found"),
+        (status = StatusCode::PRECONDITION_FAILED, description = "User was modified since it was read"),
+        (status = StatusCode::PRECONDITION_REQUIRED, description = "Missing If-Match header"),
+        (status = StatusCode::UNPROCESSABLE_ENTITY, description = "Invalid patch document"),
+        (status = StatusCode::INTERNAL_SERVER_ERROR)
+    ),
+    tag = "User"
+)]
+pub async fn patch_user(
+    user_id: Path<i64>,
+    state: State<AppState>,
+    headers: HeaderMap,
+    user_patch_request: Json<UserPatchRequest>
+) -> Result<(HeaderMap, Json<UserV2Response>), StatusCode> {
+    user_controller::patch_user(user_id, state, headers, user_patch_request).await.map(into_v2)
+}
+
+
+#[utoipa::path(
+    put,
+    path = "/api/v2/user/{user_id}/status",
+    request_body = UserUpdateStatusRequest,
+    responses(
+        (status = StatusCode::OK, description = "User status successfully changed", body = UserV2Response,
+            headers(("ETag" = String, description = "New version of the user"))),
+        (status = StatusCode::UNAUTHORIZED, description = "Missing or invalid bearer token"),
+        (status = StatusCode::FORBIDDEN, description = "Only managers can change the status of a user"),
+        (status = StatusCode::NOT_FOUND, description = "User not found"),
+        (status = StatusCode::CONFLICT, description = "Deleted users must be restored first"),
+        (status = StatusCode::UNPROCESSABLE_ENTITY, description = "Unknown user status or the deleted status"),
+        (status = StatusCode::INTERNAL_SERVER_ERROR)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "User"
+)]
+pub async fn update_user_status(
+    user_id: Path<i64>,
+    state: State<AppState>,
+    caller: AuthenticatedUser,
+    user_update_status_request: Json<UserUpdateStatusRequest>
+) -> Result<(HeaderMap, Json<UserV2Response>), StatusCode> {
+    user_controller::update_user_status(user_id, state, caller, user_update_status_request).await.map(into_v2)
+}
+
+
+#[utoipa::path(
+    put,
+    path = "/api/v2/user/{user_id}/profile-pic",
+    request_body = UserUpdateProfilePicUrlRequest,
+    responses(
+        (status = StatusCode::OK, description = "Profile picture successfully changed", body = UserV2Response,
+            headers(("ETag" = String, description = "New version of the user"))),
+        (status = StatusCode::UNAUTHORIZED, description = "Missing or invalid bearer token"),
+        (status = StatusCode::FORBIDDEN, description = "Users can only change their own profile picture"),
+        (status = StatusCode::NOT_FOUND, description = "User not found"),
+        (status = StatusCode::INTERNAL_SERVER_ERROR)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "User"
+)]
+pub async fn update_user_profile_pic(
+    user_id: Path<i64>,
+    state: State<AppState>,
+    caller: AuthenticatedUser,
+    user_update_profile_pic_url_request: Json<UserUpdateProfilePicUrlRequest>
+) -> Result<(HeaderMap, Json<UserV2Response>), StatusCode> {
+    user_controller::update_user_profile_pic(user_id, state, caller, user_update_profile_pic_url_request).await.map(into_v2)
+}
+
+
+#[utoipa::path(
+    post,
+    path = "/api/v2/user/{user_id}/profile-pic",
+    request_body(content = UserProfilePicUploadRequest, content_type = "multipart/form-data"),
+    responses(
+        (status = StatusCode::OK, description = "Profile picture successfully uploaded", body = UserV2Response,
+            headers(("ETag" = String, description = "New version of the user"))),
+        (status = StatusCode::BAD_REQUEST, description = "Malformed multipart body or missing file part"),
+        (status = StatusCode::UNAUTHORIZED, description = "Missing or invalid bearer token"),
+        (status = StatusCode::FORBIDDEN, description = "Users can only change their own profile picture"),
+        (status = StatusCode::NOT_FOUND, description = "User not found"),
+        (status = StatusCode::PAYLOAD_TOO_LARGE, description = "Picture exceeds the upload limit"),
+        (status = StatusCode::UNSUPPORTED_MEDIA_TYPE, description = "File is not a PNG, JPEG, GIF or WebP image"),
+        (status = StatusCode::UNPROCESSABLE_ENTITY, description = "Image cannot be decoded"),
+        (status = StatusCode::INTERNAL_SERVER_ERROR)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "User"
+)]
+pub async fn upload_user_profile_pic(
+    user_id: Path<i64>,
+    state: State<AppState>,
+    caller: AuthenticatedUser,
+    multipart: Multipart
+) -> Result<(HeaderMap, Json<UserV2Response>), StatusCode> {
+    user_controller::upload_user_profile_pic(user_id, state, caller, multipart).await.map(into_v2)
+}
+
+
+#[utoipa::path(
+    post,
+    path = "/api/v2/user/{user_id}/restore",
+    responses(
+        (status = StatusCode::OK, description = "User successfully restored", body = UserV2Response,
+            headers(("ETag" = String, description = "New version of the user"))),
+        (status = StatusCode::UNAUTHORIZED, description = "Missing or invalid bearer token"),
+        (status = StatusCode::FORBIDDEN, description = "Only managers can restore users"),
+        (status = StatusCode::NOT_FOUND, description = "User not found"),
+        (status = StatusCode::CONFLICT, description = "User is not deleted"),
+        (status = StatusCode::INTERNAL_SERVER_ERROR)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "User"
+)]
+pub async fn restore_user(
+    user_id: Path<i64>,
+    state: State<AppState>,
+    caller: AuthenticatedUser
+) -> Result<(HeaderMap, Json<UserV2Response>), StatusCode> {
+    user_controller::restore_user(user_id, state, caller).await.map(into_v2)
+}
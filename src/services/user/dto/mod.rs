@@ -1,3 +1,3 @@
 pub mod user_auth_dto;
 pub mod user_status_dto;
-pub mod user_dto;
\ No newline at end of file
+pub mod user_dto;pub mod user_v2_dto;
@@ -2,12 +2,15 @@ use serde::{Serialize, Deserialize};
 use utoipa::ToSchema;
 
 use crate::services::user::model::user_model::{User, UserAuth};
+use crate::shared::models::patch::Patch;
 
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct UserAuthResponse {
     pub id: i64,
     pub name: String,
     pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<i64>,
 }
 
 impl UserAuthResponse {
@@ -16,14 +19,16 @@ impl UserAuthResponse {
             id: user_auth.id.unwrap_or(0),
             name: user_auth.name,
             description: user_auth.description,
+            version: user_auth.version,
         }
     }
 
     pub fn from_user(user: &User) -> Self {
         Self {
             id: user.auth,
-            name: user.auth_name.clone().unwrap_or("user".to_string()),
-            description: user.auth_description.clone(),
+            name: user.user_auth.as_ref().map_or("user".to_string(), |user_auth| user_auth.name.clone()),
+            description: user.user_auth.as_ref().and_then(|user_auth| user_auth.description.clone()),
+            version: None,
         }
     }
 }
@@ -42,3 +47,15 @@ pub struct UserAuthUpdateRequest {
     pub description: Option<String>,
 }
 
+
+/// JSON Merge Patch (RFC 7396) body: absent members are kept, `null` members are cleared.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct UserAuthPatchRequest {
+    #[serde(default, skip_serializing_if = "Patch::is_absent")]
+    #[schema(value_type = Option<String>)]
+    pub name: Patch<String>,
+    #[serde(default, skip_serializing_if = "Patch::is_absent")]
+    #[schema(value_type = Option<String>)]
+    pub description: Patch<String>,
+}
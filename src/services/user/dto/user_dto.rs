@@ -4,6 +4,7 @@ use utoipa::{IntoParams, ToSchema};
 use crate::services::user::dto::user_auth_dto::UserAuthResponse;
 use crate::services::user::dto::user_status_dto::UserStatusResponse;
 use crate::services::user::model::user_model::User;
+use crate::shared::models::patch::Patch;
 
 
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
@@ -21,6 +22,8 @@ pub struct UserResponse {
 
     pub created_at: Option<DateTime<Utc>>,
     pub updated_at: Option<DateTime<Utc>>,
+
+    pub version: Option<i64>,
 }
 
 impl UserResponse {
@@ -37,6 +40,7 @@ impl UserResponse {
             status,
             created_at: user.created_at,
             updated_at: user.updated_at,
+            version: user.version,
         }
     }
 }
@@ -82,12 +86,36 @@ impl CustomerUserResponse {
 }
 
 
+/// Managers are hired employees: `hired_date` and `title` are required.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ManagerCreateRequest {
+    pub first_name: String,
+    pub last_name: String,
+    pub username: String,
+    pub password: String,
+    pub hired_date: DateTime<Utc>,
+    pub title: String,
+}
+
+/// Customers need a shipping address: `address`, `country` and `phone` are required.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct CustomerCreateRequest {
+    pub first_name: String,
+    pub last_name: String,
+    pub username: String,
+    pub password: String,
+    pub address: String,
+    pub country: String,
+    pub phone: String,
+}
+
+
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct UserCreateRequest {
     pub first_name: String,
     pub last_name: String,
     pub username: String,
-    pub password: Option<String>,
+    pub password: String,
     pub auth: i64,
     pub status: i64,
     pub hired_date: Option<DateTime<Utc>>,
@@ -99,7 +127,6 @@ pub struct UserCreateRequest {
 
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct UserUpdateRequest {
-    pub id: i64,
     pub first_name: String,
     pub last_name: String,
     pub hired_date: Option<DateTime<Utc>>,
@@ -109,6 +136,58 @@ pub struct UserUpdateRequest {
     pub phone: Option<String>,
 }
 
+/// JSON Merge Patch (RFC 7396) body: absent members are kept, `null` members are cleared.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct UserPatchRequest {
+    #[serde(default, skip_serializing_if = "Patch::is_absent")]
+    #[schema(value_type = Option<String>)]
+    pub first_name: Patch<String>,
+    #[serde(default, skip_serializing_if = "Patch::is_absent")]
+    #[schema(value_type = Option<String>)]
+    pub last_name: Patch<String>,
+    #[serde(default, skip_serializing_if = "Patch::is_absent")]
+    #[schema(value_type = Option<DateTime<Utc>>)]
+    pub hired_date: Patch<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Patch::is_absent")]
+    #[schema(value_type = Option<String>)]
+    pub title: Patch<String>,
+    #[serde(default, skip_serializing_if = "Patch::is_absent")]
+    #[schema(value_type = Option<String>)]
+    pub address: Patch<String>,
+    #[serde(default, skip_serializing_if = "Patch::is_absent")]
+    #[schema(value_type = Option<String>)]
+    pub country: Patch<String>,
+    #[serde(default, skip_serializing_if = "Patch::is_absent")]
+    #[schema(value_type = Option<String>)]
+    pub phone: Patch<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct UserUpdateStatusRequest {
+    pub status: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct UserUpdateProfilePicUrlRequest {
+    pub profile_pic_url: Option<String>,
+}
+
+/// Multipart form carrying the picture in its `file` part.
+#[derive(Debug, ToSchema)]
+pub struct UserProfilePicUploadRequest {
+    #[schema(value_type = String, format = Binary)]
+    pub file: Vec<u8>,
+}
+
+/// `current_password` is required when users change their own password,
+/// managers may reset the password of other users without it.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct UserUpdatePasswordRequest {
+    pub current_password: Option<String>,
+    pub password: String,
+}
+
 #[derive(Debug, Serialize, Deserialize, ToSchema, IntoParams)]
 pub struct SearchCountryRequest {
     #[param(example = "Italy")]
@@ -1,5 +1,6 @@
 use serde::{Serialize, Deserialize};
 use utoipa::ToSchema;
+use crate::shared::models::patch::Patch;
 use crate::services::user::model::user_model::{User, UserStatus};
 
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
@@ -7,6 +8,8 @@ pub struct UserStatusResponse {
     pub id: i64,
     pub name: String,
     pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<i64>,
 }
 
 impl UserStatusResponse {
@@ -15,14 +18,16 @@ impl UserStatusResponse {
             id: user_status.id.unwrap_or(0),
             name: user_status.name,
             description: user_status.description,
+            version: user_status.version,
         }
     }
 
     pub fn from_user(user: &User) -> Self {
         Self {
             id: user.status,
-            name: user.status_name.clone().unwrap_or("active".to_string()),
-            description: user.status_description.clone(),
+            name: user.user_status.as_ref().map_or("active".to_string(), |user_status| user_status.name.clone()),
+            description: user.user_status.as_ref().and_then(|user_status| user_status.description.clone()),
+            version: None,
         }
     }
 }
@@ -40,3 +45,15 @@ pub struct UserStatusUpdateRequest {
     pub description: Option<String>,
 }
 
+
+/// JSON Merge Patch (RFC 7396) body: absent members are kept, `null` members are cleared.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct UserStatusPatchRequest {
+    #[serde(default, skip_serializing_if = "Patch::is_absent")]
+    #[schema(value_type = Option<String>)]
+    pub name: Patch<String>,
+    #[serde(default, skip_serializing_if = "Patch::is_absent")]
+    #[schema(value_type = Option<String>)]
+    pub description: Patch<String>,
+}
@@ -0,0 +1,75 @@
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+use utoipa::ToSchema;
+use crate::services::user::dto::user_dto::{CustomerUserResponse, ManagerUserResponse, UserResponse};
+
+
+/// User of the v2 API: auth and status by name, the version only in the `ETag` header.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct UserV2Response {
+    pub id: i64,
+    pub first_name: String,
+    pub last_name: String,
+    pub username: String,
+    pub profile_pic_url: Option<String>,
+
+    pub auth: String,
+    pub status: String,
+
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl UserV2Response {
+    pub fn from(user: UserResponse) -> Self {
+        Self {
+            id: user.id,
+            first_name: user.first_name,
+            last_name: user.last_name,
+            username: user.username,
+            profile_pic_url: user.profile_pic_url,
+            auth: user.auth.name,
+            status: user.status.name,
+            created_at: user.created_at,
+            updated_at: user.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ManagerUserV2Response {
+    pub user: UserV2Response,
+
+    pub hired_date: Option<DateTime<Utc>>,
+    pub title: Option<String>,
+}
+
+impl ManagerUserV2Response {
+    pub fn from(manager: ManagerUserResponse) -> Self {
+        Self {
+            user: UserV2Response::from(manager.user),
+            hired_date: manager.hired_date,
+            title: manager.title,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct CustomerUserV2Response {
+    pub user: UserV2Response,
+
+    pub address: Option<String>,
+    pub country: Option<String>,
+    pub phone: Option<String>,
+}
+
+impl CustomerUserV2Response {
+    pub fn from(customer: CustomerUserResponse) -> Self {
+        Self {
+            user: UserV2Response::from(customer.user),
+            address: customer.address,
+            country: customer.country,
+            phone: customer.phone,
+        }
+    }
+}
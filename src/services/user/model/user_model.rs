@@ -1,14 +1,13 @@
-use std::collections::HashMap;
 use chrono::{DateTime, Utc};
+use e_commerce_system_macros::FromSqlRow;
 use serde::{Deserialize, Serialize};
-use sqlx::{mysql::MySqlRow, Row, Error as SqlxError};
-use crate::shared::database::mysql::FromSqlRow;
 
-#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, FromSqlRow)]
 pub struct UserAuth {
     pub id: Option<i64>,
     pub name: String,
     pub description: Option<String>,
+    pub version: Option<i64>,
 }
 
 impl UserAuth {
@@ -17,26 +16,28 @@ impl UserAuth {
             id: None,
             name,
             description,
+            version: None,
         }
     }
 }
 
-impl FromSqlRow for UserAuth {
-    fn map_row_to_entity(row: MySqlRow, index_map: &HashMap<String, usize>) -> Result<Self, SqlxError> {
-        Ok(UserAuth {
-            id: row.try_get(index_map["id"])?,
-            name: row.try_get(index_map["name"])?,
-            description: row.try_get(index_map["description"])?,
-        })
-    }
-}
 
+/// `UserAuth` level of back-office users, allowed to administrate other users
+pub const USER_AUTH_MANAGER: &str = "manager";
+/// `UserAuth` level of shop customers
+pub const USER_AUTH_CUSTOMER: &str = "customer";
 
-#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+/// Status given back to a user by a restore
+pub const USER_STATUS_ACTIVE: &str = "active";
+/// Status given by DELETE, soft-deleted users keep it until they are restored
+pub const USER_STATUS_DELETED: &str = "deleted";
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, FromSqlRow)]
 pub struct UserStatus {
     pub id: Option<i64>,
     pub name: String,
     pub description: Option<String>,
+    pub version: Option<i64>,
 }
 
 impl UserStatus {
@@ -45,22 +46,17 @@ impl UserStatus {
             id: None,
             name,
             description,
+            version: None,
         }
     }
-}
 
-impl FromSqlRow for UserStatus {
-    fn map_row_to_entity(row: MySqlRow, index_map: &HashMap<String, usize>) -> Result<Self, SqlxError> {
-        Ok(UserStatus {
-            id: row.try_get(index_map["id"])?,
-            name: row.try_get(index_map["name"])?,
-            description: row.try_get(index_map["description"])?,
-        })
+    pub fn is_deleted(&self) -> bool {
+        self.name.eq_ignore_ascii_case(USER_STATUS_DELETED)
     }
 }
 
 
-#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+#[derive(Debug, Clone, Serialize, Deserialize, FromSqlRow)]
 pub struct User {
     /// Shared information
     pub id: Option<i64>,
@@ -74,13 +70,15 @@ pub struct User {
 
     /// User Authentication Level: user, customer, manager
     pub auth: i64,
-    pub auth_name: Option<String>,
-    pub auth_description: Option<String>,
+    /// Row of `auth`, read from the `auth_*` columns of `user_view`
+    #[sql(nested, prefix = "auth_")]
+    pub user_auth: Option<UserAuth>,
 
     /// User Status Level: active, inactive, locked, deleted, suspended, expired, reseted, unverified, unknown
     pub status: i64,
-    pub status_name: Option<String>,
-    pub status_description: Option<String>,
+    /// Row of `status`, read from the `status_*` columns of `user_view`
+    #[sql(nested, prefix = "status_")]
+    pub user_status: Option<UserStatus>,
 
     /// Manager
     pub hired_date: Option<DateTime<Utc>>,
@@ -94,9 +92,13 @@ pub struct User {
 
     pub created_at: Option<DateTime<Utc>>,
     pub updated_at: Option<DateTime<Utc>>,
+
+    /// Row version, bumped on every write and exposed as the `ETag`
+    pub version: Option<i64>,
 }
 
 impl User {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         first_name: String, 
         last_name: String, 
@@ -118,11 +120,9 @@ impl User {
             password,
             profile_pic_url: None,
             auth,
-            auth_name: None,
-            auth_description: None,
+            user_auth: None,
             status,
-            status_name: None,
-            status_description: None,
+            user_status: None,
             hired_date,
             title,
             address,
@@ -130,32 +130,15 @@ impl User {
             phone,
             created_at: None,
             updated_at: None,
+            version: None,
         }
     }
-}
 
-impl FromSqlRow for User {
-    fn map_row_to_entity(row: MySqlRow, index_map: &HashMap<String, usize>) -> Result<Self, SqlxError> {
-        Ok(User {
-            id: row.try_get(index_map["id"])?,
-            first_name: row.try_get(index_map["first_name"])?,
-            last_name: row.try_get(index_map["last_name"])?,
-            username: row.try_get(index_map["username"])?,
-            password: row.try_get(index_map["password"])?,
-            profile_pic_url: row.try_get(index_map["profile_pic_url"])?,
-            auth: row.try_get(index_map["auth"])?,
-            auth_name: row.try_get(index_map["auth_name"])?,
-            auth_description: row.try_get(index_map["auth_description"])?,
-            status: row.try_get(index_map["status"])?,
-            status_name: row.try_get(index_map["status_name"])?,
-            status_description: row.try_get(index_map["status_description"])?,
-            hired_date: row.try_get(index_map["hired_date"])?,
-            title: row.try_get(index_map["title"])?,
-            address: row.try_get(index_map["address"])?,
-            country: row.try_get(index_map["country"])?,
-            phone: row.try_get(index_map["phone"])?,
-            created_at: row.try_get(index_map["created_at"])?,
-            updated_at: row.try_get(index_map["updated_at"])?,
-        })
+    pub fn has_auth(&self, auth_name: &str) -> bool {
+        self.user_auth.as_ref().is_some_and(|user_auth| user_auth.name.eq_ignore_ascii_case(auth_name))
+    }
+
+    pub fn is_deleted(&self) -> bool {
+        self.user_status.as_ref().is_some_and(UserStatus::is_deleted)
     }
 }
@@ -1,100 +1,92 @@
+use std::sync::Arc;
 use anyhow::{Error, Result};
 use async_trait::async_trait;
-use sqlx::MySqlPool;
 
 use crate::services::user::model::user_model::{UserAuth};
-use crate::shared::database::mysql::{GenericRepository, MySqlParam};
+use crate::shared::database::mysql::GenericRepository;
+use crate::shared::database::mysql_pools::MySqlPools;
+use crate::shared::database::procedures;
 use crate::shared::repository::crud_repository::CrudRepository;
 
 #[async_trait]
-pub trait UserAuthRepositoryInterface {
+pub trait UserAuthRepositoryInterface: Send + Sync {
+    /// Same repository reading from the primary, for reads that must see the caller's own writes.
+    fn on_primary(&self) -> Arc<dyn UserAuthRepositoryInterface>;
+
     async fn get_user_auth(&self, user_auth_id: i64) -> Result<Option<UserAuth>, Error>;
 
+    async fn get_user_auth_by_name(&self, name: String) -> Result<Option<UserAuth>, Error>;
+
     async fn create_user_auth(&self, user_auth: UserAuth) -> Result<UserAuth, Error>;
 
     async fn update_user_auth(
         &self,
         user_auth_id: i64,
+        version: i64,
         user_auth: UserAuth,
     ) -> Result<Option<UserAuth>, Error>;
 
-    async fn delete_user_auth(&self, user_auth_id: i64) -> Result<(), Error>;
+    /// Deletes the user auth if it is still at `version`; `false` when it was changed or removed since.
+    async fn delete_user_auth(&self, user_auth_id: i64, version: i64) -> Result<bool, Error>;
 
     async fn get_all_user_auths(&self) -> Result<Vec<UserAuth>, Error>;
 }
 
 #[derive(Clone)]
 pub struct UserAuthRepository {
-    pool: MySqlPool,
+    pools: MySqlPools,
 }
 
 impl UserAuthRepository {
-    pub fn new(pool: MySqlPool) -> Self {
-        Self { pool }
+    pub fn new(pools: MySqlPools) -> Self {
+        Self { pools }
     }
 }
 
 impl GenericRepository<UserAuth> for UserAuthRepository {
-    fn get_pool(&self) -> &MySqlPool {
-        &self.pool
+    fn get_pools(&self) -> &MySqlPools {
+        &self.pools
     }
 }
 
 #[async_trait]
 impl UserAuthRepositoryInterface for UserAuthRepository {
+    fn on_primary(&self) -> Arc<dyn UserAuthRepositoryInterface> {
+        Arc::new(Self { pools: self.pools.primary_only() })
+    }
+
     async fn get_user_auth(&self, user_auth_id: i64) -> Result<Option<UserAuth>, Error> {
-        // CALL app_user_auth_get_by_id(?, ?)
-        let params = vec![
-            MySqlParam::from(user_auth_id),
-            MySqlParam::from(None::<i64>), // meta_user
-        ];
-        
-        self.call_procedure_for_optional("app_user_auth_get_by_id", params).await
+        self.call_procedure_for_optional(procedures::app_user_auth_get_by_id(user_auth_id, None)).await
+    }
+
+    async fn get_user_auth_by_name(&self, name: String) -> Result<Option<UserAuth>, Error> {
+        self.call_procedure_for_optional_from_replica(procedures::app_user_auth_get_by_name(name, None)).await
     }
 
     async fn create_user_auth(&self, user_auth: UserAuth) -> Result<UserAuth, Error> {
-        // CALL app_user_auth_insert(?, ?, ?)
-        let params = vec![
-            MySqlParam::from(user_auth.name),
-            MySqlParam::from(user_auth.description),
-            MySqlParam::from(None::<i64>), // meta_user
-        ];
-
-        self.call_procedure_for_one("app_user_auth_insert", params).await
+        let call = procedures::app_user_auth_insert(user_auth.name, user_auth.description, None);
+
+        self.call_procedure_for_one(call).await
     }
 
     async fn update_user_auth(
         &self,
         user_auth_id: i64,
+        version: i64,
         user_auth: UserAuth,
     ) -> Result<Option<UserAuth>, Error> {
-        // CALL app_user_auth_update(?, ?, ?, ?)
-        let params = vec![
-            MySqlParam::from(user_auth_id),
-            MySqlParam::from(user_auth.name),
-            MySqlParam::from(user_auth.description),
-            MySqlParam::from(None::<i64>), // meta_user
-        ];
-
-        self.call_procedure_for_optional("app_user_auth_update", params).await
+        let call = procedures::app_user_auth_update(user_auth_id, version, user_auth.name, user_auth.description, None);
+
+        self.call_procedure_for_optional(call).await
     }
 
-    async fn delete_user_auth(&self, user_auth_id: i64) -> Result<(), Error> {
-        // CALL app_user_auth_delete(?, ?)
-        let params = vec![
-            MySqlParam::from(user_auth_id),
-            MySqlParam::from(None::<i64>), // meta_user
-        ];
-        
-        self.call_procedure("app_user_auth_delete", params).await
+    async fn delete_user_auth(&self, user_auth_id: i64, version: i64) -> Result<bool, Error> {
+        let affected_rows = self.call_procedure_for_affected_rows(procedures::app_user_auth_delete(user_auth_id, version, None)).await?;
+
+        Ok(affected_rows > 0)
     }
 
     async fn get_all_user_auths(&self) -> Result<Vec<UserAuth>, Error> {
-        // CALL app_user_auth_get_all(?)
-        let params = vec![
-            MySqlParam::from(None::<i64>), // meta_user
-        ];
-        
-        self.call_procedure_for_list("app_user_auth_get_all", params).await
+        self.call_procedure_for_list(procedures::app_user_auth_get_all(None)).await
     }
 }
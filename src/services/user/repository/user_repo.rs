@@ -1,19 +1,36 @@
+use std::sync::Arc;
 use anyhow::{Error, Result};
 use async_trait::async_trait;
-use sqlx::MySqlPool;
 
 use crate::services::user::model::user_model::User;
-use crate::shared::database::mysql::{GenericRepository, MySqlParam};
+use crate::shared::database::mysql::GenericRepository;
 // use crate::shared::models::utils_model::CountModel;
+use crate::shared::database::mysql_pools::MySqlPools;
+use crate::shared::database::procedures;
+use crate::shared::database::unit_of_work::UnitOfWork;
 use crate::shared::repository::crud_repository::CrudRepository;
 
 #[async_trait]
-pub trait UserRepositoryInterface {
+pub trait UserRepositoryInterface: Send + Sync {
+    /// Same repository reading from the primary, for reads that must see the caller's own writes.
+    fn on_primary(&self) -> Arc<dyn UserRepositoryInterface>;
+
+    /// Same repository running its procedures inside `unit_of_work`.
+    fn in_unit_of_work(&self, unit_of_work: &UnitOfWork) -> Arc<dyn UserRepositoryInterface>;
+
+    /// Pools the units of work of this repository begin on, `None` when it is not backed by MySQL.
+    fn unit_of_work_pools(&self) -> Option<&MySqlPools>;
+
     async fn get_user(&self, user_id: i64) -> Result<Option<User>, Error>;
 
+    /// Reads the user and locks its row until the unit of work ends, so that a check made on it
+    /// still holds when the row is written. Outside of a unit of work it is a plain read.
+    async fn get_user_for_update(&self, user_id: i64) -> Result<Option<User>, Error>;
+
     async fn create_user(&self, user: User) -> Result<User, Error>;
 
-    async fn update_user(&self, user_id: i64, user: User) -> Result<Option<User>, Error>;
+    /// Updates the user only if its row still has `version`; returns `None` otherwise.
+    async fn update_user(&self, user_id: i64, version: i64, user: User) -> Result<Option<User>, Error>;
 
     async fn update_user_password(&self, user_id: i64, user_password: Option<String>) -> Result<Option<User>, Error>;
 
@@ -21,12 +38,16 @@ pub trait UserRepositoryInterface {
 
     async fn update_user_status(&self, user_id: i64, status: i64) -> Result<Option<User>, Error>;
 
-    async fn delete_user(&self, user_id: i64) -> Result<User, Error>;
+    /// Moves the user to the deleted `status` if it is still at `version`; `false` when it was changed since.
+    async fn delete_user(&self, user_id: i64, status: i64, version: i64) -> Result<bool, Error>;
 
     async fn get_all_users(&self, limit: Option<u32>, offset: Option<u32>) -> Result<Vec<User>, Error>;
 
     // async fn get_all_users_count(&self) -> Result<u32, Error>;
 
+    /// Users of `user_ids` in one query, in no particular order; unknown ids are skipped.
+    async fn get_users_by_ids(&self, user_ids: Vec<i64>) -> Result<Vec<User>, Error>;
+
     async fn get_user_by_username(&self, username: String) -> Result<Option<User>, Error>;
 
     async fn get_user_by_title(&self, title: String, limit: Option<u32>, offset: Option<u32>) -> Result<Vec<User>, Error>;
@@ -48,257 +69,164 @@ pub trait UserRepositoryInterface {
 
 #[derive(Clone)]
 pub struct UserRepository {
-    pool: MySqlPool,
+    pools: MySqlPools,
 }
 
 impl UserRepository {
-    pub fn new(pool: MySqlPool) -> Self {
-        Self { pool }
+    pub fn new(pools: MySqlPools) -> Self {
+        Self { pools }
     }
 }
 
 impl GenericRepository<User> for UserRepository {
-    fn get_pool(&self) -> &MySqlPool {
-        &self.pool
+    fn get_pools(&self) -> &MySqlPools {
+        &self.pools
     }
 }
 
 #[async_trait]
 impl UserRepositoryInterface for UserRepository {
+    fn on_primary(&self) -> Arc<dyn UserRepositoryInterface> {
+        Arc::new(Self { pools: self.pools.primary_only() })
+    }
+
+    fn in_unit_of_work(&self, unit_of_work: &UnitOfWork) -> Arc<dyn UserRepositoryInterface> {
+        Arc::new(Self { pools: self.pools.in_unit_of_work(unit_of_work) })
+    }
+
+    fn unit_of_work_pools(&self) -> Option<&MySqlPools> {
+        Some(&self.pools)
+    }
+
     async fn get_user(&self, user_id: i64) -> Result<Option<User>, Error> {
-        // CALL app_user_get_by_id(?, ?)
-        let params = vec![
-            MySqlParam::from(user_id),
-            MySqlParam::from(None::<i64>), // meta_user
-        ];
+        self.call_procedure_for_optional(procedures::app_user_get(user_id, None)).await
+    }
 
-        self.call_procedure_for_optional("app_user_get_by_id", params).await
+    async fn get_user_for_update(&self, user_id: i64) -> Result<Option<User>, Error> {
+        self.call_procedure_for_optional(procedures::app_user_get_for_update(user_id, None)).await
     }
 
     async fn create_user(&self, user: User) -> Result<User, Error> {
-        //   app_user_insert(
-        //                  first_name, last_name,
-        //                  username, password,
-        //                  profile_pic_url,
-        //                  auth, status,
-        //                  hired_date, title,
-        //                  address, country, phone,
-        //                  meta_user)
-        let params = vec![
-            MySqlParam::from(user.first_name),
-            MySqlParam::from(user.last_name),
-            MySqlParam::from(user.username),
-            MySqlParam::from(user.password),
-            MySqlParam::from(user.profile_pic_url),
-            MySqlParam::from(user.auth),
-            MySqlParam::from(user.status),
-            MySqlParam::from(user.hired_date),
-            MySqlParam::from(user.title),
-            MySqlParam::from(user.address),
-            MySqlParam::from(user.country),
-            MySqlParam::from(user.phone),
-            MySqlParam::from(None::<i64>),   // meta_user
-        ];
-
-        self.call_procedure_for_one("app_user_insert", params).await
+        let call = procedures::app_user_insert(
+            user.first_name, user.last_name,
+            user.username, user.password,
+            user.profile_pic_url,
+            user.auth, user.status,
+            user.hired_date, user.title,
+            user.address, user.country, user.phone,
+            None, // meta_user
+        );
+
+        self.call_procedure_for_one(call).await
     }
 
-    async fn update_user(&self, user_id: i64, user: User) -> Result<Option<User>, Error> {
-        // app_user_update(id,
-        //                  first_name, last_name,
-        //                  hired_date, title,
-        //                  address, country, phone,
-        //                  meta_user)
-        let params = vec![
-            MySqlParam::from(user_id),
-            MySqlParam::from(user.first_name),
-            MySqlParam::from(user.last_name),
-            MySqlParam::from(user.hired_date),
-            MySqlParam::from(user.title),
-            MySqlParam::from(user.address),
-            MySqlParam::from(user.country),
-            MySqlParam::from(user.phone),
-            MySqlParam::from(None::<i64>), // meta_user
-        ];
-
-        self.call_procedure_for_optional("app_user_update", params).await
+    async fn update_user(&self, user_id: i64, version: i64, user: User) -> Result<Option<User>, Error> {
+        let call = procedures::app_user_update(
+            user_id, version,
+            user.first_name, user.last_name,
+            user.hired_date, user.title,
+            user.address, user.country, user.phone,
+            None, // meta_user
+        );
+
+        self.call_procedure_for_optional(call).await
     }
 
     async fn update_user_password(&self, user_id: i64, user_password: Option<String>) -> Result<Option<User>, Error> {
-        // app_user_update_password(id,
-        //                          user_password,
-        //                          meta_user)
-        let params = vec![
-            MySqlParam::from(user_id),
-            MySqlParam::from(user_password),
-            MySqlParam::from(None::<i64>), // meta_user
-        ];
-
-        self.call_procedure_for_optional("app_user_update_password", params).await
+        let call = procedures::app_user_update_password(user_id, user_password, None);
+
+        self.call_procedure_for_optional(call).await
     }
 
     async fn update_user_profile_pic_url(&self, user_id: i64, profile_pic_url: Option<String>) -> Result<Option<User>, Error> {
-        // app_user_update_profile_pic_url(id,
-        //                          user_password,
-        //                          meta_user)
-        let params = vec![
-            MySqlParam::from(user_id),
-            MySqlParam::from(profile_pic_url),
-            MySqlParam::from(None::<i64>), // meta_user
-        ];
-
-        self.call_procedure_for_optional("app_user_update_profile_pic_url", params).await
-    }
+        let call = procedures::app_user_update_profile_pic_url(user_id, profile_pic_url, None);
 
-    async fn update_user_status(&self, user_id: i64, status: i64) -> Result<Option<User>, Error> {
-        // app_user_update_status(id,
-        //                          user_status,
-        //                          meta_user)
-        let params = vec![
-            MySqlParam::from(user_id),
-            MySqlParam::from(status),
-            MySqlParam::from(None::<i64>), // meta_user
-        ];
-
-        self.call_procedure_for_optional("app_user_update_status", params).await
+        self.call_procedure_for_optional(call).await
     }
 
-    async fn delete_user(&self, user_id: i64) -> Result<User, Error> {
-        let params = vec![
-            MySqlParam::from(user_id),
-            MySqlParam::from(None::<i64>), // meta_user
-        ];
-
-        let existing = self.call_procedure_for_optional("app_user_get_by_id", params.clone()).await;
+    async fn update_user_status(&self, user_id: i64, status: i64) -> Result<Option<User>, Error> {
+        let call = procedures::app_user_update_status(user_id, status, None);
 
-        match existing {
-            Ok(existing_opt) => {
-                let user = existing_opt.ok_or_else(|| Error::msg("User not found for delete"))?;
+        self.call_procedure_for_optional(call).await
+    }
 
-                let result = self.call_procedure("app_user_delete_log", params).await;
+    async fn delete_user(&self, user_id: i64, status: i64, version: i64) -> Result<bool, Error> {
+        let affected_rows = self.call_procedure_for_affected_rows(procedures::app_user_delete(user_id, status, version, None)).await
+            .map_err(|e| Error::msg(format!("Failed to delete user: {}", e)))?;
 
-                match result {
-                    Ok(_) => Ok(user),
-                    Err(e) => Err(Error::msg(format!("Failed to delete user: {}", e)))
-                }
-            },
-            Err(e) => Err(Error::msg(format!("Failed to load user for delete: {}", e)))
-        }
+        Ok(affected_rows > 0)
     }
 
     async fn get_all_users(&self, limit: Option<u32>, offset: Option<u32>) -> Result<Vec<User>, Error> {
-        let params = vec![
-            MySqlParam::from(limit),
-            MySqlParam::from(offset),
-            MySqlParam::from(None::<i64>), // meta_user
-        ];
+        let call = procedures::app_user_get_all(page(limit)?, page(offset)?, None);
 
-        self.call_procedure_for_list("app_user_get_all", params).await
+        self.call_procedure_for_list(call).await
     }
 
     /*async fn get_all_users_count(&self) -> Result<u32, Error> {
-        let params = vec![
-            MySqlParam::from(None::<i64>),
-        ];
-
-        self.call_procedure_for_optional("app_user_get_all_count", params).await
+        self.call_procedure_for_optional(procedures::app_user_get_all_count(None)).await
     }*/
 
-    async fn get_user_by_username(&self, username: String) -> Result<Option<User>, Error> {
-        let params = vec![
-            MySqlParam::from(username),
-            MySqlParam::from(None::<i64>), // meta_user
-        ];
+    async fn get_users_by_ids(&self, user_ids: Vec<i64>) -> Result<Vec<User>, Error> {
+        let call = procedures::app_user_get_by_ids(serde_json::to_string(&user_ids)?, None);
 
-        self.call_procedure_for_optional("app_user_get_by_username", params).await
+        self.call_procedure_for_list(call).await
+    }
+
+    async fn get_user_by_username(&self, username: String) -> Result<Option<User>, Error> {
+        self.call_procedure_for_optional_from_replica(procedures::app_user_get_by_username(username, None)).await
     }
 
     async fn get_user_by_title(&self, title: String, limit: Option<u32>, offset: Option<u32>) -> Result<Vec<User>, Error> {
-        let params = vec![
-            MySqlParam::from(title),
-            MySqlParam::from(limit),
-            MySqlParam::from(offset),
-            MySqlParam::from(None::<i64>), // meta_user
-        ];
-
-        self.call_procedure_for_list("app_user_get_by_title", params).await
+        let call = procedures::app_user_get_by_title(title, page(limit)?, page(offset)?, None);
+
+        self.call_procedure_for_list(call).await
     }
 
     async fn get_user_by_country(&self, country: String, limit: Option<u32>, offset: Option<u32>) -> Result<Vec<User>, Error> {
-        let params = vec![
-            MySqlParam::from(country),
-            MySqlParam::from(limit),
-            MySqlParam::from(offset),
-            MySqlParam::from(None::<i64>), // meta_user
-        ];
-
-        self.call_procedure_for_list("app_user_get_by_country", params).await
+        let call = procedures::app_user_get_by_country(country, page(limit)?, page(offset)?, None);
+
+        self.call_procedure_for_list(call).await
     }
 
     async fn get_user_by_country_and_or_title(&self, country: Option<String>, title: Option<String>, limit: Option<u32>, offset: Option<u32>) -> Result<Vec<User>, Error> {
-        let params = vec![
-            MySqlParam::from(country),
-            MySqlParam::from(title),
-            MySqlParam::from(limit),
-            MySqlParam::from(offset),
-            MySqlParam::from(None::<i64>), // meta_user
-        ];
-
-        self.call_procedure_for_list("app_user_get_by_country_and_or_title", params).await
+        let call = procedures::app_user_get_by_country_and_or_title(country, title, page(limit)?, page(offset)?, None);
+
+        self.call_procedure_for_list(call).await
     }
 
     async fn search_user_by_username(&self, username: String, limit: Option<u32>, offset: Option<u32>) -> Result<Vec<User>, Error> {
-        let params = vec![
-            MySqlParam::from(username),
-            MySqlParam::from(limit),
-            MySqlParam::from(offset),
-            MySqlParam::from(None::<i64>), // meta_user
-        ];
-
-        self.call_procedure_for_list("app_user_search_by_username", params).await
+        let call = procedures::app_user_search_by_username(username, page(limit)?, page(offset)?, None);
+
+        self.call_procedure_for_list(call).await
     }
 
     async fn search_user_by_title(&self, title: String, limit: Option<u32>, offset: Option<u32>) -> Result<Vec<User>, Error> {
-        let params = vec![
-            MySqlParam::from(title),
-            MySqlParam::from(limit),
-            MySqlParam::from(offset),
-            MySqlParam::from(None::<i64>), // meta_user
-        ];
-
-        self.call_procedure_for_list("app_user_search_by_title", params).await
+        let call = procedures::app_user_search_by_title(title, page(limit)?, page(offset)?, None);
+
+        self.call_procedure_for_list(call).await
     }
 
     async fn search_user_by_country(&self, country: String, limit: Option<u32>, offset: Option<u32>) -> Result<Vec<User>, Error> {
-        let params = vec![
-            MySqlParam::from(country),
-            MySqlParam::from(limit),
-            MySqlParam::from(offset),
-            MySqlParam::from(None::<i64>), // meta_user
-        ];
-
-        self.call_procedure_for_list("app_user_search_by_country", params).await
+        let call = procedures::app_user_search_by_country(country, page(limit)?, page(offset)?, None);
+
+        self.call_procedure_for_list(call).await
     }
 
     async fn get_user_by_auth(&self, auth: i64, limit: Option<u32>, offset: Option<u32>) -> Result<Vec<User>, Error> {
-        let params = vec![
-            MySqlParam::from(auth),
-            MySqlParam::from(limit),
-            MySqlParam::from(offset),
-            MySqlParam::from(None::<i64>), // meta_user
-        ];
-
-        self.call_procedure_for_list("app_user_get_by_auth", params).await
+        let call = procedures::app_user_get_by_auth(auth, page(limit)?, page(offset)?, None);
+
+        self.call_procedure_for_list(call).await
     }
 
     async fn get_user_by_status(&self, status: i64, limit: Option<u32>, offset: Option<u32>) -> Result<Vec<User>, Error> {
-        let params = vec![
-            MySqlParam::from(status),
-            MySqlParam::from(limit),
-            MySqlParam::from(offset),
-            MySqlParam::from(None::<i64>), // meta_user
-        ];
-
-        self.call_procedure_for_list("app_user_get_by_status", params).await
+        let call = procedures::app_user_get_by_status(status, page(limit)?, page(offset)?, None);
+
+        self.call_procedure_for_list(call).await
     }
 }
+
+/// Limits and offsets are `int` parameters of the procedures.
+fn page(value: Option<u32>) -> Result<Option<i32>, Error> {
+    value.map(i32::try_from).transpose().map_err(|_| Error::msg("Limit or offset out of range"))
+}
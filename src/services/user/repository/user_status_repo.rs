@@ -1,96 +1,89 @@
+use std::sync::Arc;
 use anyhow::{Error, Result};
 use async_trait::async_trait;
-use sqlx::MySqlPool;
 
 use crate::services::user::model::user_model::UserStatus;
-use crate::shared::database::mysql::{
-    GenericRepository,
-    MySqlParam,
-};
+use crate::shared::database::mysql::GenericRepository;
+use crate::shared::database::mysql_pools::MySqlPools;
+use crate::shared::database::procedures;
 use crate::shared::repository::crud_repository::CrudRepository;
 
 
 #[async_trait]
-pub trait UserStatusRepositoryInterface {
+pub trait UserStatusRepositoryInterface: Send + Sync {
+    /// Same repository reading from the primary, for reads that must see the caller's own writes.
+    fn on_primary(&self) -> Arc<dyn UserStatusRepositoryInterface>;
+
     async fn get_user_status(&self, user_status_id: i64) -> Result<Option<UserStatus>, Error>;
 
+    async fn get_user_status_by_name(&self, name: String) -> Result<Option<UserStatus>, Error>;
+
     async fn create_user_status(&self, user_status: UserStatus) -> Result<UserStatus, Error>;
 
-    async fn update_user_status(&self, user_status_id: i64, user_status: UserStatus) -> Result<Option<UserStatus>, Error>;
+    async fn update_user_status(&self, user_status_id: i64, version: i64, user_status: UserStatus) -> Result<Option<UserStatus>, Error>;
 
-    async fn delete_user_status(&self, user_status_id: i64) -> Result<(), Error>;
+    /// Deletes the user status if it is still at `version`; `false` when it was changed or removed since.
+    async fn delete_user_status(&self, user_status_id: i64, version: i64) -> Result<bool, Error>;
 
     async fn get_all_user_status(&self) -> Result<Vec<UserStatus>, Error>;
 }
 
 #[derive(Clone)]
 pub struct UserStatusRepository {
-    pool: MySqlPool,
+    pools: MySqlPools,
 }
 
 impl UserStatusRepository {
-    pub fn new(pool: MySqlPool) -> Self {
-        Self { pool }
+    pub fn new(pools: MySqlPools) -> Self {
+        Self { pools }
     }
 }
 
 /// Hook this repo into the generic MySQL infrastructure.
 impl GenericRepository<UserStatus> for UserStatusRepository {
-    fn get_pool(&self) -> &MySqlPool {
-        &self.pool
+    fn get_pools(&self) -> &MySqlPools {
+        &self.pools
     }
 }
 
 #[async_trait]
 impl UserStatusRepositoryInterface for UserStatusRepository {
+    fn on_primary(&self) -> Arc<dyn UserStatusRepositoryInterface> {
+        Arc::new(Self { pools: self.pools.primary_only() })
+    }
+
     async fn get_user_status(&self, user_status_id: i64) -> Result<Option<UserStatus>, Error> {
-        let params = vec![
-            MySqlParam::from(user_status_id),
-            MySqlParam::from(None::<i64>), // meta_user
-        ];
-        
-        self.call_procedure_for_optional("app_user_status_get_by_id", params).await
+        self.call_procedure_for_optional(procedures::app_user_status_get_by_id(user_status_id, None)).await
+    }
+
+    async fn get_user_status_by_name(&self, name: String) -> Result<Option<UserStatus>, Error> {
+        self.call_procedure_for_optional_from_replica(procedures::app_user_status_get_by_name(name, None)).await
     }
 
     async fn create_user_status(&self, user_status: UserStatus) -> Result<UserStatus, Error> {
-        let params = vec![
-            MySqlParam::from(user_status.name),
-            MySqlParam::from(user_status.description),
-            MySqlParam::from(None::<i64>), // meta_user
-        ];
+        let call = procedures::app_user_status_insert(user_status.name, user_status.description, None);
 
-        self.call_procedure_for_one("app_user_status_insert", params).await
+        self.call_procedure_for_one(call).await
     }
 
     async fn update_user_status(
         &self,
         user_status_id: i64,
+        version: i64,
         user_status: UserStatus,
     ) -> Result<Option<UserStatus>, Error> {
-        let params = vec![
-            MySqlParam::from(user_status_id),
-            MySqlParam::from(user_status.name),
-            MySqlParam::from(user_status.description),
-            MySqlParam::from(None::<i64>), // meta_user
-        ];
-
-        self.call_procedure_for_optional("app_user_status_update", params).await
+        let call = procedures::app_user_status_update(user_status_id, version, user_status.name, user_status.description, None);
+
+        self.call_procedure_for_optional(call).await
     }
 
-    async fn delete_user_status(&self, user_status_id: i64) -> Result<(), Error> {
-        let params = vec![
-            MySqlParam::from(user_status_id),
-            MySqlParam::from(None::<i64>), // meta_user
-        ];
-        
-        self.call_procedure("app_user_status_delete", params).await
+    async fn delete_user_status(&self, user_status_id: i64, version: i64) -> Result<bool, Error> {
+        let affected_rows = self.call_procedure_for_affected_rows(procedures::app_user_status_delete(user_status_id, version, None)).await?;
+
+        Ok(affected_rows > 0)
     }
 
     async fn get_all_user_status(&self) -> Result<Vec<UserStatus>, Error> {
-        let params = vec![
-            MySqlParam::from(None::<i64>), // meta_user
-        ];
-        
-        self.call_procedure_for_list("app_user_status_get_all", params).await
+        self.call_procedure_for_list(procedures::app_user_status_get_all(None)).await
     }
 }
@@ -2,8 +2,13 @@ use axum::Router;
 use crate::shared::state::AppState;
 use crate::services::user::controller::{
     user_controller::routes as user_routes,
+    user_v2_controller::routes as user_v2_routes,
     user_auth_controller::routes as user_auth_routes,
-    user_status_controller::routes as user_status_routes
+    user_status_controller::routes as user_status_routes,
+    manager_controller::routes as manager_controller_routes,
+    manager_v2_controller::routes as manager_v2_controller_routes,
+    customer_controller::routes as customer_controller_routes,
+    customer_v2_controller::routes as customer_v2_controller_routes
 };
 
 pub fn routes() -> Router<AppState> {
@@ -14,3 +19,28 @@ pub fn routes() -> Router<AppState> {
         .nest("/status", user_status_routes())
 }
 
+/// Same routes as [`routes`], the users in the v2 shape.
+pub fn v2_routes() -> Router<AppState> {
+    Router::new()
+        .merge(user_v2_routes())
+        .nest("/auth", user_auth_routes())
+        .nest("/status", user_status_routes())
+}
+
+pub fn manager_routes() -> Router<AppState> {
+    manager_controller_routes()
+}
+
+pub fn customer_routes() -> Router<AppState> {
+    customer_controller_routes()
+}
+
+/// Same routes as [`manager_routes`], the managers in the v2 shape.
+pub fn manager_v2_routes() -> Router<AppState> {
+    manager_v2_controller_routes()
+}
+
+/// Same routes as [`customer_routes`], the customers in the v2 shape.
+pub fn customer_v2_routes() -> Router<AppState> {
+    customer_v2_controller_routes()
+}
@@ -1,3 +1,4 @@
 pub mod user_auth_service;
 pub mod user_status_service;
-pub mod user_service;
\ No newline at end of file
+pub mod user_service;
+pub mod user_role_service;
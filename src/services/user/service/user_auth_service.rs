@@ -1,27 +1,33 @@
+use std::sync::Arc;
 use anyhow::{Error, Result};
 use async_trait::async_trait;
-use bb8::Pool;
-use bb8_redis::RedisConnectionManager;
-use crate::shared::state::AppState;
 use crate::services::user::command::user_auth_command::{
     UserAuthCreateCommand, 
     UserAuthDeleteCommand, 
     UserAuthGetCommand, 
     UserAuthListCommand, 
+    UserAuthPatchCommand,
     UserAuthUpdateCommand
 };
 use crate::services::user::dto::user_auth_dto::{UserAuthResponse};
 use crate::services::user::model::user_model::UserAuth;
-use crate::services::user::repository::user_auth_repo::{UserAuthRepository, UserAuthRepositoryInterface};
-use crate::shared::database::redis::{delete_key, get_key, set_key};
+use crate::services::user::repository::user_auth_repo::UserAuthRepositoryInterface;
+use crate::shared::cache::invalidation::invalidate_tags;
+use crate::shared::cache::read_through::ReadThrough;
+use crate::shared::cache::key::CacheKeys;
+use crate::shared::cache::store::CacheStore;
+use crate::shared::errors::ServiceError;
+use crate::shared::http::etag::IfMatch;
 
 #[async_trait]
-pub trait UserAuthServiceInterface {
+pub trait UserAuthServiceInterface: Send + Sync {
     async fn get(&self, user_auth_get_command: UserAuthGetCommand) -> Result<Option<UserAuthResponse>, Error>;
 
     async fn create(&self, user_auth_create_command: UserAuthCreateCommand) -> Result<UserAuthResponse, Error>;
     
     async fn update(&self, user_auth_update_command: UserAuthUpdateCommand) -> Result<UserAuthResponse, Error>;
+
+    async fn patch(&self, user_auth_patch_command: UserAuthPatchCommand) -> Result<UserAuthResponse, Error>;
     
     async fn delete(&self, user_auth_delete_command: UserAuthDeleteCommand) -> Result<(), Error>;
     
@@ -29,39 +35,89 @@ pub trait UserAuthServiceInterface {
 }
 
 
+/// Entity name used in the cache tags of user auths
+pub const USER_AUTH_CACHE_ENTITY: &str = "user_auth";
+
+
 #[derive(Clone)]
 pub struct UserAuthService {
-    user_auth_repo: UserAuthRepository,
-    redis_pool: Option<Pool<RedisConnectionManager>>,
+    user_auth_repo: Arc<dyn UserAuthRepositoryInterface>,
+    cache: Option<CacheStore>,
+    cache_keys: CacheKeys,
 }
 
 impl UserAuthService {
-    pub fn new(user_auth_repo: UserAuthRepository, redis_pool: Option<Pool<RedisConnectionManager>>) -> Self {
+    pub fn new(user_auth_repo: Arc<dyn UserAuthRepositoryInterface>, cache: Option<CacheStore>, cache_keys: CacheKeys) -> Self {
         Self {
             user_auth_repo,
-            redis_pool
+            cache,
+            cache_keys
         }
     }
-    
-    pub fn from_app_state(app_state: &AppState) -> Self {
-        let auth_repo = UserAuthRepository::new(app_state.mysql_pool.clone());
-        Self::new(auth_repo, Option::from(app_state.redis_pool.clone()))
+
+    pub fn single_cache_policy(&self) -> ReadThrough {
+        ReadThrough::new(&self.cache_keys).with_local()
     }
 
-    pub fn redis_key_single_ttl(&self) -> Option<u64> {
-        Some(60*60)
+    /// Loads the current state of a user auth and checks its row version against the `If-Match` precondition.
+    async fn get_for_write(&self, user_auth_id: i64, if_match: &IfMatch) -> Result<UserAuth, Error> {
+        let user_auth = self.user_auth_repo.on_primary().get_user_auth(user_auth_id).await
+            .map_err(|_| Error::msg("Error during get user auth"))?
+            .ok_or_else(|| Error::new(ServiceError::NotFound("User auth".to_string())))?;
+
+        if !if_match.matches(user_auth.version.unwrap_or_default()) {
+            return Err(Error::new(ServiceError::VersionMismatch("User auth".to_string())));
+        }
+        Ok(user_auth)
+    }
+
+    /// Writes `user_auth` if its row is still at `version`, then refreshes the cache.
+    async fn save(&self, user_auth_id: i64, version: i64, user_auth: UserAuth) -> Result<UserAuthResponse, Error> {
+        let user_auth = self.user_auth_repo.update_user_auth(user_auth_id, version, user_auth).await;
+        match user_auth {
+            Ok(user_auth) => {
+                match user_auth {
+                    Some(user_auth) => {
+                        let user_auth_response = UserAuthResponse::from(user_auth);
+                        // cached users embed the name of their auth
+                        self.invalidate(user_auth_response.id).await;
+                        self.cache_single(&user_auth_response).await;
+                        Ok(user_auth_response)
+                    },
+                    // the row changed between the version check and the update
+                    None => Err(Error::new(ServiceError::VersionMismatch("User auth".to_string()))),
+                }           
+            },
+            Err(_) => Err(Error::msg("Error updating user auth")),
+        }
+    }
+
+    async fn cache_single(&self, user_auth_response: &UserAuthResponse) {
+        if let Some(cache) = &self.cache {
+            let key = self.form_redis_key_single(&user_auth_response.id);
+            self.single_cache_policy().store(cache, key.as_str(), user_auth_response, &[self.cache_keys.entity_tag(USER_AUTH_CACHE_ENTITY, user_auth_response.id)]).await;
+        }
+    }
+
+    /// Evicts the user auth, its list and every cached key depending on it.
+    async fn invalidate(&self, user_auth_id: i64) {
+        if let Some(cache) = &self.cache {
+            let tags = [self.cache_keys.entity_tag(USER_AUTH_CACHE_ENTITY, user_auth_id), self.cache_keys.list_tag(USER_AUTH_CACHE_ENTITY)];
+            invalidate_tags(cache, &tags).await;
+        }
     }
 
     pub fn form_redis_key_single(&self, key: &i64) -> String {
-        format!("user:auth:{}", key)
+        self.cache_keys.entity(USER_AUTH_CACHE_ENTITY, key)
     }
 
-    pub fn redis_key_list_ttl(&self) -> Option<u64> {
-        Some(60*60)
+    pub fn list_cache_policy(&self) -> ReadThrough {
+        // tiny and read on every user lookup
+        ReadThrough::new(&self.cache_keys).with_local()
     }
 
     pub fn form_redis_key_list(&self) -> String {
-        "user:auth:list".to_string()
+        self.cache_keys.list(USER_AUTH_CACHE_ENTITY)
     }
 }
 
@@ -69,29 +125,22 @@ impl UserAuthService {
 impl UserAuthServiceInterface for UserAuthService {
 
     async fn get(&self, user_auth_get_command: UserAuthGetCommand) -> Result<Option<UserAuthResponse>, Error> {
-        if let Some(redis_pool) = &self.redis_pool {
-            let key = self.form_redis_key_single(&user_auth_get_command.id);
-            let user_auth_cache: Option<UserAuthResponse> = get_key(&redis_pool, key.as_str()).await?;
-            if let Some(user_auth_cache) = user_auth_cache {
-                return Ok(Some(user_auth_cache));
-            }
-        }
-
-        let user_auth = self.user_auth_repo.get_user_auth(user_auth_get_command.id).await;
-        match user_auth {
-            Ok(user_auth) => {
-                match user_auth {
-                    Some(user_auth) => {
-                        if let Some(redis_pool) = &self.redis_pool {
-                            let key = self.form_redis_key_single(&user_auth_get_command.id);
-                            let _: () = set_key(&redis_pool, key.as_str(), &user_auth.clone(), self.redis_key_single_ttl()).await?;
-                        }
-                        Ok(Some(UserAuthResponse::from(user_auth)))
-                    },
-                    None => Err(Error::msg("User auth not found")),
-                }
+        let user_auth_repo = self.user_auth_repo.clone();
+        let user_auth_id = user_auth_get_command.id;
+        let loader = move || async move {
+            let user_auth = user_auth_repo.get_user_auth(user_auth_id).await
+                .map_err(|_| Error::msg("Error during get user auth. Please check if user auth exists in database. If not, please create new user auth using /user/auth/create endpoint. If yes, please check if user auth id is correct. If yes, please try again. If not, please contact support."))?;
+            Ok(user_auth.map(UserAuthResponse::from))
+        };
+
+        match &self.cache {
+            Some(cache) => {
+                let key = self.form_redis_key_single(&user_auth_get_command.id);
+                let cache_keys = self.cache_keys.clone();
+                let tags = move |user_auth_response: &UserAuthResponse| vec![cache_keys.entity_tag(USER_AUTH_CACHE_ENTITY, user_auth_response.id)];
+                self.single_cache_policy().get_or_load(cache, key.as_str(), tags, loader).await
             },
-            Err(_) => Err(Error::msg("Error during get user auth. Please check if user auth exists in database. If not, please create new user auth using /user/auth/create endpoint. If yes, please check if user auth id is correct. If yes, please try again. If not, please contact support.")),
+            None => loader().await,
         }
     }
 
@@ -101,10 +150,10 @@ impl UserAuthServiceInterface for UserAuthService {
         match user_auth {
             Ok(user_auth) => {
                 let user_auth_response = UserAuthResponse::from(user_auth);
-                if let Some(redis_pool) = &self.redis_pool {
-                    let key = self.form_redis_key_single(&user_auth_response.id);
-                    let _: () = set_key(&redis_pool, key.as_str(), &user_auth_response, self.redis_key_single_ttl()).await?;
+                if let Some(cache) = &self.cache {
+                    invalidate_tags(cache, &[self.cache_keys.list_tag(USER_AUTH_CACHE_ENTITY)]).await;
                 }
+                self.cache_single(&user_auth_response).await;
                 Ok(user_auth_response)
             },
             Err(_) => Err(Error::msg("Error creating user auth")),
@@ -112,57 +161,59 @@ impl UserAuthServiceInterface for UserAuthService {
     }
 
     async fn update(&self, user_auth_update_command: UserAuthUpdateCommand) -> Result<UserAuthResponse, Error> {
-        let user_auth_update = UserAuth::new(user_auth_update_command.name, user_auth_update_command.description);
-        let user_auth = self.user_auth_repo.update_user_auth(user_auth_update_command.id, user_auth_update).await;
-        match user_auth {
-            Ok(user_auth) => {
-                match user_auth {
-                    Some(user_auth) => {
-                        let user_auth_response = UserAuthResponse::from(user_auth);
-                        if let Some(redis_pool) = &self.redis_pool {
-                            let key = self.form_redis_key_single(&user_auth_response.id);
-                            let _: () = set_key(&redis_pool, key.as_str(), &user_auth_response, self.redis_key_single_ttl()).await?;
-                        }
-                        Ok(user_auth_response)
-                    },
-                    None => Err(Error::msg("User auth not found")),
-                }           
-            },
-            Err(_) => Err(Error::msg("Error updating user auth")),
-        }
+        let current = self.get_for_write(user_auth_update_command.id, &user_auth_update_command.if_match).await?;
+        let version = current.version.unwrap_or_default();
+
+        let user_auth_update = UserAuth {
+            name: user_auth_update_command.name,
+            description: user_auth_update_command.description,
+            ..current
+        };
+        self.save(user_auth_update_command.id, version, user_auth_update).await
+    }
+
+    async fn patch(&self, user_auth_patch_command: UserAuthPatchCommand) -> Result<UserAuthResponse, Error> {
+        let current = self.get_for_write(user_auth_patch_command.id, &user_auth_patch_command.if_match).await?;
+        let version = current.version.unwrap_or_default();
+
+        let user_auth_update = UserAuth {
+            name: user_auth_patch_command.name.apply_required(current.name, "name")?,
+            description: user_auth_patch_command.description.apply(current.description),
+            ..current
+        };
+        self.save(user_auth_patch_command.id, version, user_auth_update).await
     }
 
     async fn delete(&self, user_auth_delete_command: UserAuthDeleteCommand) -> Result<(), Error> {
-        let user_auth = self.user_auth_repo.delete_user_auth(user_auth_delete_command.id).await;
-        if let Some(redis_pool) = &self.redis_pool {
-            let key = self.form_redis_key_single(&user_auth_delete_command.id);
-            let _: () = delete_key(&redis_pool, key.as_str()).await?;
-        }
+        let current = self.get_for_write(user_auth_delete_command.id, &user_auth_delete_command.if_match).await?;
+
+        let user_auth = self.user_auth_repo.delete_user_auth(user_auth_delete_command.id, current.version.unwrap_or_default()).await;
+        self.invalidate(user_auth_delete_command.id).await;
         match user_auth {
-            Ok(()) => Ok(()),
+            Ok(true) => Ok(()),
+            Ok(false) => Err(Error::new(ServiceError::VersionMismatch("User auth".to_string()))),
             Err(_) => Err(Error::msg("Error deleting user auth")),
         }
     }
 
     async fn get_all(&self, _: UserAuthListCommand) -> Result<Vec<UserAuthResponse>, Error> {
-        if let Some(redis_pool) = &self.redis_pool {
-            let user_auths_cache: Option<Vec<UserAuthResponse>> = get_key(&redis_pool, self.form_redis_key_list().as_str()).await?;
-            if let Some(user_auths_cache) = user_auths_cache {
-                return Ok(user_auths_cache);
-            }
-        }
-
-        let user_auths = self.user_auth_repo.get_all_user_auths().await;
-        match user_auths {
-            Ok(user_auths) => {
-                let user_auths_response: Vec<UserAuthResponse> = user_auths.into_iter().map(UserAuthResponse::from).collect();
-                if let Some(redis_pool) = &self.redis_pool {
-                    let _: () = set_key(&redis_pool, self.form_redis_key_list().as_str(), &user_auths_response, self.redis_key_list_ttl()).await?;
-                }
-                Ok(user_auths_response)
+        // the list is cached, a lagging replica would bring back rows that were just invalidated
+        let user_auth_repo = self.user_auth_repo.on_primary();
+        let loader = move || async move {
+            let user_auths = user_auth_repo.get_all_user_auths().await
+                .map_err(|_| Error::msg("Error getting all user auths"))?;
+            Ok(Some(user_auths.into_iter().map(UserAuthResponse::from).collect::<Vec<_>>()))
+        };
+
+        let user_auths_response = match &self.cache {
+            Some(cache) => {
+                let list_tag = self.cache_keys.list_tag(USER_AUTH_CACHE_ENTITY);
+                let tags = move |_: &Vec<UserAuthResponse>| vec![list_tag.clone()];
+                self.list_cache_policy().get_or_load(cache, self.form_redis_key_list().as_str(), tags, loader).await?
             },
-            Err(_) => Err(Error::msg("Error getting all user auths")),
-        }
+            None => loader().await?,
+        };
+        Ok(user_auths_response.unwrap_or_default())
     }
 
 }
@@ -0,0 +1,243 @@
+use std::marker::PhantomData;
+use std::sync::Arc;
+use anyhow::{Error, Result};
+use async_trait::async_trait;
+use crate::services::user::command::customer_command::CustomerCreateCommand;
+use crate::services::user::command::manager_command::ManagerCreateCommand;
+use crate::services::user::command::user_command::{UserGetCommand, UserListCommand};
+use crate::services::user::dto::user_dto::{CustomerUserResponse, ManagerUserResponse};
+use crate::services::user::model::user_model::{User, USER_AUTH_CUSTOMER, USER_AUTH_MANAGER, USER_STATUS_ACTIVE};
+use crate::services::user::repository::user_auth_repo::UserAuthRepositoryInterface;
+use crate::services::user::repository::user_repo::UserRepositoryInterface;
+use crate::services::user::repository::user_status_repo::UserStatusRepositoryInterface;
+use crate::services::user::service::user_service::{MIN_PASSWORD_LENGTH, USER_CACHE_ENTITY};
+use crate::shared::cache::invalidation::invalidate_tags;
+use crate::shared::cache::key::CacheKeys;
+use crate::shared::cache::store::CacheStore;
+use crate::shared::database::mysql::DuplicateKey;
+use crate::shared::errors::{require_not_blank, ServiceError};
+use crate::shared::security::password::hash_password;
+
+
+/// Auth level whose users are exposed as a resource of their own, e.g. `/api/manager`.
+pub trait UserRole: Send + Sync + 'static {
+    /// Name of the `user_auth` row of the role
+    const AUTH: &'static str;
+
+    type CreateCommand: Send + 'static;
+    type Response: Send + 'static;
+
+    /// Fails with a `Validation` error when a field the role requires is missing.
+    fn validate(command: &Self::CreateCommand) -> Result<(), ServiceError>;
+
+    fn username(command: &Self::CreateCommand) -> &str;
+
+    fn password(command: &Self::CreateCommand) -> &str;
+
+    fn new_user(command: Self::CreateCommand, password_hash: String, auth: i64, status: i64) -> User;
+
+    fn response(user: User) -> Self::Response;
+}
+
+
+/// Hired employees, exposed with their employment details.
+pub struct Manager;
+
+impl UserRole for Manager {
+    const AUTH: &'static str = USER_AUTH_MANAGER;
+
+    type CreateCommand = ManagerCreateCommand;
+    type Response = ManagerUserResponse;
+
+    fn validate(command: &ManagerCreateCommand) -> Result<(), ServiceError> {
+        require_not_blank("first_name", &command.first_name)?;
+        require_not_blank("last_name", &command.last_name)?;
+        require_not_blank("username", &command.username)?;
+        require_not_blank("title", &command.title)
+    }
+
+    fn username(command: &ManagerCreateCommand) -> &str {
+        &command.username
+    }
+
+    fn password(command: &ManagerCreateCommand) -> &str {
+        &command.password
+    }
+
+    fn new_user(command: ManagerCreateCommand, password_hash: String, auth: i64, status: i64) -> User {
+        User::new(
+            command.first_name,
+            command.last_name,
+            command.username,
+            password_hash,
+            auth,
+            status,
+            Some(command.hired_date),
+            Some(command.title),
+            None,
+            None,
+            None
+        )
+    }
+
+    fn response(user: User) -> ManagerUserResponse {
+        ManagerUserResponse::from(user)
+    }
+}
+
+
+/// Shoppers, exposed with their contact details.
+pub struct Customer;
+
+impl UserRole for Customer {
+    const AUTH: &'static str = USER_AUTH_CUSTOMER;
+
+    type CreateCommand = CustomerCreateCommand;
+    type Response = CustomerUserResponse;
+
+    fn validate(command: &CustomerCreateCommand) -> Result<(), ServiceError> {
+        require_not_blank("first_name", &command.first_name)?;
+        require_not_blank("last_name", &command.last_name)?;
+        require_not_blank("username", &command.username)?;
+        require_not_blank("address", &command.address)?;
+        require_not_blank("country", &command.country)?;
+        require_not_blank("phone", &command.phone)
+    }
+
+    fn username(command: &CustomerCreateCommand) -> &str {
+        &command.username
+    }
+
+    fn password(command: &CustomerCreateCommand) -> &str {
+        &command.password
+    }
+
+    fn new_user(command: CustomerCreateCommand, password_hash: String, auth: i64, status: i64) -> User {
+        User::new(
+            command.first_name,
+            command.last_name,
+            command.username,
+            password_hash,
+            auth,
+            status,
+            None,
+            None,
+            Some(command.address),
+            Some(command.country),
+            Some(command.phone)
+        )
+    }
+
+    fn response(user: User) -> CustomerUserResponse {
+        CustomerUserResponse::from(user)
+    }
+}
+
+
+#[async_trait]
+pub trait UserRoleServiceInterface<R: UserRole>: Send + Sync {
+    async fn get(&self, user_get_command: UserGetCommand) -> Result<Option<R::Response>, Error>;
+
+    async fn create(&self, create_command: R::CreateCommand) -> Result<R::Response, Error>;
+
+    async fn get_all(&self, user_list_command: UserListCommand) -> Result<Vec<R::Response>, Error>;
+}
+
+
+/// Users with the auth level of `R`.
+pub struct UserRoleService<R> {
+    user_repo: Arc<dyn UserRepositoryInterface>,
+    user_auth_repo: Arc<dyn UserAuthRepositoryInterface>,
+    user_status_repo: Arc<dyn UserStatusRepositoryInterface>,
+    cache: Option<CacheStore>,
+    cache_keys: CacheKeys,
+    role: PhantomData<R>,
+}
+
+impl<R: UserRole> UserRoleService<R> {
+    pub fn new(
+        user_repo: Arc<dyn UserRepositoryInterface>,
+        user_auth_repo: Arc<dyn UserAuthRepositoryInterface>,
+        user_status_repo: Arc<dyn UserStatusRepositoryInterface>,
+        cache: Option<CacheStore>,
+        cache_keys: CacheKeys
+    ) -> Self {
+        Self {
+            user_repo,
+            user_auth_repo,
+            user_status_repo,
+            cache,
+            cache_keys,
+            role: PhantomData
+        }
+    }
+
+
+    async fn auth_id(&self) -> Result<i64, Error> {
+        self.user_auth_repo.get_user_auth_by_name(R::AUTH.to_string()).await
+            .map_err(|_| Error::msg("Error during get user auth."))?
+            .and_then(|user_auth| user_auth.id)
+            .ok_or_else(|| Error::msg(format!("User auth '{}' is not configured", R::AUTH)))
+    }
+}
+
+#[async_trait]
+impl<R: UserRole> UserRoleServiceInterface<R> for UserRoleService<R> {
+    async fn get(&self, user_get_command: UserGetCommand) -> Result<Option<R::Response>, Error> {
+        let user = self.user_repo.get_user(user_get_command.id).await;
+        match user {
+            // users of another auth level are not part of this resource
+            Ok(user) => Ok(user.filter(|user| user.has_auth(R::AUTH)).map(R::response)),
+            Err(_) => Err(Error::msg(format!("Error during get {}.", R::AUTH))),
+        }
+    }
+
+    async fn create(&self, create_command: R::CreateCommand) -> Result<R::Response, Error> {
+        R::validate(&create_command)?;
+        if R::password(&create_command).chars().count() < MIN_PASSWORD_LENGTH {
+            return Err(Error::new(ServiceError::Validation(format!("password must contain at least {} characters", MIN_PASSWORD_LENGTH))));
+        }
+
+        let auth = self.auth_id().await?;
+        let status = self.user_status_repo.get_user_status_by_name(USER_STATUS_ACTIVE.to_string()).await
+            .map_err(|_| Error::msg("Error during get user status."))?
+            .and_then(|user_status| user_status.id)
+            .ok_or_else(|| Error::msg("User status 'active' is not configured"))?;
+
+        let username = R::username(&create_command).to_string();
+        let password_hash = hash_password(R::password(&create_command))?;
+        // the unique index on the username decides between concurrent sign-ups
+        let user = self.user_repo.create_user(R::new_user(create_command, password_hash, auth, status)).await;
+        match user {
+            Ok(user) => {
+                // the new user belongs to every cached user list and count
+                if let Some(cache) = &self.cache {
+                    invalidate_tags(cache, &[self.cache_keys.list_tag(USER_CACHE_ENTITY)]).await;
+                }
+                Ok(R::response(user))
+            },
+            Err(e) if e.is::<DuplicateKey>() => Err(Error::new(ServiceError::Conflict(format!("Username {} is already taken", username)))),
+            Err(_) => Err(Error::msg(format!("Error during create {}.", R::AUTH))),
+        }
+    }
+
+    async fn get_all(&self, user_list_command: UserListCommand) -> Result<Vec<R::Response>, Error> {
+        let mut limit: Option<u32> = None;
+        let mut offset: Option<u32> = None;
+
+        if let Some(pagination) = user_list_command.pagination {
+            limit = pagination.page_size;
+
+            if let (Some(page_size), Some(page)) = (pagination.page_size, pagination.page) {
+                offset = Some(page * page_size);
+            }
+        }
+
+        let auth = self.auth_id().await?;
+        let users = self.user_repo.get_user_by_auth(auth, limit, offset).await;
+        match users {
+            Ok(users) => Ok(users.into_iter().map(R::response).collect()),
+            Err(_) => Err(Error::msg(format!("Error during get all {}s.", R::AUTH))),
+        }
+    }
+}
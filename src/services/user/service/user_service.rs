@@ -1,43 +1,72 @@
-use anyhow::{Error, Result};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use anyhow::{Context, Error, Result};
 use async_trait::async_trait;
-use bb8::Pool;
-use bb8_redis::RedisConnectionManager;
 use crate::services::user::command::user_command::{
     UserCreateCommand, 
     UserDeleteCommand, 
     UserGetByCountryCommand, 
+    UserGetByIdsCommand,
     UserGetBySearchCommand, 
     UserGetByTitleCommand, 
     UserGetByUsernameCommand, 
     UserGetCommand, 
     UserListCommand, 
+    UserPatchCommand,
+    UserRestoreCommand,
     UserUpdateCommand, 
-    UserUpdatePasswordCommand
+    UserUpdatePasswordCommand,
+    UserUpdateProfilePicUrlCommand,
+    UserUpdateStatusCommand,
+    UserUploadProfilePicCommand
 };
 use crate::services::user::dto::user_dto::UserResponse;
-use crate::services::user::model::user_model::User;
-use crate::services::user::repository::user_repo::{UserRepository, UserRepositoryInterface};
-use crate::shared::database::redis::{delete_key, get_key, set_key};
-use crate::shared::state::AppState;
+use crate::services::user::model::user_model::{User, UserStatus, USER_STATUS_ACTIVE, USER_STATUS_DELETED};
+use crate::services::user::repository::user_repo::UserRepositoryInterface;
+use crate::services::user::repository::user_status_repo::UserStatusRepositoryInterface;
+use crate::services::user::service::user_auth_service::USER_AUTH_CACHE_ENTITY;
+use crate::services::user::service::user_status_service::USER_STATUS_CACHE_ENTITY;
+use crate::shared::cache::invalidation::invalidate_tags;
+use crate::shared::cache::read_through::ReadThrough;
+use crate::shared::cache::key::CacheKeys;
+use crate::shared::cache::store::CacheStore;
+use crate::shared::cache::tag::CacheTag;
+use crate::shared::database::mysql::DuplicateKey;
+use crate::shared::database::unit_of_work::transactional;
+use crate::shared::errors::ServiceError;
+use crate::shared::http::etag::IfMatch;
+use crate::shared::media::{picture_keys, process_profile_picture, thumbnail_key};
+use crate::shared::security::password::{hash_password, verify_password};
+use crate::shared::storage::blob_store::BlobStore;
+use tracing::warn;
 
 #[async_trait]
-pub trait UserServiceInterface {
+pub trait UserServiceInterface: Send + Sync {
     async fn get(&self, user_get_command: UserGetCommand) -> Result<Option<UserResponse>, Error>;
     
     async fn create(&self, user_create_command: UserCreateCommand) -> Result<UserResponse, Error>;
     
     async fn update(&self, user_update_command: UserUpdateCommand) -> Result<Option<UserResponse>, Error>;
+
+    async fn patch(&self, user_patch_command: UserPatchCommand) -> Result<Option<UserResponse>, Error>;
     
     async fn update_password(&self, user_update_password_command: UserUpdatePasswordCommand) -> Result<Option<UserResponse>, Error>;
     
-    async fn update_profile_pic_url(&self, user_id: i64, profile_pic_url: Option<String>) -> Result<Option<UserResponse>, Error>;
+    async fn update_profile_pic_url(&self, user_update_profile_pic_url_command: UserUpdateProfilePicUrlCommand) -> Result<Option<UserResponse>, Error>;
     
-    async fn update_status(&self, user_id: i64, status: i64) -> Result<Option<UserResponse>, Error>;
+    async fn upload_profile_pic(&self, user_upload_profile_pic_command: UserUploadProfilePicCommand) -> Result<Option<UserResponse>, Error>;
+
+    async fn update_status(&self, user_update_status_command: UserUpdateStatusCommand) -> Result<Option<UserResponse>, Error>;
+
+    async fn restore(&self, user_restore_command: UserRestoreCommand) -> Result<Option<UserResponse>, Error>;
     
     async fn delete(&self, user_delete_command: UserDeleteCommand) -> Result<(), Error>;
 
     async fn get_all(&self, user_list_command: UserListCommand) -> Result<Vec<UserResponse>, Error>;
     
+    /// Users of the given ids in the order of the command, unknown ids skipped.
+    async fn get_by_ids(&self, user_get_by_ids_command: UserGetByIdsCommand) -> Result<Vec<UserResponse>, Error>;
+
     async fn get_by_username(&self, user_get_by_username_command: UserGetByUsernameCommand) -> Result<Option<UserResponse>, Error>;
     
     async fn get_by_title(&self, user_get_by_title_command: UserGetByTitleCommand, user_list_command: UserListCommand) -> Result<Vec<UserResponse>, Error>;
@@ -48,31 +77,159 @@ pub trait UserServiceInterface {
 }
 
 
+/// Entity name used in the cache tags of users
+pub const USER_CACHE_ENTITY: &str = "user";
+
+/// Shortest password accepted for new passwords
+pub const MIN_PASSWORD_LENGTH: usize = 8;
+
+/// Most ids resolved by one batch lookup
+pub const MAX_BATCH_IDS: usize = 100;
+
+
+/// Blob key prefix of the pictures uploaded for a user.
+fn profile_pic_prefix(user_id: i64) -> String {
+    format!("users/{}/profile", user_id)
+}
+
+
 #[derive(Clone)]
 pub struct UserService {
-    user_repo: UserRepository,
-    redis_pool: Option<Pool<RedisConnectionManager>>,
+    user_repo: Arc<dyn UserRepositoryInterface>,
+    user_status_repo: Arc<dyn UserStatusRepositoryInterface>,
+    cache: Option<CacheStore>,
+    cache_keys: CacheKeys,
+    blob_store: Arc<dyn BlobStore>,
+    max_upload_bytes: usize,
 }
 
 impl UserService {
-    pub fn new(user_repo: UserRepository, redis_pool: Option<Pool<RedisConnectionManager>>) -> Self {
+    pub fn new(
+        user_repo: Arc<dyn UserRepositoryInterface>,
+        user_status_repo: Arc<dyn UserStatusRepositoryInterface>,
+        cache: Option<CacheStore>,
+        cache_keys: CacheKeys,
+        blob_store: Arc<dyn BlobStore>,
+        max_upload_bytes: usize
+    ) -> Self {
         Self { 
             user_repo, 
-            redis_pool 
+            user_status_repo,
+            cache,
+            cache_keys,
+            blob_store,
+            max_upload_bytes
         }
     }
-    
-    pub fn from_app_state(app_state: &AppState) -> Self {
-        let user_repo = UserRepository::new(app_state.mysql_pool.clone());
-        Self::new(user_repo, Option::from(app_state.redis_pool.clone()))
+
+    pub fn single_cache_policy(&self) -> ReadThrough {
+        ReadThrough::new(&self.cache_keys)
+    }
+
+    /// Loads the current state of a user and checks its row version against the `If-Match` precondition.
+    async fn get_for_write(&self, user_id: i64, if_match: &IfMatch) -> Result<User, Error> {
+        let user = self.user_repo.on_primary().get_user(user_id).await
+            .map_err(|_| Error::msg("Error during get user."))?
+            .ok_or_else(|| Error::new(ServiceError::NotFound("User".to_string())))?;
+
+        if !if_match.matches(user.version.unwrap_or_default()) {
+            return Err(Error::new(ServiceError::VersionMismatch("User".to_string())));
+        }
+        Ok(user)
     }
 
-    pub fn redis_key_single_ttl(&self) -> Option<u64> {
-        Some(60*60)
+    /// Writes the editable fields of `user` if its row is still at `version`, then refreshes the cache.
+    async fn save(&self, user_id: i64, version: i64, user: User) -> Result<Option<UserResponse>, Error> {
+        let user = self.user_repo.update_user(user_id, version, user).await;
+        match user {
+            Ok(user) => match user {
+                Some(user) => {
+                    let user_response = UserResponse::from(user);
+                    self.refresh_cache(&user_response).await;
+                    Ok(Some(user_response))
+                },
+                // the row changed between the version check and the update
+                None => Err(Error::new(ServiceError::VersionMismatch("User".to_string()))),
+            },
+            Err(_) => Err(Error::msg("Error during update user.")),
+        }
+    }
+
+    /// Changes the status of a user and refreshes the cache.
+    async fn set_status(&self, user_id: i64, status: &UserStatus) -> Result<Option<UserResponse>, Error> {
+        let status_id = status.id.ok_or_else(|| Error::msg("User status without id"))?;
+        let user = self.user_repo.update_user_status(user_id, status_id).await;
+        match user {
+            Ok(user) => match user {
+                Some(user) => {
+                    let user_response = UserResponse::from(user);
+                    self.refresh_cache(&user_response).await;
+                    Ok(Some(user_response))
+                },
+                None => Err(Error::new(ServiceError::NotFound("User".to_string()))),
+            },
+            Err(_) => Err(Error::msg("Error during update user status.")),
+        }
+    }
+
+    /// Checks the current password, required when users change their own, and replaces it with `password_hash`.
+    async fn change_password(
+        user_repo: Arc<dyn UserRepositoryInterface>,
+        command: &UserUpdatePasswordCommand,
+        password_hash: String
+    ) -> Result<Option<User>, Error> {
+        let user = user_repo.get_user_for_update(command.id).await
+            .context("Error during get user.")?
+            .ok_or_else(|| Error::new(ServiceError::NotFound("User".to_string())))?;
+
+        match &command.current_password {
+            Some(current_password) if !verify_password(current_password, &user.password) => {
+                return Err(Error::new(ServiceError::Forbidden("current_password does not match".to_string())));
+            },
+            None if command.caller_id == command.id => {
+                return Err(Error::new(ServiceError::Validation("current_password is required to change your own password".to_string())));
+            },
+            _ => {},
+        }
+
+        user_repo.update_user_password(command.id, Some(password_hash)).await
+            .context("Error during update user password.")
+    }
+
+    /// Deletes a replaced picture uploaded for the user and its thumbnails; URLs set by clients
+    /// and pictures of other users are left alone. Failures only leave orphaned objects.
+    async fn delete_profile_pic(&self, user_id: i64, profile_pic_url: &str) {
+        let Some(key) = self.blob_store.key_of(profile_pic_url)
+            .filter(|key| key.starts_with(&format!("{}/", profile_pic_prefix(user_id)))) else {
+            return;
+        };
+        for key in picture_keys(&key) {
+            if let Err(e) = self.blob_store.delete(&key).await {
+                warn!("Failed to delete replaced profile picture {}: {}", key, e);
+            }
+        }
+    }
+
+    /// A cached user embeds the name of its auth and status, so it also carries their entity tags.
+    fn cache_tags(cache_keys: &CacheKeys, user_response: &UserResponse) -> Vec<CacheTag> {
+        vec![
+            cache_keys.entity_tag(USER_CACHE_ENTITY, user_response.id),
+            cache_keys.entity_tag(USER_AUTH_CACHE_ENTITY, user_response.auth.id),
+            cache_keys.entity_tag(USER_STATUS_CACHE_ENTITY, user_response.status.id),
+        ]
+    }
+
+    /// Stores the new state of a user and evicts the lists and counts it may appear in.
+    async fn refresh_cache(&self, user_response: &UserResponse) {
+        if let Some(cache) = &self.cache {
+            invalidate_tags(cache, &[self.cache_keys.list_tag(USER_CACHE_ENTITY)]).await;
+            let key = self.form_redis_key_single(&user_response.id);
+            self.single_cache_policy().store(cache, key.as_str(), user_response, &Self::cache_tags(&self.cache_keys, user_response)).await;
+        }
     }
 
     pub fn form_redis_key_single(&self, key: &i64) -> String {
-        format!("user:{}", key)
+        self.cache_keys.entity(USER_CACHE_ENTITY, key)
     }
 
     pub fn redis_key_list_count_ttl(&self) -> Option<u64> {
@@ -80,37 +237,42 @@ impl UserService {
     }
 
     pub fn form_redis_key_list_count(&self) -> String {
-        "user:list:count".to_string()
+        self.cache_keys.list_count(USER_CACHE_ENTITY)
     }
 }
 
 #[async_trait]
 impl UserServiceInterface for UserService {
     async fn get(&self, user_get_command: UserGetCommand) -> Result<Option<UserResponse>, Error> {
-        if let Some(redis_pool) = &self.redis_pool {
-            let key = self.form_redis_key_single(&user_get_command.id);
-            let user_cache: Option<UserResponse> = get_key(&redis_pool, key.as_str()).await?;
-            if let Some(user_cache) = user_cache {
-                return Ok(Some(user_cache));
-            }
-        }
-        
-        let user = self.user_repo.get_user(user_get_command.id).await;
-        match user {
-            Ok(user) => match user {
-                Some(user) => Ok(Some(UserResponse::from(user))),
-                None => Err(Error::msg("User not found")),
+        let user_repo = self.user_repo.clone();
+        let user_id = user_get_command.id;
+        let loader = move || async move {
+            let user = user_repo.get_user(user_id).await.map_err(|_| Error::msg("Error during get user."))?;
+            Ok(user.map(UserResponse::from))
+        };
+
+        match &self.cache {
+            Some(cache) => {
+                let key = self.form_redis_key_single(&user_get_command.id);
+                let cache_keys = self.cache_keys.clone();
+                let tags = move |user_response: &UserResponse| Self::cache_tags(&cache_keys, user_response);
+                self.single_cache_policy().get_or_load(cache, key.as_str(), tags, loader).await
             },
-            Err(_) => Err(Error::msg("Error during get user.")),
+            None => loader().await,
         }
     }
 
     async fn create(&self, user_create_command: UserCreateCommand) -> Result<UserResponse, Error> {
+        if user_create_command.password.chars().count() < MIN_PASSWORD_LENGTH {
+            return Err(Error::new(ServiceError::Validation(format!("password must contain at least {} characters", MIN_PASSWORD_LENGTH))));
+        }
+
+        let username = user_create_command.username.clone();
         let user_create = User::new(
             user_create_command.first_name, 
             user_create_command.last_name, 
             user_create_command.username, 
-            user_create_command.password.unwrap_or_else(|| "1234567890".to_string()), 
+            hash_password(&user_create_command.password)?, 
             user_create_command.auth, 
             user_create_command.status, 
             user_create_command.hired_date, 
@@ -123,121 +285,183 @@ impl UserServiceInterface for UserService {
         match user {
             Ok(user) => {
                 let user_response = UserResponse::from(user);
-                if let Some(redis_pool) = &self.redis_pool {
-                    let key = self.form_redis_key_single(&user_response.id);
-                    let _: () = set_key(&redis_pool, key.as_str(), &user_response, self.redis_key_single_ttl()).await?;
-                }
+                self.refresh_cache(&user_response).await;
                 Ok(user_response)
             },
+            Err(e) if e.is::<DuplicateKey>() => Err(Error::new(ServiceError::Conflict(format!("Username {} is already taken", username)))),
             Err(_) => Err(Error::msg("Error during create user.")),
         }
     }
 
     async fn update(&self, user_update_command: UserUpdateCommand) -> Result<Option<UserResponse>, Error> {
-        let user_update = User::new(
-            user_update_command.first_name, 
-            user_update_command.last_name, 
-            "".to_string(),
-            "".to_string(), 
-            0, 
-            0, 
-            user_update_command.hired_date, 
-            user_update_command.title, 
-            user_update_command.address, 
-            user_update_command.country, 
-            user_update_command.phone
-        );
-        let user = self.user_repo.update_user(user_update_command.id, user_update).await;
-        match user {
-            Ok(user) => match user {
-                Some(user) => {
-                    let user_response = UserResponse::from(user);
-                    if let Some(redis_pool) = &self.redis_pool {
-                        let key = self.form_redis_key_single(&user_response.id);
-                        let _: () = set_key(&redis_pool, key.as_str(), &user_response, self.redis_key_single_ttl()).await?;   
-                    }
-                    Ok(Some(user_response))
-                },
-                None => Err(Error::msg("User not found")),
-            },
-            Err(_) => Err(Error::msg("Error during update user.")),
-        }
+        let current = self.get_for_write(user_update_command.id, &user_update_command.if_match).await?;
+        let version = current.version.unwrap_or_default();
+
+        let user_update = User {
+            first_name: user_update_command.first_name,
+            last_name: user_update_command.last_name,
+            hired_date: user_update_command.hired_date,
+            title: user_update_command.title,
+            address: user_update_command.address,
+            country: user_update_command.country,
+            phone: user_update_command.phone,
+            ..current
+        };
+        self.save(user_update_command.id, version, user_update).await
+    }
+
+    async fn patch(&self, user_patch_command: UserPatchCommand) -> Result<Option<UserResponse>, Error> {
+        let current = self.get_for_write(user_patch_command.id, &user_patch_command.if_match).await?;
+        let version = current.version.unwrap_or_default();
+
+        let user_update = User {
+            first_name: user_patch_command.first_name.apply_required(current.first_name, "first_name")?,
+            last_name: user_patch_command.last_name.apply_required(current.last_name, "last_name")?,
+            hired_date: user_patch_command.hired_date.apply(current.hired_date),
+            title: user_patch_command.title.apply(current.title),
+            address: user_patch_command.address.apply(current.address),
+            country: user_patch_command.country.apply(current.country),
+            phone: user_patch_command.phone.apply(current.phone),
+            ..current
+        };
+        self.save(user_patch_command.id, version, user_update).await
     }
 
     async fn update_password(&self, user_update_password_command: UserUpdatePasswordCommand) -> Result<Option<UserResponse>, Error> {
-        let user = self.user_repo.get_user_by_username(user_update_password_command.username.clone()).await;
-        
+        if user_update_password_command.password.chars().count() < MIN_PASSWORD_LENGTH {
+            return Err(Error::new(ServiceError::Validation(format!("password must contain at least {} characters", MIN_PASSWORD_LENGTH))));
+        }
+
+        let password_hash = hash_password(&user_update_password_command.password)?;
+        // the check of the current password and the update see the same row
+        let user = match self.user_repo.unit_of_work_pools() {
+            Some(pools) => transactional(pools, |unit_of_work| {
+                let user_repo = self.user_repo.in_unit_of_work(&unit_of_work);
+                Self::change_password(user_repo, &user_update_password_command, password_hash.clone())
+            }).await?,
+            None => Self::change_password(self.user_repo.clone(), &user_update_password_command, password_hash).await?,
+        };
+
         match user {
-            Ok(user) => match user {
-                Some(user) => {
-                    let user = self.user_repo.update_user_password(user.id.unwrap(), user_update_password_command.password).await;
-                    match user {
-                        Ok(user) => match user {
-                            Some(user) => {
-                                let user_response = UserResponse::from(user);
-                                if let Some(redis_pool) = &self.redis_pool {
-                                    let key = self.form_redis_key_single(&user_response.id);
-                                    let _: () = set_key(&redis_pool, key.as_str(), &user_response, self.redis_key_single_ttl()).await?;
-                                }
-                                Ok(Some(user_response))
-                            },
-                            None => Err(Error::msg("User not found")),
-                        },
-                        Err(_) => Err(Error::msg("Error during update user password.")),
-                    }
-                },
-                None => Err(Error::msg("User not found")),
+            Some(user) => {
+                let user_response = UserResponse::from(user);
+                self.refresh_cache(&user_response).await;
+                Ok(Some(user_response))
             },
-            Err(_) => Err(Error::msg("Error during get user by username. "))
+            None => Err(Error::new(ServiceError::NotFound("User".to_string()))),
         }
     }
 
-    async fn update_profile_pic_url(&self, user_id: i64, profile_pic_url: Option<String>) -> Result<Option<UserResponse>, Error> {
-        let user = self.user_repo.update_user_profile_pic_url(user_id, profile_pic_url).await;
+    async fn update_profile_pic_url(&self, user_update_profile_pic_url_command: UserUpdateProfilePicUrlCommand) -> Result<Option<UserResponse>, Error> {
+        let user_id = user_update_profile_pic_url_command.id;
+        let previous_url = self.user_repo.on_primary().get_user(user_id).await
+            .map_err(|_| Error::msg("Error during get user."))?
+            .and_then(|user| user.profile_pic_url);
+        let user = self.user_repo.update_user_profile_pic_url(
+            user_id,
+            user_update_profile_pic_url_command.profile_pic_url
+        ).await;
         
         match user {
             Ok(user) => match user {
                 Some(user) => {
                     let user_response = UserResponse::from(user);
-                    if let Some(redis_pool) = &self.redis_pool {
-                        let key = self.form_redis_key_single(&user_response.id);
-                        let _: () = set_key(&redis_pool, key.as_str(), &user_response, self.redis_key_single_ttl()).await?;
+                    self.refresh_cache(&user_response).await;
+                    if let Some(previous_url) = previous_url
+                        && user_response.profile_pic_url.as_ref() != Some(&previous_url) {
+                        self.delete_profile_pic(user_id, &previous_url).await;
                     }
                     Ok(Some(user_response))
                 },
-                None => Err(Error::msg("User not found")),
+                None => Err(Error::new(ServiceError::NotFound("User".to_string()))),
             },
             Err(_) => Err(Error::msg("Error during update user profile pic url.")),
         }
     }
 
-    async fn update_status(&self, user_id: i64, status: i64) -> Result<Option<UserResponse>, Error> {
-        let user = self.user_repo.update_user_status(user_id, status).await;
-        match user {
-            Ok(user) => match user {
-                Some(user) => {
-                    let user_response = UserResponse::from(user);
-                    if let Some(redis_pool) = &self.redis_pool {
-                        let key = self.form_redis_key_single(&user_response.id);
-                        let _: () = set_key(&redis_pool, key.as_str(), &user_response, self.redis_key_single_ttl()).await?;
-                    }
-                    Ok(Some(user_response))
-                },
-                None => Err(Error::msg("User not found")),
-            },
-            Err(_) => Err(Error::msg("Error during update user status.")),       
+    async fn upload_profile_pic(&self, user_upload_profile_pic_command: UserUploadProfilePicCommand) -> Result<Option<UserResponse>, Error> {
+        let user_id = user_upload_profile_pic_command.id;
+        if self.user_repo.on_primary().get_user(user_id).await.map_err(|_| Error::msg("Error during get user."))?.is_none() {
+            return Err(Error::new(ServiceError::NotFound("User".to_string())));
+        }
+
+        let max_upload_bytes = self.max_upload_bytes;
+        let content = user_upload_profile_pic_command.content;
+        let image = tokio::task::spawn_blocking(move || process_profile_picture(content, max_upload_bytes)).await??;
+
+        // thumbnails are stored next to the picture as `<name>_<size>.<ext>`
+        let name = format!("{}/{}", profile_pic_prefix(user_id), uuid::Uuid::new_v4());
+        let key = format!("{}.{}", name, image.extension());
+        let (thumbnail_extension, thumbnail_content_type) = (image.thumbnail_extension(), image.thumbnail_content_type());
+        let content_type = image.content_type();
+        for (size, thumbnail) in image.thumbnails {
+            self.blob_store.put(&thumbnail_key(&name, size, thumbnail_extension), thumbnail, thumbnail_content_type).await?;
+        }
+        self.blob_store.put(&key, image.original, content_type).await?;
+
+        let user_update_profile_pic_url_command = UserUpdateProfilePicUrlCommand {
+            id: user_id,
+            profile_pic_url: Some(self.blob_store.public_url(&key)),
+        };
+        self.update_profile_pic_url(user_update_profile_pic_url_command).await
+    }
+
+    async fn update_status(&self, user_update_status_command: UserUpdateStatusCommand) -> Result<Option<UserResponse>, Error> {
+        let user = self.user_repo.on_primary().get_user(user_update_status_command.id).await
+            .map_err(|_| Error::msg("Error during get user."))?
+            .ok_or_else(|| Error::new(ServiceError::NotFound("User".to_string())))?;
+
+        let status = self.user_status_repo.on_primary().get_user_status(user_update_status_command.status).await
+            .map_err(|_| Error::msg("Error during get user status."))?
+            .ok_or_else(|| Error::new(ServiceError::Validation(format!("Unknown user status {}", user_update_status_command.status))))?;
+
+        // users are deleted through DELETE, and only leave that status through a restore
+        if status.is_deleted() {
+            return Err(Error::new(ServiceError::Validation("Users cannot be moved to the deleted status, delete them instead".to_string())));
         }
+        if user.is_deleted() {
+            return Err(Error::new(ServiceError::Conflict("Deleted users must be restored before changing their status".to_string())));
+        }
+
+        self.set_status(user_update_status_command.id, &status).await
+    }
+
+    async fn restore(&self, user_restore_command: UserRestoreCommand) -> Result<Option<UserResponse>, Error> {
+        let user = self.user_repo.on_primary().get_user(user_restore_command.id).await
+            .map_err(|_| Error::msg("Error during get user."))?
+            .ok_or_else(|| Error::new(ServiceError::NotFound("User".to_string())))?;
+
+        if !user.is_deleted() {
+            return Err(Error::new(ServiceError::Conflict("Only deleted users can be restored".to_string())));
+        }
+
+        let status = self.user_status_repo.on_primary().get_user_status_by_name(USER_STATUS_ACTIVE.to_string()).await
+            .map_err(|_| Error::msg("Error during get user status."))?
+            .ok_or_else(|| Error::msg("User status 'active' is not configured"))?;
+
+        self.set_status(user_restore_command.id, &status).await
     }
 
     async fn delete(&self, user_delete_command: UserDeleteCommand) -> Result<(), Error> {
-        let result = self.user_repo.delete_user(user_delete_command.id).await;
-        if let Some(redis_pool) = &self.redis_pool {
-            let key = self.form_redis_key_single(&user_delete_command.id);
-            let _: () = delete_key(&redis_pool, key.as_str()).await?;
+        let current = self.get_for_write(user_delete_command.id, &user_delete_command.if_match).await?;
+        if current.is_deleted() {
+            return Err(Error::new(ServiceError::Conflict("User is already deleted".to_string())));
+        }
+
+        let status = self.user_status_repo.on_primary().get_user_status_by_name(USER_STATUS_DELETED.to_string()).await
+            .map_err(|_| Error::msg("Error during get user status."))?
+            .ok_or_else(|| Error::msg("User status 'deleted' is not configured"))?;
+        let status_id = status.id.ok_or_else(|| Error::msg("User status without id"))?;
+
+        let result = self.user_repo.delete_user(user_delete_command.id, status_id, current.version.unwrap_or_default()).await;
+        if let Some(cache) = &self.cache {
+            let tags = [self.cache_keys.entity_tag(USER_CACHE_ENTITY, user_delete_command.id), self.cache_keys.list_tag(USER_CACHE_ENTITY)];
+            invalidate_tags(cache, &tags).await;
         }
         match result {
-            Ok(_) => Ok(()),
-            Err(_) => Err(Error::msg("Error during delete user.")),       
+            Ok(true) => Ok(()),
+            Ok(false) => Err(Error::new(ServiceError::VersionMismatch("User".to_string()))),
+            Err(_) => Err(Error::msg("Error during delete user.")),
         }
     }
 
@@ -259,6 +483,41 @@ impl UserServiceInterface for UserService {
         }
     }
 
+    async fn get_by_ids(&self, user_get_by_ids_command: UserGetByIdsCommand) -> Result<Vec<UserResponse>, Error> {
+        let mut ids = user_get_by_ids_command.ids;
+        if ids.len() > MAX_BATCH_IDS {
+            return Err(Error::new(ServiceError::Validation(format!("ids must contain at most {} ids", MAX_BATCH_IDS))));
+        }
+        let mut seen = HashSet::new();
+        ids.retain(|id| seen.insert(*id));
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // the users are cached, a lagging replica would bring back rows that were just invalidated
+        let user_repo = self.user_repo.on_primary();
+        let loader = move |user_ids: Vec<i64>| async move {
+            let users = user_repo.get_users_by_ids(user_ids).await.map_err(|_| Error::msg("Error during get users by ids."))?;
+            Ok(users.into_iter()
+                .map(|user| {
+                    let user_response = UserResponse::from(user);
+                    (user_response.id, user_response)
+                })
+                .collect::<HashMap<_, _>>())
+        };
+
+        // cached users come from one MGET, the others from one query, then are cached
+        let mut users = match &self.cache {
+            Some(cache) => {
+                let key_of = |user_id: &i64| self.form_redis_key_single(user_id);
+                let tags = |user_response: &UserResponse| Self::cache_tags(&self.cache_keys, user_response);
+                self.single_cache_policy().get_many_or_load(cache, &ids, key_of, tags, loader).await?
+            },
+            None => loader(ids.clone()).await?,
+        };
+        Ok(ids.iter().filter_map(|id| users.remove(id)).collect())
+    }
+
     async fn get_by_username(&self, user_get_by_username_command: UserGetByUsernameCommand) -> Result<Option<UserResponse>, Error> {
         let user = self.user_repo.get_user_by_username(user_get_by_username_command.username).await;
         match user {
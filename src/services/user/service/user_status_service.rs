@@ -1,59 +1,115 @@
+use std::sync::Arc;
 use anyhow::{Error, Result};
 use async_trait::async_trait;
-use bb8::Pool;
-use bb8_redis::RedisConnectionManager;
-use crate::services::user::command::user_status_command::{UserStatusCreateCommand, UserStatusDeleteCommand, UserStatusGetCommand, UserStatusListCommand, UserStatusUpdateCommand};
+use crate::services::user::command::user_status_command::{UserStatusCreateCommand, UserStatusDeleteCommand, UserStatusGetCommand, UserStatusListCommand, UserStatusPatchCommand, UserStatusUpdateCommand};
 use crate::services::user::dto::user_status_dto::{UserStatusResponse};
 use crate::services::user::model::user_model::UserStatus;
-use crate::services::user::repository::user_status_repo::{UserStatusRepository, UserStatusRepositoryInterface};
-use crate::shared::database::redis::{delete_key, get_key, set_key};
+use crate::services::user::repository::user_status_repo::UserStatusRepositoryInterface;
+use crate::shared::cache::invalidation::invalidate_tags;
+use crate::shared::cache::read_through::ReadThrough;
+use crate::shared::cache::key::CacheKeys;
+use crate::shared::cache::store::CacheStore;
+use crate::shared::errors::ServiceError;
+use crate::shared::http::etag::IfMatch;
 
 #[async_trait]
-pub trait UserStatusServiceInterface {
+pub trait UserStatusServiceInterface: Send + Sync {
     async fn get(&self, user_status_get_command: UserStatusGetCommand) -> Result<Option<UserStatusResponse>, Error>;
 
     async fn create(&self, user_status_create_command: UserStatusCreateCommand) -> Result<UserStatusResponse, Error>;
 
     async fn update(&self, user_status_update_command: UserStatusUpdateCommand) -> Result<UserStatusResponse, Error>;
 
+    async fn patch(&self, user_status_patch_command: UserStatusPatchCommand) -> Result<UserStatusResponse, Error>;
+
     async fn delete(&self, user_status_delete_command: UserStatusDeleteCommand) -> Result<(), Error>;
 
     async fn get_all(&self, _: UserStatusListCommand) -> Result<Vec<UserStatusResponse>, Error>;
 }
 
+/// Entity name used in the cache tags of user statuses
+pub const USER_STATUS_CACHE_ENTITY: &str = "user_status";
+
+
 #[derive(Clone)]
 pub struct UserStatusService {
-    user_status_repo: UserStatusRepository,
-    redis_pool: Option<Pool<RedisConnectionManager>>,
+    user_status_repo: Arc<dyn UserStatusRepositoryInterface>,
+    cache: Option<CacheStore>,
+    cache_keys: CacheKeys,
 }
 
 impl UserStatusService {
-    pub fn new(user_status_repo: UserStatusRepository, redis_pool: Option<Pool<RedisConnectionManager>>) -> Self {
+    pub fn new(user_status_repo: Arc<dyn UserStatusRepositoryInterface>, cache: Option<CacheStore>, cache_keys: CacheKeys) -> Self {
         Self {
             user_status_repo,
-            redis_pool
+            cache,
+            cache_keys
         }
     }
-    
-    pub fn from_app_state(app_state: &crate::shared::state::AppState) -> Self {
-        let status_repo = UserStatusRepository::new(app_state.mysql_pool.clone());
-        Self::new(status_repo, Option::from(app_state.redis_pool.clone()))
+
+    pub fn single_cache_policy(&self) -> ReadThrough {
+        ReadThrough::new(&self.cache_keys).with_local()
     }
 
-    pub fn redis_key_single_ttl(&self) -> Option<u64> {
-        Some(60*60)
+    /// Loads the current state of a user status and checks its row version against the `If-Match` precondition.
+    async fn get_for_write(&self, user_status_id: i64, if_match: &IfMatch) -> Result<UserStatus, Error> {
+        let user_status = self.user_status_repo.on_primary().get_user_status(user_status_id).await
+            .map_err(|_| Error::msg("Error during get user status"))?
+            .ok_or_else(|| Error::new(ServiceError::NotFound("User status".to_string())))?;
+
+        if !if_match.matches(user_status.version.unwrap_or_default()) {
+            return Err(Error::new(ServiceError::VersionMismatch("User status".to_string())));
+        }
+        Ok(user_status)
     }
 
-    pub fn redis_key_list_ttl(&self) -> Option<u64> {
-        Some(60*60)
+    /// Writes `user_status` if its row is still at `version`, then refreshes the cache.
+    async fn save(&self, user_status_id: i64, version: i64, user_status: UserStatus) -> Result<UserStatusResponse, Error> {
+        let user_status = self.user_status_repo.update_user_status(user_status_id, version, user_status).await;
+        match user_status {
+            Ok(user_status) => {
+                match user_status {
+                    Some(user_status) => {
+                        let user_status_response = UserStatusResponse::from(user_status);
+                        // cached users embed the name of their status
+                        self.invalidate(user_status_response.id).await;
+                        self.cache_single(&user_status_response).await;
+                        Ok(user_status_response)
+                    },
+                    // the row changed between the version check and the update
+                    None => Err(Error::new(ServiceError::VersionMismatch("User status".to_string()))),
+                }
+            },
+            Err(_) => Err(Error::msg("Error updating user status")),
+        }
+    }
+
+    pub fn list_cache_policy(&self) -> ReadThrough {
+        // tiny and read on every user lookup
+        ReadThrough::new(&self.cache_keys).with_local()
+    }
+
+    async fn cache_single(&self, user_status_response: &UserStatusResponse) {
+        if let Some(cache) = &self.cache {
+            let key = self.form_redis_key_single(&user_status_response.id);
+            self.single_cache_policy().store(cache, key.as_str(), user_status_response, &[self.cache_keys.entity_tag(USER_STATUS_CACHE_ENTITY, user_status_response.id)]).await;
+        }
+    }
+
+    /// Evicts the user status, its list and every cached key depending on it.
+    async fn invalidate(&self, user_status_id: i64) {
+        if let Some(cache) = &self.cache {
+            let tags = [self.cache_keys.entity_tag(USER_STATUS_CACHE_ENTITY, user_status_id), self.cache_keys.list_tag(USER_STATUS_CACHE_ENTITY)];
+            invalidate_tags(cache, &tags).await;
+        }
     }
 
     pub fn form_redis_key_single(&self, key: &i64) -> String {
-        format!("user:status:{}", key)
+        self.cache_keys.entity(USER_STATUS_CACHE_ENTITY, key)
     }
 
     pub fn form_redis_key_list(&self) -> String {
-        "user:status:list".to_string()
+        self.cache_keys.list(USER_STATUS_CACHE_ENTITY)
     }
 }
 
@@ -61,36 +117,35 @@ impl UserStatusService {
 impl UserStatusServiceInterface for UserStatusService {
 
     async fn get(&self, user_status_get_command: UserStatusGetCommand) -> Result<Option<UserStatusResponse>, Error> {
-        if let Some(redis_pool) = &self.redis_pool {
-            let key = self.form_redis_key_single(&user_status_get_command.id);
-            let user_status_cache: Option<UserStatusResponse> = get_key(&redis_pool, key.as_str()).await?;
-            if let Some(user_status_cache) = user_status_cache {
-                return Ok(Some(user_status_cache));
-            }
-        }
-
-        let user_status = self.user_status_repo.get_user_status(user_status_get_command.id).await;
-        match user_status {
-            Ok(user_status) => {
-                match user_status {
-                    Some(user_status) => Ok(Some(UserStatusResponse::from(user_status))),
-                    None => Err(Error::msg("User status not found")),
-                }
+        let user_status_repo = self.user_status_repo.clone();
+        let user_status_id = user_status_get_command.id;
+        let loader = move || async move {
+            let user_status = user_status_repo.get_user_status(user_status_id).await
+                .map_err(|_| Error::msg("Error during get user status. Please check if user status exists in database. If not, please create new user status using /user-status/create endpoint. If yes, please check if user status id is correct. If yes, please try again. If not, please contact support."))?;
+            Ok(user_status.map(UserStatusResponse::from))
+        };
+
+        match &self.cache {
+            Some(cache) => {
+                let key = self.form_redis_key_single(&user_status_get_command.id);
+                let cache_keys = self.cache_keys.clone();
+                let tags = move |user_status_response: &UserStatusResponse| vec![cache_keys.entity_tag(USER_STATUS_CACHE_ENTITY, user_status_response.id)];
+                self.single_cache_policy().get_or_load(cache, key.as_str(), tags, loader).await
             },
-            Err(_) => Err(Error::msg("Error during get user status. Please check if user status exists in database. If not, please create new user status using /user-status/create endpoint. If yes, please check if user status id is correct. If yes, please try again. If not, please contact support.")),
+            None => loader().await,
         }
     }
 
     async fn create(&self, user_status_create_command: UserStatusCreateCommand) -> Result<UserStatusResponse, Error> {
         let user_status_create = UserStatus::new(user_status_create_command.name, user_status_create_command.description);
-        let user_status = self.user_status_repo.create_user_status(UserStatus::from(user_status_create)).await;
+        let user_status = self.user_status_repo.create_user_status(user_status_create).await;
         match user_status {
             Ok(user_status) => {
                 let user_status_response = UserStatusResponse::from(user_status);
-                if let Some(redis_pool) = &self.redis_pool {
-                    let key = self.form_redis_key_single(&user_status_response.id);
-                    let _: () = set_key(&redis_pool, key.as_str(), &user_status_response, self.redis_key_single_ttl()).await?;
+                if let Some(cache) = &self.cache {
+                    invalidate_tags(cache, &[self.cache_keys.list_tag(USER_STATUS_CACHE_ENTITY)]).await;
                 }
+                self.cache_single(&user_status_response).await;
                 Ok(user_status_response)
             },
             Err(_) => Err(Error::msg("Error creating user status")),
@@ -98,57 +153,59 @@ impl UserStatusServiceInterface for UserStatusService {
     }
 
     async fn update(&self, user_status_update_command: UserStatusUpdateCommand) -> Result<UserStatusResponse, Error> {
-        let user_status_update = UserStatus::new(user_status_update_command.name, user_status_update_command.description);
-        let user_status = self.user_status_repo.update_user_status(user_status_update_command.id, user_status_update).await;
-        match user_status {
-            Ok(user_status) => {
-                match user_status {
-                    Some(user_status) => {
-                        let user_status_response = UserStatusResponse::from(user_status);
-                        if let Some(redis_pool) = &self.redis_pool {
-                            let key = self.form_redis_key_single(&user_status_response.id);
-                            let _: () = set_key(&redis_pool, key.as_str(), &user_status_response, self.redis_key_single_ttl()).await?;
-                        }
-                        Ok(user_status_response)
-                    },
-                    None => Err(Error::msg("User status not found")),
-                }
-            },
-            Err(_) => Err(Error::msg("Error updating user status")),
-        }
+        let current = self.get_for_write(user_status_update_command.id, &user_status_update_command.if_match).await?;
+        let version = current.version.unwrap_or_default();
+
+        let user_status_update = UserStatus {
+            name: user_status_update_command.name,
+            description: user_status_update_command.description,
+            ..current
+        };
+        self.save(user_status_update_command.id, version, user_status_update).await
     }
-    
+
+    async fn patch(&self, user_status_patch_command: UserStatusPatchCommand) -> Result<UserStatusResponse, Error> {
+        let current = self.get_for_write(user_status_patch_command.id, &user_status_patch_command.if_match).await?;
+        let version = current.version.unwrap_or_default();
+
+        let user_status_update = UserStatus {
+            name: user_status_patch_command.name.apply_required(current.name, "name")?,
+            description: user_status_patch_command.description.apply(current.description),
+            ..current
+        };
+        self.save(user_status_patch_command.id, version, user_status_update).await
+    }
+
     async fn delete(&self, user_status_delete_command: UserStatusDeleteCommand) -> Result<(), Error> {
-        let user_status = self.user_status_repo.delete_user_status(user_status_delete_command.id).await;
-        if let Some(redis_pool) = &self.redis_pool {
-            let key = self.form_redis_key_single(&user_status_delete_command.id);
-            let _: () = delete_key(&redis_pool, key.as_str()).await?;
-        }
+        let current = self.get_for_write(user_status_delete_command.id, &user_status_delete_command.if_match).await?;
+
+        let user_status = self.user_status_repo.delete_user_status(user_status_delete_command.id, current.version.unwrap_or_default()).await;
+        self.invalidate(user_status_delete_command.id).await;
         match user_status {
-            Ok(()) => Ok(()),
+            Ok(true) => Ok(()),
+            Ok(false) => Err(Error::new(ServiceError::VersionMismatch("User status".to_string()))),
             Err(_) => Err(Error::msg("Error deleting user status")),
         }
     }
     
     async fn get_all(&self, _: UserStatusListCommand) -> Result<Vec<UserStatusResponse>, Error> {
-        if let Some(redis_pool) = &self.redis_pool {
-            let user_statuses_cache: Option<Vec<UserStatusResponse>> = get_key(&redis_pool, self.form_redis_key_list().as_str()).await?;
-            if let Some(user_statuses_cache) = user_statuses_cache {
-                return Ok(user_statuses_cache);
-            }
-        }
-
-        let user_statuses = self.user_status_repo.get_all_user_status().await;
-        match user_statuses {
-            Ok(user_statuses) => {
-                let user_statuses_response: Vec<UserStatusResponse> = user_statuses.into_iter().map(UserStatusResponse::from).collect();
-                if let Some(redis_pool) = &self.redis_pool {
-                    let _: () = set_key(&redis_pool, self.form_redis_key_list().as_str(), &user_statuses_response, self.redis_key_list_ttl()).await?;
-                }
-                Ok(user_statuses_response)
+        // the list is cached, a lagging replica would bring back rows that were just invalidated
+        let user_status_repo = self.user_status_repo.on_primary();
+        let loader = move || async move {
+            let user_statuses = user_status_repo.get_all_user_status().await
+                .map_err(|_| Error::msg("Error getting all user statuses"))?;
+            Ok(Some(user_statuses.into_iter().map(UserStatusResponse::from).collect::<Vec<_>>()))
+        };
+
+        let user_statuses_response = match &self.cache {
+            Some(cache) => {
+                let list_tag = self.cache_keys.list_tag(USER_STATUS_CACHE_ENTITY);
+                let tags = move |_: &Vec<UserStatusResponse>| vec![list_tag.clone()];
+                self.list_cache_policy().get_or_load(cache, self.form_redis_key_list().as_str(), tags, loader).await?
             },
-            Err(_) => Err(Error::msg("Error getting all user statuses")),
-        }
+            None => loader().await?,
+        };
+        Ok(user_statuses_response.unwrap_or_default())
     }
 
 }
@@ -0,0 +1,235 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use bb8::{PooledConnection, RunError};
+use bb8_redis::RedisConnectionManager;
+use redis::{AsyncCommands, ErrorKind, RedisError, RedisResult, Script};
+
+use crate::shared::database::redis::RedisDatabase;
+
+
+/// Writes the value and registers the key in the set of each tag.
+/// Tag sets live at least as long as the keys they reference.
+const SET_TAGGED_SCRIPT: &str = r#"
+local ttl = tonumber(ARGV[2])
+if ttl > 0 then
+    redis.call('SET', KEYS[1], ARGV[1], 'EX', ttl)
+else
+    redis.call('SET', KEYS[1], ARGV[1])
+end
+for i = 2, #KEYS do
+    local existed = redis.call('EXISTS', KEYS[i])
+    redis.call('SADD', KEYS[i], KEYS[1])
+    if ttl > 0 then
+        local current = redis.call('TTL', KEYS[i])
+        if existed == 0 or (current >= 0 and current < ttl) then
+            redis.call('EXPIRE', KEYS[i], ttl)
+        end
+    else
+        redis.call('PERSIST', KEYS[i])
+    end
+end
+return 1
+"#;
+
+/// Deletes every key registered in the given tag sets, then the sets themselves.
+/// When a channel is given, publishes the tag set keys on it for the in-process caches.
+const INVALIDATE_TAGS_SCRIPT: &str = r#"
+local removed = 0
+for i = 1, #KEYS do
+    local members = redis.call('SMEMBERS', KEYS[i])
+    for _, key in ipairs(members) do
+        removed = removed + redis.call('DEL', key)
+    end
+    redis.call('DEL', KEYS[i])
+end
+if #ARGV > 0 then
+    redis.call('PUBLISH', ARGV[1], ARGV[2])
+end
+return removed
+"#;
+
+/// Deletes the key only if it still holds the given value.
+const DELETE_IF_EQUALS_SCRIPT: &str = r#"
+if redis.call('GET', KEYS[1]) == ARGV[1] then
+    return redis.call('DEL', KEYS[1])
+end
+return 0
+"#;
+
+/// Takes a token from the bucket at KEYS[1], holding up to ARGV[1] tokens refilled over ARGV[2] ms.
+/// Time comes from the Redis server so that every instance shares the same clock.
+/// Returns allowed (0 or 1), remaining tokens, ms until the bucket is full and ms until the next token.
+const TAKE_TOKEN_SCRIPT: &str = r#"
+local capacity = tonumber(ARGV[1])
+local window = tonumber(ARGV[2])
+local rate = capacity / window
+local time = redis.call('TIME')
+local now = tonumber(time[1]) * 1000 + math.floor(tonumber(time[2]) / 1000)
+local bucket = redis.call('HMGET', KEYS[1], 'tokens', 'at')
+local tokens = tonumber(bucket[1]) or capacity
+local at = tonumber(bucket[2]) or now
+tokens = math.min(capacity, tokens + math.max(0, now - at) * rate)
+local allowed = 0
+if tokens >= 1 then
+    tokens = tokens - 1
+    allowed = 1
+end
+redis.call('HSET', KEYS[1], 'tokens', tostring(tokens), 'at', now)
+redis.call('PEXPIRE', KEYS[1], window)
+local retry = 0
+if allowed == 0 then
+    retry = math.ceil((1 - tokens) / rate)
+end
+return {allowed, math.floor(tokens), math.ceil((capacity - tokens) / rate), retry}
+"#;
+
+
+/// State of a token bucket after an attempt to take a token from it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TokenBucket {
+    pub allowed: bool,
+    pub remaining: u32,
+    /// Until the bucket is full again
+    pub reset_after: Duration,
+    /// Until the next token, zero when the attempt was allowed
+    pub retry_after: Duration,
+}
+
+
+/// Storage operations the cache is built on, each one atomic.
+///
+/// Implemented over Redis by [`RedisCacheBackend`]; other implementations, such as an in-memory
+/// store for tests, only have to honour the same expiry and tag semantics.
+#[async_trait]
+pub trait CacheBackend: Send + Sync {
+    async fn ping(&self) -> RedisResult<()>;
+
+    async fn get(&self, key: &str) -> RedisResult<Option<String>>;
+
+    /// Values of `keys` in the same order, `None` for the missing ones.
+    async fn get_many(&self, keys: &[&str]) -> RedisResult<Vec<Option<String>>>;
+
+    /// Sets `key` to `value` for `ttl` unless it already exists, returns whether it was set.
+    async fn set_if_absent(&self, key: &str, value: &str, ttl: Duration) -> RedisResult<bool>;
+
+    /// Deletes `key` only if it still holds `value`, returns whether it was deleted.
+    async fn delete_if_equals(&self, key: &str, value: &str) -> RedisResult<bool>;
+
+    /// Sets `key` to `value`, expiring after `ttl_seconds` unless 0, and adds it to each tag set.
+    async fn set_tagged(&self, key: &str, value: &str, ttl_seconds: u64, tag_keys: &[&str]) -> RedisResult<()>;
+
+    /// Deletes the keys of the tag sets and the sets, returns the number of deleted keys.
+    /// `notification` is a channel and message published once the keys are gone.
+    async fn invalidate_tags(&self, tag_keys: &[&str], notification: Option<(&str, &str)>) -> RedisResult<i64>;
+
+    /// Takes a token from the bucket at `key`, holding `capacity` tokens that refill evenly over `window`.
+    async fn take_token(&self, key: &str, capacity: u32, window: Duration) -> RedisResult<TokenBucket>;
+}
+
+
+/// Cache backend over a Redis pool.
+pub struct RedisCacheBackend {
+    pool: RedisDatabase,
+}
+
+impl RedisCacheBackend {
+    pub fn new(pool: RedisDatabase) -> Self {
+        Self { pool }
+    }
+
+    async fn connection(&self) -> RedisResult<PooledConnection<'_, RedisConnectionManager>> {
+        self.pool.get().await.map_err(|e| match e {
+            RunError::User(e) => e,
+            RunError::TimedOut => RedisError::from((ErrorKind::IoError, "Timed out waiting for a Redis connection")),
+        })
+    }
+}
+
+#[async_trait]
+impl CacheBackend for RedisCacheBackend {
+    async fn ping(&self) -> RedisResult<()> {
+        let mut conn = self.connection().await?;
+        redis::cmd("PING").query_async::<String>(&mut *conn).await?;
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> RedisResult<Option<String>> {
+        self.connection().await?.get(key).await
+    }
+
+    async fn get_many(&self, keys: &[&str]) -> RedisResult<Vec<Option<String>>> {
+        if keys.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut conn = self.connection().await?;
+        // `AsyncCommands::mget` sends a GET for a single key, which does not reply with an array
+        redis::cmd("MGET").arg(keys).query_async(&mut *conn).await
+    }
+
+    async fn set_if_absent(&self, key: &str, value: &str, ttl: Duration) -> RedisResult<bool> {
+        let mut conn = self.connection().await?;
+        let set: Option<String> = redis::cmd("SET")
+            .arg(key)
+            .arg(value)
+            .arg("NX")
+            .arg("PX")
+            .arg(ttl.as_millis() as u64)
+            .query_async(&mut *conn)
+            .await?;
+        Ok(set.is_some())
+    }
+
+    async fn delete_if_equals(&self, key: &str, value: &str) -> RedisResult<bool> {
+        let mut conn = self.connection().await?;
+        let deleted: i64 = Script::new(DELETE_IF_EQUALS_SCRIPT)
+            .key(key)
+            .arg(value)
+            .invoke_async(&mut *conn)
+            .await?;
+        Ok(deleted > 0)
+    }
+
+    async fn set_tagged(&self, key: &str, value: &str, ttl_seconds: u64, tag_keys: &[&str]) -> RedisResult<()> {
+        let script = Script::new(SET_TAGGED_SCRIPT);
+        let mut invocation = script.key(key);
+        for tag_key in tag_keys {
+            invocation.key(*tag_key);
+        }
+        invocation.arg(value).arg(ttl_seconds);
+
+        let mut conn = self.connection().await?;
+        invocation.invoke_async::<i64>(&mut *conn).await?;
+        Ok(())
+    }
+
+    async fn invalidate_tags(&self, tag_keys: &[&str], notification: Option<(&str, &str)>) -> RedisResult<i64> {
+        let script = Script::new(INVALIDATE_TAGS_SCRIPT);
+        let mut invocation = script.prepare_invoke();
+        for tag_key in tag_keys {
+            invocation.key(*tag_key);
+        }
+        if let Some((channel, message)) = notification {
+            invocation.arg(channel).arg(message);
+        }
+
+        let mut conn = self.connection().await?;
+        invocation.invoke_async(&mut *conn).await
+    }
+
+    async fn take_token(&self, key: &str, capacity: u32, window: Duration) -> RedisResult<TokenBucket> {
+        let mut conn = self.connection().await?;
+        let (allowed, remaining, reset_after_ms, retry_after_ms): (i64, i64, u64, u64) = Script::new(TAKE_TOKEN_SCRIPT)
+            .key(key)
+            .arg(capacity)
+            .arg(window.as_millis().max(1) as u64)
+            .invoke_async(&mut *conn)
+            .await?;
+        Ok(TokenBucket {
+            allowed: allowed == 1,
+            remaining: remaining.max(0) as u32,
+            reset_after: Duration::from_millis(reset_after_ms),
+            retry_after: Duration::from_millis(retry_after_ms),
+        })
+    }
+}
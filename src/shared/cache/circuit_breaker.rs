@@ -0,0 +1,78 @@
+use std::sync::atomic::{AtomicI64, AtomicU32, Ordering};
+use std::time::Duration;
+
+use chrono::Utc;
+use tracing::{info, warn};
+
+
+/// Stops calling a failing dependency for a cool-down once it failed `threshold` times in a row.
+///
+/// When the cool-down is over a single call goes through as a probe while the circuit stays open
+/// for everybody else: its success closes the circuit, its failure re-opens it for another cool-down.
+/// A probe that never reports back is replaced by a new one after a cool-down.
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    name: &'static str,
+    threshold: u32,
+    cool_down: Duration,
+    failures: AtomicU32,
+    open_until: AtomicI64, // unix time in milliseconds, 0 when closed
+}
+
+impl CircuitBreaker {
+    pub fn new(name: &'static str, threshold: u32, cool_down: Duration) -> Self {
+        Self {
+            name,
+            threshold: threshold.max(1),
+            cool_down,
+            failures: AtomicU32::new(0),
+            open_until: AtomicI64::new(0),
+        }
+    }
+
+    /// Whether a call may be attempted now; once the cool-down is over only the first caller is
+    /// let through, and it must report its outcome with `record_success` or `record_failure`.
+    pub fn allow(&self) -> bool {
+        let open_until = self.open_until.load(Ordering::Relaxed);
+        if open_until == 0 {
+            return true;
+        }
+        let now = Utc::now().timestamp_millis();
+        if now < open_until {
+            return false;
+        }
+        // pushing the deadline back keeps the circuit open while the probe is out
+        let probe_until = now + self.cool_down.as_millis() as i64;
+        let probing = self.open_until
+            .compare_exchange(open_until, probe_until, Ordering::Relaxed, Ordering::Relaxed)
+            .is_ok();
+        if probing {
+            info!("[CIRCUIT] {} cool-down over, probing", self.name);
+        }
+        probing
+    }
+
+    /// Whether calls are currently refused, without taking the probe.
+    pub fn is_open(&self) -> bool {
+        self.open_until.load(Ordering::Relaxed) != 0
+    }
+
+    pub fn record_success(&self) {
+        self.failures.store(0, Ordering::Relaxed);
+        if self.open_until.swap(0, Ordering::Relaxed) != 0 {
+            info!("[CIRCUIT] {} recovered, circuit closed", self.name);
+        }
+    }
+
+    pub fn record_failure(&self) {
+        let failures = self.failures.fetch_add(1, Ordering::Relaxed).saturating_add(1);
+        if failures >= self.threshold {
+            let open_until = Utc::now().timestamp_millis() + self.cool_down.as_millis() as i64;
+            self.open_until.store(open_until, Ordering::Relaxed);
+            warn!(
+                "[CIRCUIT] {} failed {} times in a row, circuit open for {:?}",
+                self.name, failures, self.cool_down
+            );
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use tracing::warn;
+
+use crate::shared::cache::store::CacheStore;
+use crate::shared::cache::tag::CacheTag;
+use crate::shared::logging::log::TimePrinter;
+
+
+/// Caches `value` under `key` and attaches it to `tags`.
+/// Failures are logged and counted by the circuit breaker, the write is then simply lost.
+pub async fn set_tagged<T: serde::Serialize>(
+    cache: &CacheStore,
+    key: &str,
+    value: &T,
+    ttl_seconds: Option<u64>,
+    tags: &[CacheTag],
+) {
+    let timer = TimePrinter::with_message(&format!(
+        "[REDIS] [SET TAGGED] Key: {} ",
+        key
+    ));
+
+    let serialized = match serde_json::to_string(value) {
+        Ok(serialized) => serialized,
+        Err(e) => {
+            warn!("[CACHE] Cannot serialize {}: {}", key, e);
+            return;
+        },
+    };
+    let tag_keys: Vec<&str> = tags.iter().map(CacheTag::redis_key).collect();
+
+    let Some(backend) = cache.backend() else {
+        return;
+    };
+    let result = backend.set_tagged(key, &serialized, ttl_seconds.unwrap_or(0), &tag_keys).await;
+    if cache.record("SET TAGGED", result).is_some() {
+        timer.log();
+    }
+}
+
+/// Evicts every key attached to one of `tags`, returns the number of deleted keys.
+///
+/// Returns `None` when Redis could not be reached; entries then live until their TTL expires.
+pub async fn invalidate_tags(cache: &CacheStore, tags: &[CacheTag]) -> Option<i64> {
+    if tags.is_empty() {
+        return Some(0);
+    }
+    let tag_keys: Vec<&str> = tags.iter().map(CacheTag::redis_key).collect();
+    if let Some(local) = cache.local() {
+        local.invalidate_tags(&tag_keys);
+    }
+
+    let timer = TimePrinter::with_message(&format!(
+        "[REDIS] [INVALIDATE] Tags: {} ",
+        tags.iter().map(CacheTag::to_string).collect::<Vec<_>>().join(", ")
+    ));
+
+    // the in-process caches of the other instances evict the same tags
+    let message = match cache.invalidation_channel() {
+        Some(_) => Some(serde_json::to_string(&tag_keys).ok()?),
+        None => None,
+    };
+    let notification = cache.invalidation_channel().zip(message.as_deref());
+
+    let result = cache.backend()?.invalidate_tags(&tag_keys, notification).await;
+    let removed = cache.record("INVALIDATE", result)?;
+
+    timer.log();
+    Some(removed)
+}
@@ -0,0 +1,94 @@
+use std::fmt::Display;
+use std::time::Duration;
+
+use crate::shared::cache::tag::CacheTag;
+use crate::shared::configuration::AppDatabaseRedisConfig;
+
+
+/// Version of the cached value layouts, bump it when a cached DTO changes shape:
+/// the new deploy reads and writes a fresh key space and the old one expires on its own.
+pub const CACHE_SCHEMA_VERSION: u32 = 1;
+
+/// TTL used when `database.redis.default_ttl` is not set.
+const FALLBACK_TTL_SECONDS: u64 = 60 * 60;
+
+
+/// Builds every Redis key of the application as `<app space>:v<schema version>:<entity>:...`.
+#[derive(Debug, Clone)]
+pub struct CacheKeys {
+    prefix: String,
+    default_ttl: Duration,
+}
+
+impl Default for CacheKeys {
+    /// Keys used without Redis configuration, only relevant for naming since nothing is cached.
+    fn default() -> Self {
+        Self::new("app", CACHE_SCHEMA_VERSION, Duration::from_secs(FALLBACK_TTL_SECONDS))
+    }
+}
+
+impl CacheKeys {
+    pub fn new(app_space_name: &str, schema_version: u32, default_ttl: Duration) -> Self {
+        Self {
+            prefix: format!("{}:v{}", app_space_name, schema_version),
+            default_ttl,
+        }
+    }
+
+    pub fn from_config(redis_config: &AppDatabaseRedisConfig) -> Self {
+        Self::new(
+            redis_config.app_space_name.as_deref().unwrap_or("app"),
+            CACHE_SCHEMA_VERSION,
+            Duration::from_secs(redis_config.default_ttl.unwrap_or(FALLBACK_TTL_SECONDS)),
+        )
+    }
+
+    /// Key of a single row, e.g. `shop:v1:user:7`.
+    pub fn entity(&self, entity: &str, id: impl Display) -> String {
+        format!("{}:{}:{}", self.prefix, entity, id)
+    }
+
+    /// Key of the unpaginated list of an entity, e.g. `shop:v1:user_auth:list`.
+    pub fn list(&self, entity: &str) -> String {
+        format!("{}:{}:list", self.prefix, entity)
+    }
+
+    /// Key of the row count of an entity, e.g. `shop:v1:user:list:count`.
+    pub fn list_count(&self, entity: &str) -> String {
+        format!("{}:{}:list:count", self.prefix, entity)
+    }
+
+    pub fn entity_tag(&self, entity: &str, id: impl Display) -> CacheTag {
+        CacheTag::new(&self.prefix, format!("{}:{}", entity, id))
+    }
+
+    pub fn list_tag(&self, entity: &str) -> CacheTag {
+        CacheTag::new(&self.prefix, format!("{}:list", entity))
+    }
+
+    /// Pub/sub channel carrying the tags to evict from the in-process caches of every instance.
+    pub fn local_invalidation_channel(&self) -> String {
+        format!("{}:local:invalidate", self.prefix)
+    }
+
+    /// Key of the token bucket of `subject` under a rate limit policy, e.g. `shop:v1:rate_limit:strict:user:7`.
+    pub fn rate_limit(&self, policy: &str, subject: &str) -> String {
+        format!("{}:rate_limit:{}:{}", self.prefix, policy, subject)
+    }
+
+    /// Key of the response stored for an `Idempotency-Key` of `caller`, e.g. `shop:v1:idempotency:user:7:<sha256>`.
+    pub fn idempotency(&self, caller: &str, key_hash: &str) -> String {
+        format!("{}:idempotency:{}:{}", self.prefix, caller, key_hash)
+    }
+
+    /// Key of the lock taken by the single caller loading or writing `key`, e.g. `shop:v1:lock:user:7`.
+    pub fn lock(&self, key: &str) -> String {
+        let key = key.strip_prefix(&self.prefix).and_then(|rest| rest.strip_prefix(':')).unwrap_or(key);
+        format!("{}:lock:{}", self.prefix, key)
+    }
+
+    /// TTL applied to cached values, from `database.redis.default_ttl`.
+    pub fn default_ttl(&self) -> Duration {
+        self.default_ttl
+    }
+}
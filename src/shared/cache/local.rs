@@ -0,0 +1,153 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use futures_util::StreamExt;
+use serde::{de::DeserializeOwned, Serialize};
+use tracing::{info, warn};
+
+use crate::shared::shutdown::Shutdown;
+
+use crate::shared::cache::tag::CacheTag;
+
+
+/// Entries kept when `database.redis.local_cache_capacity` is not set.
+pub const DEFAULT_LOCAL_CAPACITY: usize = 1024;
+/// Lifetime of an entry when `database.redis.local_cache_ttl` is not set, bounds staleness if an
+/// invalidation message is lost.
+pub const DEFAULT_LOCAL_TTL: Duration = Duration::from_secs(30);
+
+/// Delay before the invalidation listener reconnects after losing its subscription.
+const RESUBSCRIBE_DELAY: Duration = Duration::from_secs(5);
+
+
+struct LocalEntry {
+    data: String,
+    tags: Vec<String>,
+    expires_at: Instant,
+    last_used: u64,
+}
+
+#[derive(Default)]
+struct LocalEntries {
+    entries: HashMap<String, LocalEntry>,
+    clock: u64,
+}
+
+
+/// Bounded in-process LRU cache with a TTL, layered in front of Redis for small reference data.
+///
+/// Entries are keyed by their Redis key and carry the Redis keys of their tags, so that
+/// [`LocalCache::invalidate_tags`] evicts what a tag invalidation evicts from Redis.
+pub struct LocalCache {
+    capacity: usize,
+    ttl: Duration,
+    inner: Mutex<LocalEntries>,
+}
+
+impl LocalCache {
+    pub fn new(capacity: usize, ttl: Duration) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            ttl,
+            inner: Mutex::new(LocalEntries::default()),
+        }
+    }
+
+    pub fn get<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        inner.clock += 1;
+        let clock = inner.clock;
+
+        let entry = inner.entries.get_mut(key)?;
+        if entry.expires_at <= Instant::now() {
+            inner.entries.remove(key);
+            return None;
+        }
+        entry.last_used = clock;
+        serde_json::from_str(&entry.data).ok()
+    }
+
+    pub fn insert<T: Serialize>(&self, key: &str, value: &T, tags: &[CacheTag]) {
+        let Ok(data) = serde_json::to_string(value) else {
+            return;
+        };
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        inner.clock += 1;
+        let clock = inner.clock;
+
+        if !inner.entries.contains_key(key) && inner.entries.len() >= self.capacity {
+            let now = Instant::now();
+            inner.entries.retain(|_, entry| entry.expires_at > now);
+            if inner.entries.len() >= self.capacity {
+                let oldest = inner.entries.iter()
+                    .min_by_key(|(_, entry)| entry.last_used)
+                    .map(|(key, _)| key.clone());
+                if let Some(oldest) = oldest {
+                    inner.entries.remove(&oldest);
+                }
+            }
+        }
+
+        inner.entries.insert(key.to_string(), LocalEntry {
+            data,
+            tags: tags.iter().map(|tag| tag.redis_key().to_string()).collect(),
+            expires_at: Instant::now() + self.ttl,
+            last_used: clock,
+        });
+    }
+
+    /// Evicts every entry attached to one of the given tag Redis keys.
+    pub fn invalidate_tags<S: AsRef<str>>(&self, tag_keys: &[S]) {
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        inner.entries.retain(|_, entry| {
+            !entry.tags.iter().any(|tag| tag_keys.iter().any(|key| key.as_ref() == tag))
+        });
+    }
+}
+
+
+/// Listens on `channel` for tag invalidations published by any instance and applies them to `local`.
+///
+/// Runs until `shutdown` stops the instance and resubscribes after connection losses; while unsubscribed,
+/// entries still expire after the local TTL.
+pub fn spawn_invalidation_listener(local: Arc<LocalCache>, redis_uri: String, channel: String, shutdown: Shutdown) {
+    tokio::spawn(async move {
+        let subscription = async {
+            loop {
+                match listen(&local, &redis_uri, &channel).await {
+                    Ok(()) => warn!("[CACHE] Invalidation subscription on {} closed", channel),
+                    Err(e) => warn!("[CACHE] Invalidation subscription on {} failed: {}", channel, e),
+                }
+                tokio::time::sleep(RESUBSCRIBE_DELAY).await;
+            }
+        };
+        tokio::select! {
+            _ = subscription => {},
+            _ = shutdown.stopped() => info!("[CACHE] Invalidation subscription on {} stopped", channel),
+        }
+    });
+}
+
+async fn listen(local: &LocalCache, redis_uri: &str, channel: &str) -> redis::RedisResult<()> {
+    let client = redis::Client::open(redis_uri)?;
+    let mut pubsub = client.get_async_pubsub().await?;
+    pubsub.subscribe(channel).await?;
+    info!("[CACHE] Subscribed to invalidations on {}", channel);
+
+    let mut messages = pubsub.on_message();
+    while let Some(message) = messages.next().await {
+        let payload: String = match message.get_payload() {
+            Ok(payload) => payload,
+            Err(e) => {
+                warn!("[CACHE] Unreadable invalidation message: {}", e);
+                continue;
+            },
+        };
+        match serde_json::from_str::<Vec<String>>(&payload) {
+            Ok(tag_keys) => local.invalidate_tags(&tag_keys),
+            Err(e) => warn!("[CACHE] Unreadable invalidation message: {}", e),
+        }
+    }
+    Ok(())
+}
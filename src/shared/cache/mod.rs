@@ -0,0 +1,8 @@
+pub mod tag;
+pub mod key;
+pub mod invalidation;
+pub mod read_through;
+pub mod circuit_breaker;
+pub mod backend;
+pub mod store;
+pub mod local;
@@ -0,0 +1,343 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::hash::Hash;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::Utc;
+use futures_util::future::join_all;
+use once_cell::sync::Lazy;
+use rand::Rng;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use tracing::warn;
+
+use crate::shared::cache::invalidation::set_tagged;
+use crate::shared::cache::key::CacheKeys;
+use crate::shared::cache::store::CacheStore;
+use crate::shared::cache::tag::CacheTag;
+
+
+/// Delay between two cache reads while another instance holds the load lock.
+const LOCK_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Per-key async locks coalescing concurrent misses of this process.
+static IN_FLIGHT: Lazy<Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+
+/// Cached value with its freshness deadline; `value: None` is a negative entry for a missing row.
+#[derive(Debug, Serialize, Deserialize)]
+struct CacheEntry<T> {
+    value: Option<T>,
+    fresh_until: i64, // unix time in milliseconds
+}
+
+
+/// Timings of a read-through cache.
+///
+/// A positive entry is fresh for `ttl` (± `jitter`), then served stale for `stale_ttl` more
+/// while a single caller reloads it in the background. Missing rows are cached for `negative_ttl`.
+#[derive(Debug, Clone)]
+pub struct ReadThrough {
+    pub ttl: Duration,
+    pub stale_ttl: Duration,
+    pub negative_ttl: Duration,
+    /// Fraction of `ttl` randomly added or removed so keys written together do not expire together
+    pub jitter: f64,
+    /// Lifetime of the Redis lock taken by the instance loading a key
+    pub lock_ttl: Duration,
+    /// How long other instances wait for the lock owner before loading the key themselves
+    pub lock_wait: Duration,
+    /// Also keep values in the in-process cache of the store, meant for small reference data
+    pub local: bool,
+    /// Names the locks of the keys, under the same app space
+    pub keys: CacheKeys,
+}
+
+impl ReadThrough {
+    /// Caches for the default TTL of `keys`.
+    pub fn new(keys: &CacheKeys) -> Self {
+        Self {
+            ttl: keys.default_ttl(),
+            stale_ttl: Duration::from_secs(60),
+            negative_ttl: Duration::from_secs(30),
+            jitter: 0.1,
+            lock_ttl: Duration::from_secs(5),
+            lock_wait: Duration::from_secs(2),
+            local: false,
+            keys: keys.clone(),
+        }
+    }
+
+    /// Same timings, with values also kept in the in-process cache when the store has one.
+    pub fn with_local(mut self) -> Self {
+        self.local = true;
+        self
+    }
+
+    /// Returns the cached value of `key`, loading it with `loader` on a miss.
+    ///
+    /// Concurrent misses of the same key are coalesced: in-process through a per-key lock,
+    /// across instances through a Redis lock. Cache failures never hide the loader result.
+    pub async fn get_or_load<T, G, F, Fut>(
+        &self,
+        cache: &CacheStore,
+        key: &str,
+        tags: G,
+        loader: F,
+    ) -> Result<Option<T>>
+    where
+        T: Serialize + DeserializeOwned + Send + Sync + 'static,
+        G: Fn(&T) -> Vec<CacheTag> + Clone + Send + 'static,
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<Option<T>>> + Send + 'static,
+    {
+        let Some(local) = cache.local().filter(|_| self.local) else {
+            return self.get_or_load_shared(cache, key, tags, loader).await;
+        };
+        if let Some(value) = local.get::<T>(key) {
+            return Ok(Some(value));
+        }
+        let value = self.get_or_load_shared(cache, key, tags.clone(), loader).await?;
+        if let Some(value) = &value {
+            local.insert(key, value, &tags(value));
+        }
+        Ok(value)
+    }
+
+    async fn get_or_load_shared<T, G, F, Fut>(
+        &self,
+        cache: &CacheStore,
+        key: &str,
+        tags: G,
+        loader: F,
+    ) -> Result<Option<T>>
+    where
+        T: Serialize + DeserializeOwned + Send + Sync + 'static,
+        G: Fn(&T) -> Vec<CacheTag> + Send + 'static,
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<Option<T>>> + Send + 'static,
+    {
+        let now = Utc::now().timestamp_millis();
+        match read_entry::<T>(cache, key).await {
+            Some(entry) if entry.fresh_until > now => return Ok(entry.value),
+            Some(CacheEntry { value: Some(value), .. }) => {
+                // stale-while-revalidate: one caller refreshes, everybody gets the stale value
+                let lock_key = self.keys.lock(key);
+                if let Some(token) = try_lock(cache, &lock_key, self.lock_ttl).await {
+                    let (this, cache, key) = (self.clone(), cache.clone(), key.to_string());
+                    tokio::spawn(async move {
+                        if let Err(e) = this.load_and_store(&cache, &key, tags, loader).await {
+                            warn!("[CACHE] Background refresh of {} failed: {}", key, e);
+                        }
+                        unlock(&cache, &lock_key, &token).await;
+                    });
+                }
+                return Ok(Some(value));
+            },
+            _ => {},
+        }
+
+        let key_lock = {
+            let mut in_flight = IN_FLIGHT.lock().unwrap_or_else(|e| e.into_inner());
+            in_flight.entry(key.to_string()).or_default().clone()
+        };
+        let result = {
+            let _guard = key_lock.lock().await;
+            self.load_coalesced(cache, key, tags, loader).await
+        };
+
+        let mut in_flight = IN_FLIGHT.lock().unwrap_or_else(|e| e.into_inner());
+        // the map and this call are the last owners
+        if Arc::strong_count(&key_lock) == 2 {
+            in_flight.remove(key);
+        }
+        result
+    }
+
+    /// Returns the cached values of `ids`, loading all the misses with a single `loader` call.
+    ///
+    /// The entries are read at once and stale ones count as misses. Loaded values are stored, ids
+    /// the loader does not return are cached as missing. Unlike [`Self::get_or_load`], misses are
+    /// not coalesced with concurrent loads of the same keys and the in-process cache is not used.
+    pub async fn get_many_or_load<I, T, K, G, F, Fut>(
+        &self,
+        cache: &CacheStore,
+        ids: &[I],
+        key_of: K,
+        tags: G,
+        loader: F,
+    ) -> Result<HashMap<I, T>>
+    where
+        I: Eq + Hash + Clone,
+        T: Serialize + DeserializeOwned,
+        K: Fn(&I) -> String,
+        G: Fn(&T) -> Vec<CacheTag>,
+        F: FnOnce(Vec<I>) -> Fut,
+        Fut: Future<Output = Result<HashMap<I, T>>>,
+    {
+        let keys: Vec<String> = ids.iter().map(key_of).collect();
+        let entries = read_entries::<T>(cache, &keys).await;
+        let now = Utc::now().timestamp_millis();
+
+        let mut values = HashMap::new();
+        let mut misses = Vec::new();
+        for ((id, key), entry) in ids.iter().zip(keys).zip(entries) {
+            match entry {
+                Some(entry) if entry.fresh_until > now => {
+                    if let Some(value) = entry.value {
+                        values.insert(id.clone(), value);
+                    }
+                },
+                _ => misses.push((id.clone(), key)),
+            }
+        }
+        if misses.is_empty() {
+            return Ok(values);
+        }
+
+        let mut loaded = loader(misses.iter().map(|(id, _)| id.clone()).collect()).await?;
+        let (tags, stored) = (&tags, &loaded);
+        join_all(misses.iter().map(|(id, key)| async move {
+            match stored.get(id) {
+                Some(value) => self.store(cache, key, value, &tags(value)).await,
+                None => self.store_missing::<T>(cache, key).await,
+            }
+        })).await;
+
+        for (id, _) in misses {
+            if let Some(value) = loaded.remove(&id) {
+                values.insert(id, value);
+            }
+        }
+        Ok(values)
+    }
+
+    /// Writes a freshly loaded or updated value, replacing any negative or stale entry.
+    pub async fn store<T: Serialize>(&self, cache: &CacheStore, key: &str, value: &T, tags: &[CacheTag]) {
+        let ttl = self.jittered_ttl();
+        let entry = CacheEntry {
+            value: Some(value),
+            fresh_until: Utc::now().timestamp_millis() + ttl.as_millis() as i64,
+        };
+        set_tagged(cache, key, &entry, Some((ttl + self.stale_ttl).as_secs().max(1)), tags).await;
+    }
+
+    async fn load_coalesced<T, G, F, Fut>(
+        &self,
+        cache: &CacheStore,
+        key: &str,
+        tags: G,
+        loader: F,
+    ) -> Result<Option<T>>
+    where
+        T: Serialize + DeserializeOwned,
+        G: Fn(&T) -> Vec<CacheTag>,
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<Option<T>>>,
+    {
+        // another task of this process may have filled the key while we waited
+        if let Some(entry) = read_entry::<T>(cache, key).await
+            && entry.fresh_until > Utc::now().timestamp_millis() {
+            return Ok(entry.value);
+        }
+
+        let lock_key = self.keys.lock(key);
+        let mut token = try_lock(cache, &lock_key, self.lock_ttl).await;
+        if token.is_none() {
+            let deadline = tokio::time::Instant::now() + self.lock_wait;
+            while tokio::time::Instant::now() < deadline {
+                tokio::time::sleep(LOCK_POLL_INTERVAL).await;
+                if let Some(entry) = read_entry::<T>(cache, key).await
+                    && entry.fresh_until > Utc::now().timestamp_millis() {
+                    return Ok(entry.value);
+                }
+                token = try_lock(cache, &lock_key, self.lock_ttl).await;
+                if token.is_some() {
+                    break;
+                }
+            }
+        }
+
+        let result = self.load_and_store(cache, key, tags, loader).await;
+        if let Some(token) = token {
+            unlock(cache, &lock_key, &token).await;
+        }
+        result
+    }
+
+    async fn load_and_store<T, G, F, Fut>(
+        &self,
+        cache: &CacheStore,
+        key: &str,
+        tags: G,
+        loader: F,
+    ) -> Result<Option<T>>
+    where
+        T: Serialize,
+        G: Fn(&T) -> Vec<CacheTag>,
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<Option<T>>>,
+    {
+        let value = loader().await?;
+
+        match &value {
+            Some(value) => self.store(cache, key, value, &tags(value)).await,
+            None => self.store_missing::<T>(cache, key).await,
+        }
+
+        Ok(value)
+    }
+
+    /// Writes a negative entry, `key` having no row behind it.
+    async fn store_missing<T: Serialize>(&self, cache: &CacheStore, key: &str) {
+        let entry: CacheEntry<T> = CacheEntry {
+            value: None,
+            fresh_until: Utc::now().timestamp_millis() + self.negative_ttl.as_millis() as i64,
+        };
+        set_tagged(cache, key, &entry, Some(self.negative_ttl.as_secs().max(1)), &[]).await;
+    }
+
+    fn jittered_ttl(&self) -> Duration {
+        if self.jitter <= 0.0 {
+            return self.ttl;
+        }
+        let factor = rand::rng().random_range((1.0 - self.jitter)..(1.0 + self.jitter));
+        self.ttl.mul_f64(factor)
+    }
+}
+
+
+/// Reads an entry, treating connection and decoding failures as a miss.
+async fn read_entry<T: DeserializeOwned>(cache: &CacheStore, key: &str) -> Option<CacheEntry<T>> {
+    let data = cache.record("GET", cache.backend()?.get(key).await)?;
+    serde_json::from_str(&data?).ok()
+}
+
+/// Reads the entries of `keys` at once, in the same order, treating failures as misses.
+async fn read_entries<T: DeserializeOwned>(cache: &CacheStore, keys: &[String]) -> Vec<Option<CacheEntry<T>>> {
+    let keys: Vec<&str> = keys.iter().map(String::as_str).collect();
+    let data = match cache.backend() {
+        Some(backend) => cache.record("MGET", backend.get_many(&keys).await),
+        None => None,
+    };
+    match data {
+        Some(data) => data.into_iter().map(|data| serde_json::from_str(&data?).ok()).collect(),
+        None => keys.iter().map(|_| None).collect(),
+    }
+}
+
+async fn try_lock(cache: &CacheStore, lock_key: &str, ttl: Duration) -> Option<String> {
+    let token = uuid::Uuid::new_v4().to_string();
+    let acquired = cache.backend()?.set_if_absent(lock_key, &token, ttl).await;
+    cache.record("LOCK", acquired)?.then_some(token)
+}
+
+/// Releases the lock only if it is still owned by the caller.
+async fn unlock(cache: &CacheStore, lock_key: &str, token: &str) {
+    let Some(backend) = cache.backend() else {
+        return;
+    };
+    let released = backend.delete_if_equals(lock_key, token).await;
+    cache.record("UNLOCK", released);
+}
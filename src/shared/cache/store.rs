@@ -0,0 +1,88 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use tracing::warn;
+
+use crate::shared::cache::backend::{CacheBackend, RedisCacheBackend};
+use crate::shared::cache::circuit_breaker::CircuitBreaker;
+use crate::shared::cache::local::LocalCache;
+use crate::shared::database::redis::RedisDatabase;
+
+
+/// Consecutive Redis failures after which the cache is bypassed.
+const FAILURE_THRESHOLD: u32 = 5;
+/// How long the cache is bypassed once the circuit opened.
+const COOL_DOWN: Duration = Duration::from_secs(30);
+
+
+/// Cache backend guarded by a circuit breaker, optionally fronted by an in-process cache.
+///
+/// Every cache operation goes through [`CacheStore::backend`] and reports its outcome with
+/// [`CacheStore::record`]; while Redis is failing callers get no backend and treat it as a miss.
+#[derive(Clone)]
+pub struct CacheStore {
+    backend: Arc<dyn CacheBackend>,
+    breaker: Arc<CircuitBreaker>,
+    local: Option<Arc<LocalCache>>,
+    invalidation_channel: Option<String>,
+}
+
+impl CacheStore {
+    pub fn new(pool: RedisDatabase) -> Self {
+        Self::with_backend(Arc::new(RedisCacheBackend::new(pool)))
+    }
+
+    pub fn with_backend(backend: Arc<dyn CacheBackend>) -> Self {
+        Self {
+            backend,
+            breaker: Arc::new(CircuitBreaker::new("Redis", FAILURE_THRESHOLD, COOL_DOWN)),
+            local: None,
+            invalidation_channel: None,
+        }
+    }
+
+    /// Enables the in-process cache; tag invalidations are published on `invalidation_channel`
+    /// for the other instances to evict their own copies.
+    pub fn with_local_cache(mut self, local: Arc<LocalCache>, invalidation_channel: String) -> Self {
+        self.local = Some(local);
+        self.invalidation_channel = Some(invalidation_channel);
+        self
+    }
+
+    pub fn local(&self) -> Option<&LocalCache> {
+        self.local.as_deref()
+    }
+
+    pub fn invalidation_channel(&self) -> Option<&str> {
+        self.invalidation_channel.as_deref()
+    }
+
+    pub fn is_available(&self) -> bool {
+        !self.breaker.is_open()
+    }
+
+    /// The backend, `None` while the circuit is open.
+    pub fn backend(&self) -> Option<&dyn CacheBackend> {
+        self.breaker.allow().then_some(self.backend.as_ref())
+    }
+
+    /// Checks that the backend answers, even while the circuit is open.
+    pub async fn ping(&self) -> redis::RedisResult<()> {
+        self.backend.ping().await
+    }
+
+    /// Feeds the outcome of a Redis command to the circuit breaker, turning errors into `None`.
+    pub fn record<T>(&self, operation: &str, result: redis::RedisResult<T>) -> Option<T> {
+        match result {
+            Ok(value) => {
+                self.breaker.record_success();
+                Some(value)
+            },
+            Err(e) => {
+                self.breaker.record_failure();
+                warn!("[CACHE] {} failed: {}", operation, e);
+                None
+            },
+        }
+    }
+}
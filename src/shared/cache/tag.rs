@@ -0,0 +1,34 @@
+use std::fmt;
+
+
+/// Label attached to cached keys so they can be evicted together, built by [`CacheKeys`].
+///
+/// * entity tags (`user:7`) are carried by every key built from that row, including keys of
+///   other entities embedding it (dependency tags, e.g. a cached user embeds its auth name);
+/// * list tags (`user:list`) are carried by list and count keys and are invalidated by any
+///   create, update or delete of the entity.
+///
+/// [`CacheKeys`]: crate::shared::cache::key::CacheKeys
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CacheTag {
+    name: String,
+    redis_key: String,
+}
+
+impl CacheTag {
+    pub(crate) fn new(prefix: &str, name: String) -> Self {
+        let redis_key = format!("{}:tag:{}", prefix, name);
+        Self { name, redis_key }
+    }
+
+    /// Redis set holding the keys carrying this tag.
+    pub fn redis_key(&self) -> &str {
+        &self.redis_key
+    }
+}
+
+impl fmt::Display for CacheTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name)
+    }
+}
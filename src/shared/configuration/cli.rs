@@ -0,0 +1,69 @@
+use anyhow::{anyhow, bail, Result};
+
+
+pub const USAGE: &str = "\
+Usage: e-commerce-system [OPTIONS]
+
+Options:
+  --config-dir <DIR>   Directory of the <environment>.toml files [default: config]
+  --env <NAME>         Environment to load, overrides APP_ENV [default: development]
+  --set <KEY=VALUE>    Overrides a configuration key, e.g. --set database.redis.default_ttl=600
+  --print-config       Prints the resolved configuration with secrets redacted, then exits
+  --help               Prints this help";
+
+
+/// Where the configuration layers are read from.
+#[derive(Debug, Clone)]
+pub struct ConfigSources {
+    pub config_dir: String,
+    /// `None` falls back to `APP_ENV`
+    pub environment: Option<String>,
+    /// `key=value` pairs of `--set`, applied last and in order
+    pub overrides: Vec<(String, String)>,
+}
+
+impl Default for ConfigSources {
+    fn default() -> Self {
+        Self { config_dir: "config".to_string(), environment: None, overrides: Vec::new() }
+    }
+}
+
+
+#[derive(Debug, Clone, Default)]
+pub struct CliOptions {
+    pub sources: ConfigSources,
+    pub print_config: bool,
+    pub help: bool,
+}
+
+impl CliOptions {
+    /// Parses the command line arguments, without the program name.
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self> {
+        let mut options = Self::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            // --name=value and --name value are equivalent
+            let (name, inline_value) = match arg.split_once('=') {
+                Some((name, value)) if name.starts_with("--") => (name.to_string(), Some(value.to_string())),
+                _ => (arg, None),
+            };
+            let mut value = || inline_value.clone().or_else(|| args.next())
+                .ok_or_else(|| anyhow!("{} expects a value\n\n{}", name, USAGE));
+
+            match name.as_str() {
+                "--config-dir" => options.sources.config_dir = value()?,
+                "--env" => options.sources.environment = Some(value()?),
+                "--set" => {
+                    let assignment = value()?;
+                    let (key, value) = assignment.split_once('=')
+                        .ok_or_else(|| anyhow!("--set expects KEY=VALUE, got {:?}", assignment))?;
+                    options.sources.overrides.push((key.trim().to_string(), value.to_string()));
+                },
+                "--print-config" => options.print_config = true,
+                "--help" | "-h" => options.help = true,
+                _ => bail!("Unknown argument {:?}\n\n{}", name, USAGE),
+            }
+        }
+        Ok(options)
+    }
+}
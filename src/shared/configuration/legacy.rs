@@ -0,0 +1,76 @@
+use std::collections::BTreeMap;
+
+
+/// Environment variables read before the layered configuration, with the key each one sets.
+/// They sit between the files and the `APP__` variables, which override them.
+pub const LEGACY_ENV_KEYS: &[(&str, &str)] = &[
+    ("BIND_ADDR", "bind_addr"),
+    ("LOG_LEVEL", "log_level"),
+
+    ("JWT_RSA_PRIVATE_KEY_PATH", "jwt.private_secret_pem_path"),
+    ("JWT_RSA_PUBLIC_KEY_PATH", "jwt.public_secret_pem_path"),
+    ("JWT_ISSUER", "jwt.issuer"),
+    ("JWT_AUDIENCE", "jwt.audience"),
+    ("JWT_EXPIRES_IN_MINUTES", "jwt.expires_in_minutes"),
+    ("JWT_KID", "jwt.kid"),
+
+    ("MYSQL_URL", "database.mysql.uri"),
+    ("MYSQL_REPLICA_URLS", "database.mysql.replica_uris"),
+    ("MYSQL_USERNAME", "database.mysql.username"),
+    ("MYSQL_PASSWORD", "database.mysql.password"),
+    ("MYSQL_DATABASE", "database.mysql.database"),
+    ("MYSQL_POOL_SIZE", "database.mysql.pool_size"),
+    ("MYSQL_POOL_MIN_IDLE", "database.mysql.pool_min_idle"),
+    ("MYSQL_POOL_MAX_LIFETIME", "database.mysql.pool_max_lifetime"),
+    ("MYSQL_POOL_IDLE_TIMEOUT", "database.mysql.pool_idle_timeout"),
+    ("MYSQL_POOL_CONNECTION_TIMEOUT", "database.mysql.pool_connection_timeout"),
+    ("MYSQL_POOL_MAX_CONNECTIONS", "database.mysql.pool_max_connections"),
+    ("MYSQL_POOL_CONNECTION_LIFETIME", "database.mysql.pool_connection_lifetime"),
+    ("MYSQL_POOL_CONNECTION_ACQUISITION_TIMEOUT", "database.mysql.pool_connection_acquisition_timeout"),
+
+    ("MONGO_URL", "database.mongo.uri"),
+    ("MONGO_DATABASE", "database.mongo.database"),
+
+    ("REDIS_URL", "database.redis.uri"),
+    ("REDIS_DEFAULT_TTL", "database.redis.default_ttl"),
+    ("REDIS_MAX_CONNECTIONS", "database.redis.max_connections"),
+    ("REDIS_APP_SPACE_NAME", "database.redis.app_space_name"),
+    ("REDIS_LOCAL_CACHE_CAPACITY", "database.redis.local_cache_capacity"),
+    ("REDIS_LOCAL_CACHE_TTL", "database.redis.local_cache_ttl"),
+
+    ("NEO4J_URL", "database.neo4j.uri"),
+    ("NEO4J_USERNAME", "database.neo4j.username"),
+    ("NEO4J_PASSWORD", "database.neo4j.password"),
+    ("NEO4J_ENCRYPTED", "database.neo4j.encrypted"),
+
+    ("STORAGE_BACKEND", "storage.backend"),
+    ("STORAGE_LOCAL_PATH", "storage.local_path"),
+    ("STORAGE_PUBLIC_BASE_URL", "storage.public_base_url"),
+    ("STORAGE_MAX_UPLOAD_BYTES", "storage.max_upload_bytes"),
+    ("S3_ENDPOINT", "storage.s3.endpoint"),
+    ("S3_BUCKET", "storage.s3.bucket"),
+    ("S3_REGION", "storage.s3.region"),
+    ("S3_ACCESS_KEY", "storage.s3.access_key"),
+    ("S3_SECRET_KEY", "storage.s3.secret_key"),
+
+    ("SHUTDOWN_READINESS_DELAY", "shutdown.readiness_delay_seconds"),
+    ("SHUTDOWN_DRAIN_TIMEOUT", "shutdown.drain_timeout_seconds"),
+];
+
+const DEFAULT_MYSQL_PORT: &str = "3306";
+
+
+/// Configuration keys set by the legacy variables of `vars`, usually `std::env::vars()`.
+/// Without `MYSQL_URL`, the MySQL URI is composed from `MYSQL_HOST` and `MYSQL_PORT` (default 3306).
+pub fn legacy_env_keys(vars: impl IntoIterator<Item = (String, String)>) -> BTreeMap<String, String> {
+    let vars: BTreeMap<String, String> = vars.into_iter().collect();
+    let mut keys: BTreeMap<String, String> = LEGACY_ENV_KEYS.iter()
+        .filter_map(|(var, key)| vars.get(*var).map(|value| (key.to_string(), value.trim().to_string())))
+        .collect();
+
+    if !vars.contains_key("MYSQL_URL") && let Some(host) = vars.get("MYSQL_HOST") {
+        let port = vars.get("MYSQL_PORT").map_or(DEFAULT_MYSQL_PORT, |port| port.as_str());
+        keys.insert("database.mysql.uri".to_string(), format!("mysql://{}:{}", host.trim(), port.trim()));
+    }
+    keys
+}
@@ -1,2 +1,5 @@
 pub mod mysql;
-pub mod redis;
\ No newline at end of file
+pub mod mysql_pools;
+pub mod procedures;
+pub mod redis;
+pub mod unit_of_work;
@@ -1,9 +1,12 @@
 use std::collections::HashMap;
+use std::str::FromStr;
+use std::time::Duration;
 use chrono::{DateTime, Utc};
 use anyhow::{Error, Result};
 use async_trait::async_trait;
 use sqlx::{
-    mysql::MySqlRow, Column, MySqlPool, Row, MySql, Pool,
+    error::UnexpectedNullError,
+    mysql::{MySqlConnectOptions, MySqlDatabaseError, MySqlPoolOptions, MySqlRow}, Column, MySqlPool, Row, MySql, Pool,
     types::{
         chrono::{NaiveDate, NaiveDateTime},
         Uuid,
@@ -11,13 +14,17 @@ use sqlx::{
 };
 use tracing::info;
 use crate::shared::configuration::AppDatabaseMySQLConfig;
+use crate::shared::database::mysql_pools::MySqlPools;
 use crate::shared::logging::log::TimePrinter;
+use crate::shared::models::decimal::Decimal;
 
 
 pub async fn connect(database_config: &AppDatabaseMySQLConfig) -> Result<Pool<MySql>> {
     info!("Connecting to MySQL database...");
 
-    let pool = MySqlPool::connect(database_config.uri.as_str()).await?;
+    let pool = pool_options(database_config)?
+        .connect_with(connect_options(database_config, &database_config.uri)?)
+        .await?;
 
     // Run migrations
     // run_migrations(&pool).await?;
@@ -27,6 +34,73 @@ pub async fn connect(database_config: &AppDatabaseMySQLConfig) -> Result<Pool<My
 }
 
 
+/// Creates the pools of the read replicas without connecting, so that a replica being down
+/// at startup only sends its reads to the primary.
+pub fn connect_replicas(database_config: &AppDatabaseMySQLConfig) -> Result<Vec<Pool<MySql>>> {
+    database_config.replica_uris.iter()
+        .map(|uri| Ok(pool_options(database_config)?.connect_lazy_with(connect_options(database_config, uri)?)))
+        .collect()
+}
+
+
+/// Parses `uri` and applies the credentials and database set apart from it.
+pub fn connect_options(database_config: &AppDatabaseMySQLConfig, uri: &str) -> Result<MySqlConnectOptions> {
+    let mut options = MySqlConnectOptions::from_str(uri)?;
+    if let Some(username) = &database_config.username {
+        options = options.username(username);
+    }
+    if let Some(password) = &database_config.password {
+        options = options.password(password);
+    }
+    if let Some(database) = &database_config.database {
+        options = options.database(database);
+    }
+    Ok(options)
+}
+
+
+/// Pool settings from the configuration, sqlx defaults for the unset ones.
+///
+/// `pool_max_connections` takes precedence over `pool_size`, `pool_max_lifetime` over
+/// `pool_connection_lifetime` and `pool_connection_acquisition_timeout` over `pool_connection_timeout`.
+pub fn pool_options(database_config: &AppDatabaseMySQLConfig) -> Result<MySqlPoolOptions> {
+    let mut options = MySqlPoolOptions::new();
+
+    let max_connections = match database_config.pool_max_connections {
+        Some(max_connections) => Some(max_connections),
+        None => database_config.pool_size.map(|size| non_negative("pool_size", size)).transpose()?.map(|size| size as u32),
+    };
+    if let Some(max_connections) = max_connections {
+        options = options.max_connections(max_connections.max(1));
+    }
+    if let Some(min_idle) = database_config.pool_min_idle {
+        options = options.min_connections(non_negative("pool_min_idle", min_idle)? as u32);
+    }
+    if let Some(lifetime) = database_config.pool_max_lifetime.or(database_config.pool_connection_lifetime) {
+        options = options.max_lifetime(Duration::from_secs(non_negative("pool_max_lifetime", lifetime)?));
+    }
+    if let Some(idle_timeout) = database_config.pool_idle_timeout {
+        options = options.idle_timeout(Duration::from_secs(non_negative("pool_idle_timeout", idle_timeout)?));
+    }
+    if let Some(timeout) = database_config.pool_connection_acquisition_timeout.or(database_config.pool_connection_timeout) {
+        options = options.acquire_timeout(Duration::from_secs(non_negative("pool_connection_acquisition_timeout", timeout)?));
+    }
+
+    if options.get_min_connections() > options.get_max_connections() {
+        return Err(Error::msg(format!(
+            "MySQL pool_min_idle ({}) exceeds the maximum number of connections ({})",
+            options.get_min_connections(),
+            options.get_max_connections()
+        )));
+    }
+    Ok(options)
+}
+
+fn non_negative(field: &str, value: i64) -> Result<u64> {
+    u64::try_from(value).map_err(|_| Error::msg(format!("MySQL {} must not be negative, got {}", field, value)))
+}
+
+
 pub fn get_column_index_map(row: &MySqlRow) -> HashMap<String, usize> {
     row.columns().iter()
         .enumerate()
@@ -35,6 +109,33 @@ pub fn get_column_index_map(row: &MySqlRow) -> HashMap<String, usize> {
 }
 
 
+/// MySQL error number of a write violating a unique index
+const ER_DUP_ENTRY: u16 = 1062;
+
+/// A write was rejected by a unique index, e.g. a username that is already taken.
+#[derive(Debug, thiserror::Error)]
+#[error("Duplicate key: {0}")]
+pub struct DuplicateKey(pub String);
+
+/// Replaces a MySQL duplicate entry error anywhere in the chain of `error` by `DuplicateKey`,
+/// so that services can tell it apart without depending on sqlx.
+pub fn map_duplicate_key(error: Error) -> Error {
+    let duplicate = error.chain()
+        .filter_map(|cause| cause.downcast_ref::<sqlx::Error>())
+        .filter_map(|cause| match cause {
+            sqlx::Error::Database(database_error) => database_error.try_downcast_ref::<MySqlDatabaseError>(),
+            _ => None,
+        })
+        .find(|database_error| database_error.number() == ER_DUP_ENTRY)
+        .map(|database_error| database_error.message().to_string());
+
+    match duplicate {
+        Some(message) => Error::new(DuplicateKey(message)),
+        None => error,
+    }
+}
+
+
 pub async fn run_migrations(pool: &Pool<MySql>) -> Result<()> {
     sqlx::migrate!("./migrations")
         .run(pool)
@@ -43,15 +144,46 @@ pub async fn run_migrations(pool: &Pool<MySql>) -> Result<()> {
 }
 
 
-/// Trait for entities that can be mapped from database rows
+/// Trait for entities that can be mapped from database rows, usually derived with
+/// `#[derive(FromSqlRow)]` from `e_commerce_system_macros`.
 pub trait FromSqlRow: Sized {
-    fn map_row_to_entity(row: MySqlRow, index_map: &HashMap<String, usize>) -> Result<Self, sqlx::Error>;
+    /// Maps the row reading every column with `prefix`, so that an entity can be nested in another.
+    fn map_row_with_prefix(row: &MySqlRow, index_map: &HashMap<String, usize>, prefix: &str) -> Result<Self, sqlx::Error>;
+
+    fn map_row_to_entity(row: MySqlRow, index_map: &HashMap<String, usize>) -> Result<Self, sqlx::Error> {
+        Self::map_row_with_prefix(&row, index_map, "")
+    }
+}
+
+/// Entity of an outer join, `None` when the joined row is missing: one of the required columns of
+/// `T` is `NULL`. Columns missing from the result set still fail.
+impl<T: FromSqlRow> FromSqlRow for Option<T> {
+    fn map_row_with_prefix(row: &MySqlRow, index_map: &HashMap<String, usize>, prefix: &str) -> Result<Self, sqlx::Error> {
+        match T::map_row_with_prefix(row, index_map, prefix) {
+            Ok(entity) => Ok(Some(entity)),
+            Err(sqlx::Error::ColumnDecode { source, .. }) if source.is::<UnexpectedNullError>() => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// Position of `prefix` + `column` in the row, `None` when the procedure does not return it.
+pub fn column_index(index_map: &HashMap<String, usize>, prefix: &str, column: &str) -> Option<usize> {
+    match prefix.is_empty() {
+        true => index_map.get(column).copied(),
+        false => index_map.get(&format!("{}{}", prefix, column).to_lowercase()).copied(),
+    }
 }
 
 /// Generic repository operations
 #[async_trait]
 pub trait GenericRepository<T: FromSqlRow + Send + Sync> {
-    fn get_pool(&self) -> &MySqlPool;
+    fn get_pools(&self) -> &MySqlPools;
+
+    /// Pool of the primary, where every write goes.
+    fn get_pool(&self) -> &MySqlPool {
+        self.get_pools().primary()
+    }
 
     // Generic parsing methods
     fn parse_entity_from_option_result_sql(
@@ -85,7 +217,7 @@ pub trait GenericRepository<T: FromSqlRow + Send + Sync> {
             }
             Err(e) => {
                 timer_printer.error_with_message(&format!("Error during database operation: {}", e));
-                Err(Error::msg("Error during database operation"))
+                Err(e.context("Error during database operation"))
             }
         }
     }
@@ -113,7 +245,7 @@ pub trait GenericRepository<T: FromSqlRow + Send + Sync> {
             },
             Err(e) => {
                 timer_printer.error_with_message(&format!("Failed database operation: {}", e));
-                Err(Error::msg("Failed database operation"))
+                Err(e.context("Failed database operation"))
             }
         }
     }
@@ -147,7 +279,7 @@ pub trait GenericRepository<T: FromSqlRow + Send + Sync> {
             }
             Err(e) => {
                 time_printer.error_with_message(&format!("Failed database operation: {}", e));
-                Err(Error::msg("Failed database operation"))
+                Err(e.context("Failed database operation"))
             }
         }
     }
@@ -202,6 +334,10 @@ pub enum MySqlParam {
     // ----- UUID (BINARY(16) / CHAR(36)) -----
     Uuid(Uuid),
     OptUuid(Option<Uuid>),
+
+    // ----- DECIMAL, bound as text so that MySQL converts it exactly -----
+    Decimal(Decimal),
+    OptDecimal(Option<Decimal>),
 }
 
 impl MySqlParam {
@@ -252,6 +388,10 @@ impl MySqlParam {
             // ---- uuid ----
             MySqlParam::Uuid(v) => query.bind(v),
             MySqlParam::OptUuid(v) => query.bind(v),
+
+            // ---- decimal ----
+            MySqlParam::Decimal(v) => query.bind(v.to_string()),
+            MySqlParam::OptDecimal(v) => query.bind(v.map(|v| v.to_string())),
         }
     }
 }
@@ -469,3 +609,14 @@ impl From<Option<Uuid>> for MySqlParam {
     }
 }
 
+// ---- decimal ----
+impl From<Decimal> for MySqlParam {
+    fn from(v: Decimal) -> Self {
+        MySqlParam::Decimal(v)
+    }
+}
+impl From<Option<Decimal>> for MySqlParam {
+    fn from(v: Option<Decimal>) -> Self {
+        MySqlParam::OptDecimal(v)
+    }
+}
@@ -0,0 +1,141 @@
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use sqlx::MySqlPool;
+use tracing::warn;
+
+use crate::shared::cache::circuit_breaker::CircuitBreaker;
+use crate::shared::database::unit_of_work::UnitOfWork;
+
+
+/// Consecutive connection failures after which a replica stops receiving reads.
+const REPLICA_FAILURE_THRESHOLD: u32 = 3;
+/// How long an unhealthy replica is skipped before it is tried again.
+const REPLICA_COOL_DOWN: Duration = Duration::from_secs(30);
+
+
+struct Replica {
+    pool: MySqlPool,
+    breaker: CircuitBreaker,
+}
+
+
+/// Primary pool and optional read replicas of a MySQL database.
+///
+/// Reads go round-robin to healthy replicas and fall back to the primary when none is healthy
+/// or when the chosen replica fails to answer. A handle obtained with [`MySqlPools::primary_only`]
+/// sends everything to the primary, for reads that must see the caller's own writes, and one
+/// obtained with [`MySqlPools::in_unit_of_work`] runs everything inside that transaction.
+#[derive(Clone)]
+pub struct MySqlPools {
+    primary: MySqlPool,
+    replicas: Arc<Vec<Replica>>,
+    next: Arc<AtomicUsize>,
+    primary_only: bool,
+    unit_of_work: Option<UnitOfWork>,
+}
+
+impl MySqlPools {
+    pub fn new(primary: MySqlPool, replicas: Vec<MySqlPool>) -> Self {
+        let replicas = replicas.into_iter()
+            .map(|pool| Replica {
+                pool,
+                breaker: CircuitBreaker::new("MySQL replica", REPLICA_FAILURE_THRESHOLD, REPLICA_COOL_DOWN),
+            })
+            .collect();
+        Self {
+            primary,
+            replicas: Arc::new(replicas),
+            next: Arc::new(AtomicUsize::new(0)),
+            primary_only: false,
+            unit_of_work: None,
+        }
+    }
+
+    pub fn primary(&self) -> &MySqlPool {
+        &self.primary
+    }
+
+    /// Waits for the checked out connections to be returned, then closes every connection of the pools.
+    pub async fn close(&self) {
+        self.primary.close().await;
+        for replica in self.replicas.iter() {
+            replica.pool.close().await;
+        }
+    }
+
+    pub fn replicas(&self) -> impl Iterator<Item = &MySqlPool> {
+        self.replicas.iter().map(|replica| &replica.pool)
+    }
+
+    /// Same pools, with every read routed to the primary.
+    pub fn primary_only(&self) -> Self {
+        Self {
+            primary_only: true,
+            ..self.clone()
+        }
+    }
+
+    /// Same pools, with every statement running inside `unit_of_work`.
+    pub fn in_unit_of_work(&self, unit_of_work: &UnitOfWork) -> Self {
+        Self {
+            unit_of_work: Some(unit_of_work.clone()),
+            ..self.clone()
+        }
+    }
+
+    pub fn unit_of_work(&self) -> Option<&UnitOfWork> {
+        self.unit_of_work.as_ref()
+    }
+
+    /// Runs a read on a healthy replica, or on the primary when there is none or the replica fails.
+    ///
+    /// `run` may be called twice, so it must rebuild its query on every call.
+    pub async fn read<R, F, Fut>(&self, run: F) -> Result<R, sqlx::Error>
+    where
+        F: Fn(MySqlPool) -> Fut,
+        Fut: Future<Output = Result<R, sqlx::Error>>,
+    {
+        let Some(replica) = self.pick_replica() else {
+            return run(self.primary.clone()).await;
+        };
+
+        match run(replica.pool.clone()).await {
+            Err(e) if is_connection_error(&e) => {
+                replica.breaker.record_failure();
+                warn!("[MYSQL] Replica read failed, retrying on the primary: {}", e);
+                run(self.primary.clone()).await
+            },
+            result => {
+                replica.breaker.record_success();
+                result
+            },
+        }
+    }
+
+    fn pick_replica(&self) -> Option<&Replica> {
+        if self.primary_only || self.replicas.is_empty() {
+            return None;
+        }
+        let start = self.next.fetch_add(1, Ordering::Relaxed);
+        (0..self.replicas.len())
+            .map(|offset| &self.replicas[(start + offset) % self.replicas.len()])
+            .find(|replica| replica.breaker.allow())
+    }
+}
+
+
+/// Errors telling the server could not be reached, as opposed to errors raised by the query itself.
+fn is_connection_error(error: &sqlx::Error) -> bool {
+    matches!(
+        error,
+        sqlx::Error::Io(_)
+            | sqlx::Error::Tls(_)
+            | sqlx::Error::Protocol(_)
+            | sqlx::Error::PoolTimedOut
+            | sqlx::Error::PoolClosed
+            | sqlx::Error::WorkerCrashed
+    )
+}
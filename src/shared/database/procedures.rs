@@ -0,0 +1,41 @@
+//! Typed bindings of the stored procedures, generated by `build.rs` from `migrations/*/routines`.
+//!
+//! Every procedure is a function taking its parameters in order; the parameters marked
+//! `/* nullable */` take an `Option`, `None` binding a SQL `NULL`: `procedures::app_user_get(user_id, None)`.
+#![allow(clippy::too_many_arguments)]
+
+#[allow(unused_imports)]
+use chrono::{DateTime, NaiveDate, Utc};
+
+use crate::shared::database::mysql::MySqlParam;
+#[allow(unused_imports)]
+use crate::shared::models::decimal::Decimal;
+
+
+/// Name and bound parameters of a procedure call, only built by the generated functions.
+#[derive(Debug, Clone)]
+pub struct ProcedureCall {
+    name: &'static str,
+    params: Vec<MySqlParam>,
+}
+
+impl ProcedureCall {
+    fn new(name: &'static str, params: Vec<MySqlParam>) -> Self {
+        Self { name, params }
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn params_count(&self) -> usize {
+        self.params.len()
+    }
+
+    pub fn into_params(self) -> Vec<MySqlParam> {
+        self.params
+    }
+}
+
+
+include!(concat!(env!("OUT_DIR"), "/procedures.rs"));
@@ -1,25 +1,43 @@
+use std::time::Duration;
 use anyhow::Result;
 use bb8::Pool;
 use bb8_redis::RedisConnectionManager;
-use redis::{AsyncCommands, RedisError};
-use tracing::info;
+use redis::AsyncCommands;
+use tracing::{info, warn};
 use crate::shared::configuration::AppDatabaseRedisConfig;
 use crate::shared::logging::log::TimePrinter;
 
 pub type RedisDatabase = Pool<RedisConnectionManager>;
 
+/// Pool size used when `database.redis.max_connections` is not set, the bb8 default.
+const DEFAULT_MAX_CONNECTIONS: u32 = 10;
+
+/// Upper bound on waiting for a pooled connection, keeps a Redis outage from stalling requests.
+const CONNECTION_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Builds the pool without requiring Redis to be up, an unreachable server only logs a warning
+/// so the application boots and starts caching once Redis comes back.
 pub async fn connect(redis_config: &AppDatabaseRedisConfig) -> Result<RedisDatabase> {
     info!("Connecting to Redis...");
 
     let manager = RedisConnectionManager::new(redis_config.uri.as_str())?;
-    let pool = Pool::builder().build(manager).await?;
+    let pool = Pool::builder()
+        .max_size(redis_config.max_connections.unwrap_or(DEFAULT_MAX_CONNECTIONS))
+        .connection_timeout(CONNECTION_TIMEOUT)
+        .build_unchecked(manager);
 
     // Test connection
-    let mut conn = pool.get().await?;
-    let _: () = conn.set("health_check", "ok").await?;
-
-    info!("Redis connected successfully");
-    Ok(pool.clone())
+    let tested: Result<()> = async {
+        let mut conn = pool.get().await?;
+        redis::cmd("PING").query_async::<String>(&mut *conn).await?;
+        Ok(())
+    }.await;
+
+    match tested {
+        Ok(()) => info!("Redis connected successfully"),
+        Err(e) => warn!("Redis is unreachable, serving without cache until it recovers: {}", e),
+    }
+    Ok(pool)
 }
 
 pub async fn set_key<T: serde::Serialize>(
@@ -30,7 +48,7 @@ pub async fn set_key<T: serde::Serialize>(
 ) -> Result<()> {
     let timer = TimePrinter::with_message(&format!(
         "[REDIS] [SET] Key: {} ",
-        key.to_string()
+        key
     ));
 
     let mut conn = pool.get().await?;
@@ -53,7 +71,7 @@ pub async fn get_key<T: serde::de::DeserializeOwned>(
 ) -> Result<Option<T>> {
     let timer = TimePrinter::with_message(&format!(
         "[REDIS] [GET] Key: {} ",
-        key.to_string()
+        key
     ));
 
     let mut conn = pool.get().await?;
@@ -75,7 +93,7 @@ pub async fn get_key<T: serde::de::DeserializeOwned>(
 pub async fn delete_key(pool: &RedisDatabase, key: &str) -> Result<()> {
     let timer = TimePrinter::with_message(&format!(
         "[REDIS] [DELETE] Key: {} ",
-        key.to_string()
+        key
     ));
 
     let mut conn = pool.get().await?;
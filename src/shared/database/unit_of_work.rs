@@ -0,0 +1,165 @@
+use std::future::Future;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Error, Result};
+use rand::Rng;
+use sqlx::mysql::{MySqlArguments, MySqlDatabaseError, MySqlQueryResult, MySqlRow};
+use sqlx::query::Query;
+use sqlx::{MySql, Transaction};
+use tokio::sync::Mutex;
+use tracing::warn;
+
+use crate::shared::database::mysql_pools::MySqlPools;
+
+
+/// Attempts of a unit of work before a deadlock is reported to the caller.
+pub const MAX_TRANSACTION_ATTEMPTS: u32 = 3;
+/// Base delay before retrying a deadlocked unit of work, grows with every attempt.
+const RETRY_BACKOFF: Duration = Duration::from_millis(50);
+
+/// `ER_LOCK_DEADLOCK`
+const MYSQL_DEADLOCK: u16 = 1213;
+/// `ER_LOCK_WAIT_TIMEOUT`
+const MYSQL_LOCK_WAIT_TIMEOUT: u16 = 1205;
+
+
+/// Database transaction shared by the repositories taking part in one operation.
+///
+/// Repositories join it with `in_unit_of_work`, after which all their procedure calls run inside
+/// the transaction. Clones share the same transaction; it ends with [`UnitOfWork::commit`] or
+/// [`UnitOfWork::rollback`], and is rolled back if dropped without either.
+#[derive(Clone)]
+pub struct UnitOfWork {
+    transaction: Arc<Mutex<Option<Transaction<'static, MySql>>>>,
+    savepoints: Arc<AtomicU32>,
+}
+
+/// Named point inside a unit of work that can be rolled back to without ending the transaction.
+#[derive(Debug)]
+pub struct Savepoint {
+    name: String,
+}
+
+impl UnitOfWork {
+    /// Starts a transaction on the primary.
+    pub async fn begin(pools: &MySqlPools) -> Result<Self> {
+        let transaction = pools.primary().begin().await?;
+        Ok(Self {
+            transaction: Arc::new(Mutex::new(Some(transaction))),
+            savepoints: Arc::new(AtomicU32::new(0)),
+        })
+    }
+
+    // Statements are serialized: a transaction runs on a single connection.
+
+    pub async fn execute(&self, query: Query<'_, MySql, MySqlArguments>) -> Result<MySqlQueryResult> {
+        let mut guard = self.transaction.lock().await;
+        let transaction = guard.as_mut().ok_or_else(finished)?;
+        Ok(query.execute(&mut **transaction).await?)
+    }
+
+    pub async fn fetch_optional(&self, query: Query<'_, MySql, MySqlArguments>) -> Result<Option<MySqlRow>> {
+        let mut guard = self.transaction.lock().await;
+        let transaction = guard.as_mut().ok_or_else(finished)?;
+        Ok(query.fetch_optional(&mut **transaction).await?)
+    }
+
+    pub async fn fetch_one(&self, query: Query<'_, MySql, MySqlArguments>) -> Result<MySqlRow> {
+        let mut guard = self.transaction.lock().await;
+        let transaction = guard.as_mut().ok_or_else(finished)?;
+        Ok(query.fetch_one(&mut **transaction).await?)
+    }
+
+    pub async fn fetch_all(&self, query: Query<'_, MySql, MySqlArguments>) -> Result<Vec<MySqlRow>> {
+        let mut guard = self.transaction.lock().await;
+        let transaction = guard.as_mut().ok_or_else(finished)?;
+        Ok(query.fetch_all(&mut **transaction).await?)
+    }
+
+    pub async fn commit(self) -> Result<()> {
+        match self.transaction.lock().await.take() {
+            Some(transaction) => Ok(transaction.commit().await?),
+            None => Err(finished()),
+        }
+    }
+
+    pub async fn rollback(self) -> Result<()> {
+        match self.transaction.lock().await.take() {
+            Some(transaction) => Ok(transaction.rollback().await?),
+            None => Ok(()),
+        }
+    }
+
+    pub async fn savepoint(&self) -> Result<Savepoint> {
+        let savepoint = Savepoint {
+            name: format!("sp_{}", self.savepoints.fetch_add(1, Ordering::Relaxed) + 1),
+        };
+        self.execute_statement(&format!("SAVEPOINT {}", savepoint.name)).await?;
+        Ok(savepoint)
+    }
+
+    /// Undoes everything done since `savepoint`, which stays usable.
+    pub async fn rollback_to(&self, savepoint: &Savepoint) -> Result<()> {
+        self.execute_statement(&format!("ROLLBACK TO SAVEPOINT {}", savepoint.name)).await
+    }
+
+    /// Keeps the work done since `savepoint` and forgets it.
+    pub async fn release(&self, savepoint: Savepoint) -> Result<()> {
+        self.execute_statement(&format!("RELEASE SAVEPOINT {}", savepoint.name)).await
+    }
+
+    async fn execute_statement(&self, statement: &str) -> Result<()> {
+        self.execute(sqlx::query(statement)).await.map(|_| ())
+    }
+}
+
+
+fn finished() -> Error {
+    Error::msg("The unit of work is already committed or rolled back")
+}
+
+
+/// Runs `work` in a new unit of work, committing when it succeeds and rolling back when it fails.
+///
+/// The whole unit is retried with a growing delay when MySQL picks it as a deadlock victim or
+/// times out waiting for a lock, so `work` must not have side effects outside the database.
+pub async fn transactional<R, F, Fut>(pools: &MySqlPools, work: F) -> Result<R>
+where
+    F: Fn(UnitOfWork) -> Fut,
+    Fut: Future<Output = Result<R>>,
+{
+    let mut attempt = 1;
+    loop {
+        let unit_of_work = UnitOfWork::begin(pools).await?;
+        let result = match work(unit_of_work.clone()).await {
+            Ok(value) => unit_of_work.commit().await.map(|_| value),
+            Err(e) => {
+                if let Err(rollback_error) = unit_of_work.rollback().await {
+                    warn!("[MYSQL] Rollback failed: {}", rollback_error);
+                }
+                Err(e)
+            },
+        };
+
+        match result {
+            Err(e) if attempt < MAX_TRANSACTION_ATTEMPTS && is_retryable(&e) => {
+                warn!("[MYSQL] Transaction attempt {} lost a lock, retrying: {}", attempt, e);
+                let jitter = rand::rng().random_range(0.5..1.5);
+                tokio::time::sleep(RETRY_BACKOFF.mul_f64(attempt as f64 * jitter)).await;
+                attempt += 1;
+            },
+            result => return result,
+        }
+    }
+}
+
+/// Whether the error chain holds a MySQL deadlock or lock wait timeout.
+pub fn is_retryable(error: &Error) -> bool {
+    error.chain()
+        .filter_map(|cause| cause.downcast_ref::<sqlx::Error>())
+        .filter_map(|e| e.as_database_error())
+        .filter_map(|e| e.try_downcast_ref::<MySqlDatabaseError>())
+        .any(|e| matches!(e.number(), MYSQL_DEADLOCK | MYSQL_LOCK_WAIT_TIMEOUT))
+}
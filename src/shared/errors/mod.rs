@@ -0,0 +1,61 @@
+use axum::http::StatusCode;
+use thiserror::Error;
+
+
+/// Service failures that controllers need to tell apart from a generic error.
+/// Services return them wrapped in `anyhow::Error`; use [`status_code_of`] to map them back.
+#[derive(Debug, Error)]
+pub enum ServiceError {
+    #[error("{0} not found")]
+    NotFound(String),
+
+    #[error("{0} has been modified by another request")]
+    VersionMismatch(String),
+
+    #[error("{0}")]
+    Validation(String),
+
+    #[error("{0}")]
+    Forbidden(String),
+
+    #[error("{0}")]
+    Conflict(String),
+
+    #[error("{0}")]
+    PayloadTooLarge(String),
+
+    #[error("{0}")]
+    UnsupportedMediaType(String),
+}
+
+impl ServiceError {
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            ServiceError::NotFound(_) => StatusCode::NOT_FOUND,
+            ServiceError::VersionMismatch(_) => StatusCode::PRECONDITION_FAILED,
+            ServiceError::Validation(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            ServiceError::Forbidden(_) => StatusCode::FORBIDDEN,
+            ServiceError::Conflict(_) => StatusCode::CONFLICT,
+            ServiceError::PayloadTooLarge(_) => StatusCode::PAYLOAD_TOO_LARGE,
+            ServiceError::UnsupportedMediaType(_) => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+        }
+    }
+}
+
+
+/// Fails with a `Validation` error when a required text field is empty or only whitespace.
+pub fn require_not_blank(field: &str, value: &str) -> Result<(), ServiceError> {
+    if value.trim().is_empty() {
+        return Err(ServiceError::Validation(format!("{} is required", field)));
+    }
+    Ok(())
+}
+
+
+/// Returns the HTTP status matching a service error, or 500 for anything unexpected.
+pub fn status_code_of(error: &anyhow::Error) -> StatusCode {
+    match error.downcast_ref::<ServiceError>() {
+        Some(service_error) => service_error.status_code(),
+        None => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
@@ -0,0 +1,146 @@
+use std::fmt::Display;
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use futures_util::future::join_all;
+use mongodb::bson::doc;
+use serde::Serialize;
+use sqlx::MySqlPool;
+use tracing::warn;
+use utoipa::ToSchema;
+
+use crate::shared::cache::store::CacheStore;
+use crate::shared::configuration::AppDatabaseConfig;
+
+
+/// Upper bound of a single dependency check, a hanging dependency is reported as down.
+const CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum HealthStatus {
+    Up,
+    Down,
+    /// The instance is shutting down and takes no new traffic
+    Draining,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DependencyHealth {
+    pub name: String,
+    pub status: HealthStatus,
+    /// A required dependency being down makes the instance not ready
+    pub required: bool,
+    pub latency_ms: u64,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct HealthReport {
+    pub status: HealthStatus,
+    pub dependencies: Vec<DependencyHealth>,
+}
+
+impl HealthReport {
+    /// Report of a running process, without looking at its dependencies.
+    pub fn alive() -> Self {
+        Self { status: HealthStatus::Up, dependencies: Vec::new() }
+    }
+
+    /// Report of an instance that is shutting down, its dependencies are not checked anymore.
+    pub fn draining() -> Self {
+        Self { status: HealthStatus::Draining, dependencies: Vec::new() }
+    }
+
+    pub fn is_up(&self) -> bool {
+        self.status == HealthStatus::Up
+    }
+}
+
+
+/// Probes of the dependencies of the instance, built once at startup.
+///
+/// Only MySQL is required. Redis is reported but not required: without it the services bypass
+/// the cache. MongoDB and Neo4j are reported when configured, no request depends on them yet.
+pub struct HealthChecks {
+    mysql: MySqlPool,
+    cache: Option<CacheStore>,
+    mongo: Option<(mongodb::Client, String)>,
+    neo4j: Option<neo4rs::Graph>,
+}
+
+impl HealthChecks {
+    pub fn new(mysql: MySqlPool, cache: Option<CacheStore>) -> Self {
+        Self { mysql, cache, mongo: None, neo4j: None }
+    }
+
+    /// Also checks the MongoDB and Neo4j servers of the configuration, neither is connected yet.
+    pub async fn with_config(mut self, database_config: &AppDatabaseConfig) -> Result<Self> {
+        if let Some(mongo_config) = &database_config.mongo {
+            let client = mongodb::Client::with_uri_str(&mongo_config.uri).await?;
+            self.mongo = Some((client, mongo_config.database.clone()));
+        }
+        if let Some(neo4j_config) = &database_config.neo4j {
+            let graph = neo4rs::Graph::new(&neo4j_config.uri, &neo4j_config.username, &neo4j_config.password).await?;
+            self.neo4j = Some(graph);
+        }
+        Ok(self)
+    }
+
+    /// Checks every dependency concurrently.
+    pub async fn readiness(&self) -> HealthReport {
+        let mut checks = vec![Box::pin(check("mysql", true, async {
+            sqlx::query("SELECT 1").execute(&self.mysql).await.map(|_| ())
+        })) as std::pin::Pin<Box<dyn Future<Output = DependencyHealth> + Send + '_>>];
+
+        if let Some(cache) = &self.cache {
+            checks.push(Box::pin(check("redis", false, cache.ping())));
+        }
+        if let Some((client, database)) = &self.mongo {
+            checks.push(Box::pin(check("mongodb", false, async {
+                client.database(database).run_command(doc! { "ping": 1 }).await.map(|_| ())
+            })));
+        }
+        if let Some(graph) = &self.neo4j {
+            checks.push(Box::pin(check("neo4j", false, graph.run(neo4rs::query("RETURN 1")))));
+        }
+
+        let dependencies = join_all(checks).await;
+        let ready = dependencies.iter().all(|dependency| !dependency.required || dependency.status == HealthStatus::Up);
+        HealthReport {
+            status: if ready { HealthStatus::Up } else { HealthStatus::Down },
+            dependencies,
+        }
+    }
+
+    /// Closes the clients that are only held by the checks.
+    pub async fn close(&self) {
+        if let Some((client, _)) = &self.mongo {
+            client.clone().shutdown().await;
+        }
+    }
+}
+
+
+/// Runs `probe`, logging why a dependency is down: the unauthenticated report only tells it is.
+async fn check<E: Display>(name: &str, required: bool, probe: impl Future<Output = Result<(), E>>) -> DependencyHealth {
+    let started = Instant::now();
+    let up = match tokio::time::timeout(CHECK_TIMEOUT, probe).await {
+        Ok(Ok(())) => true,
+        Ok(Err(e)) => {
+            warn!("[HEALTH] {} is down: {}", name, e);
+            false
+        },
+        Err(_) => {
+            warn!("[HEALTH] {} is down: no answer within {}ms", name, CHECK_TIMEOUT.as_millis());
+            false
+        },
+    };
+    DependencyHealth {
+        name: name.to_string(),
+        status: if up { HealthStatus::Up } else { HealthStatus::Down },
+        required,
+        latency_ms: started.elapsed().as_millis() as u64,
+    }
+}
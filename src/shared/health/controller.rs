@@ -0,0 +1,43 @@
+use axum::{Router, routing::get, extract::State, Json, http::StatusCode};
+
+use crate::shared::health::checks::HealthReport;
+use crate::shared::state::AppState;
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/healthz", get(liveness))
+        .route("/readyz", get(readiness))
+}
+
+
+#[utoipa::path(
+    get,
+    path = "/healthz",
+    responses(
+        (status = StatusCode::OK, description = "The process is running", body = HealthReport)
+    ),
+    tag = "Health"
+)]
+pub async fn liveness() -> Json<HealthReport> {
+    Json(HealthReport::alive())
+}
+
+
+#[utoipa::path(
+    get,
+    path = "/readyz",
+    responses(
+        (status = StatusCode::OK, description = "Every required dependency is up", body = HealthReport),
+        (status = StatusCode::SERVICE_UNAVAILABLE, description = "A required dependency is down or the instance is shutting down", body = HealthReport)
+    ),
+    tag = "Health"
+)]
+pub async fn readiness(State(state): State<AppState>) -> (StatusCode, Json<HealthReport>) {
+    if state.shutdown.is_draining() {
+        return (StatusCode::SERVICE_UNAVAILABLE, Json(HealthReport::draining()));
+    }
+
+    let report = state.health_checks.readiness().await;
+    let status = if report.is_up() { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    (status, Json(report))
+}
@@ -0,0 +1,72 @@
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+
+/// Parsed `If-Match` request header.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub enum IfMatch {
+    /// `If-Match: *`, matches any existing representation.
+    Any,
+    /// One or more strong entity tags, each holding a row version.
+    Versions(Vec<i64>),
+}
+
+impl IfMatch {
+    pub fn matches(&self, version: i64) -> bool {
+        match self {
+            IfMatch::Any => true,
+            IfMatch::Versions(versions) => versions.contains(&version),
+        }
+    }
+}
+
+
+/// Formats a row version as a strong entity tag, e.g. `"3"`.
+pub fn format_etag(version: i64) -> String {
+    format!("\"{}\"", version)
+}
+
+/// Builds the response headers carrying the `ETag` of a resource.
+/// Nothing is emitted when the version is unknown.
+pub fn etag_headers(version: Option<i64>) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    if let Some(version) = version
+        && let Ok(value) = HeaderValue::from_str(&format_etag(version)) {
+        headers.insert(header::ETAG, value);
+    }
+    headers
+}
+
+/// Reads the `If-Match` header required by mutating endpoints.
+///
+/// Answers `428 Precondition Required` when the header is missing and
+/// `400 Bad Request` when it cannot be parsed. Weak tags never match,
+/// as `If-Match` uses the strong comparison.
+pub fn parse_if_match(headers: &HeaderMap) -> Result<IfMatch, StatusCode> {
+    let value = headers
+        .get(header::IF_MATCH)
+        .ok_or(StatusCode::PRECONDITION_REQUIRED)?
+        .to_str()
+        .map_err(|_| StatusCode::BAD_REQUEST)?
+        .trim();
+
+    if value == "*" {
+        return Ok(IfMatch::Any);
+    }
+
+    let mut versions = Vec::new();
+    for tag in value.split(',').map(str::trim).filter(|tag| !tag.is_empty()) {
+        if tag.starts_with("W/") {
+            continue;
+        }
+        let version = tag
+            .strip_prefix('"')
+            .and_then(|tag| tag.strip_suffix('"'))
+            .and_then(|tag| tag.parse::<i64>().ok())
+            .ok_or(StatusCode::BAD_REQUEST)?;
+        versions.push(version);
+    }
+
+    Ok(IfMatch::Versions(versions))
+}
@@ -0,0 +1,3 @@
+pub mod etag;
+pub mod security;
+pub mod versioning;
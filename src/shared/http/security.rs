@@ -0,0 +1,99 @@
+use std::time::Duration;
+
+use axum::{
+    extract::{DefaultBodyLimit, Request},
+    http::{header, HeaderName, HeaderValue, Method},
+    Router,
+};
+use tower_http::{
+    cors::{AllowOrigin, CorsLayer},
+    limit::RequestBodyLimitLayer,
+    request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer},
+    set_header::SetResponseHeaderLayer,
+    timeout::TimeoutLayer,
+    trace::TraceLayer,
+};
+
+use crate::shared::configuration::{AppConfig, AppHttpConfig};
+
+
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// CSP of the JSON API: responses load nothing and are never framed.
+pub const API_CONTENT_SECURITY_POLICY: &str = "default-src 'none'; frame-ancestors 'none'";
+
+/// CSP of the Swagger UI pages, which load their own scripts and styles, with inline styles and `data:` images.
+pub const SWAGGER_CONTENT_SECURITY_POLICY: &str =
+    "default-src 'self'; img-src 'self' data:; style-src 'self' 'unsafe-inline'; script-src 'self'; frame-ancestors 'none'";
+
+/// Multipart framing accepted on top of `storage.max_upload_bytes` by the hard body limit.
+const MULTIPART_OVERHEAD_BYTES: usize = 64 * 1024;
+
+/// Request headers browsers may send cross-origin.
+const CORS_ALLOWED_HEADERS: [&str; 7] = [
+    "authorization", "content-type", "if-match", "idempotency-key", "x-api-key", REQUEST_ID_HEADER, "accept",
+];
+
+/// Response headers cross-origin scripts may read.
+const CORS_EXPOSED_HEADERS: [&str; 10] = [
+    "etag", "location", "retry-after", "ratelimit-limit", "ratelimit-remaining", "ratelimit-reset", "ratelimit-policy",
+    "idempotent-replayed", REQUEST_ID_HEADER, "content-disposition",
+];
+
+
+/// Sets the `Content-Security-Policy` of the responses that do not have one yet.
+pub fn content_security_policy(policy: &'static str) -> SetResponseHeaderLayer<HeaderValue> {
+    SetResponseHeaderLayer::if_not_present(header::CONTENT_SECURITY_POLICY, HeaderValue::from_static(policy))
+}
+
+/// Wraps every route of `router`, from the outside in: request ID, tracing, CORS, security headers,
+/// timeout and body limits.
+///
+/// The request ID of the caller is kept, or a UUID generated, and sent back in `X-Request-Id`.
+/// `http.max_body_bytes` applies to the body extractors; the upload routes lift it and check
+/// `storage.max_upload_bytes` themselves, bounded by a hard limit slightly above it.
+pub fn with_security_layers(router: Router, config: &AppConfig) -> Router {
+    let http_config = &config.http;
+    let hard_body_limit = http_config.max_body_bytes.max(config.storage.max_upload_bytes + MULTIPART_OVERHEAD_BYTES);
+
+    let mut router = router
+        .layer(DefaultBodyLimit::max(http_config.max_body_bytes))
+        .layer(RequestBodyLimitLayer::new(hard_body_limit))
+        .layer(TimeoutLayer::new(Duration::from_secs(http_config.request_timeout_seconds)))
+        .layer(SetResponseHeaderLayer::if_not_present(header::X_CONTENT_TYPE_OPTIONS, HeaderValue::from_static("nosniff")))
+        .layer(SetResponseHeaderLayer::if_not_present(header::X_FRAME_OPTIONS, HeaderValue::from_static("DENY")))
+        .layer(SetResponseHeaderLayer::if_not_present(header::REFERRER_POLICY, HeaderValue::from_static("no-referrer")));
+    if http_config.hsts_max_age_seconds > 0 {
+        let hsts = format!("max-age={}; includeSubDomains", http_config.hsts_max_age_seconds);
+        if let Ok(hsts) = HeaderValue::from_str(&hsts) {
+            router = router.layer(SetResponseHeaderLayer::if_not_present(header::STRICT_TRANSPORT_SECURITY, hsts));
+        }
+    }
+
+    router
+        .layer(cors_layer(http_config))
+        .layer(TraceLayer::new_for_http().make_span_with(|request: &Request| {
+            let request_id = request.headers().get(REQUEST_ID_HEADER).and_then(|value| value.to_str().ok()).unwrap_or("-");
+            tracing::info_span!("request", method = %request.method(), uri = %request.uri(), request_id)
+        }))
+        .layer(PropagateRequestIdLayer::new(HeaderName::from_static(REQUEST_ID_HEADER)))
+        .layer(SetRequestIdLayer::new(HeaderName::from_static(REQUEST_ID_HEADER), MakeRequestUuid))
+}
+
+/// Allows the configured origins only, `*` allowing any of them.
+fn cors_layer(http_config: &AppHttpConfig) -> CorsLayer {
+    let origins = &http_config.cors_allowed_origins;
+    let allow_origin = if origins.iter().any(|origin| origin == "*") {
+        AllowOrigin::any()
+    } else {
+        AllowOrigin::list(origins.iter().filter_map(|origin| origin.parse::<HeaderValue>().ok()))
+    };
+
+    CorsLayer::new()
+        .allow_origin(allow_origin)
+        .allow_methods([Method::GET, Method::HEAD, Method::POST, Method::PUT, Method::PATCH, Method::DELETE, Method::OPTIONS])
+        .allow_headers(CORS_ALLOWED_HEADERS.map(HeaderName::from_static))
+        .expose_headers(CORS_EXPOSED_HEADERS.map(HeaderName::from_static))
+        .allow_credentials(http_config.cors_allow_credentials)
+        .max_age(Duration::from_secs(http_config.cors_max_age_seconds))
+}
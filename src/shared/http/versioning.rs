@@ -0,0 +1,93 @@
+use std::borrow::Cow;
+use std::sync::Arc;
+
+use axum::extract::{OriginalUri, Request, State};
+use axum::http::{header, HeaderName, HeaderValue};
+use axum::middleware::Next;
+use axum::response::Response;
+
+use crate::shared::configuration::AppApiDeprecationConfig;
+
+/// Versions served under `/api/<version>`, oldest first.
+pub const API_VERSIONS: [&str; 2] = ["v1", "v2"];
+pub const LATEST_API_VERSION: &str = "v2";
+/// Key of the bare `/api` alias of v1 in `api.deprecations`.
+pub const UNVERSIONED: &str = "unversioned";
+
+/// RFC 9745, the date the routes were deprecated as `@<unix seconds>`.
+pub const DEPRECATION_HEADER: HeaderName = HeaderName::from_static("deprecation");
+/// RFC 8594, the date the routes may stop responding.
+pub const SUNSET_HEADER: HeaderName = HeaderName::from_static("sunset");
+
+/// Prefix of the routes of `version`, `/api` for [`UNVERSIONED`].
+pub fn api_prefix(version: &str) -> String {
+    if version == UNVERSIONED {
+        "/api".to_string()
+    } else {
+        format!("/api/{}", version)
+    }
+}
+
+/// `path` without its version segment, `/api/v1/user/{user_id}` being `/api/user/{user_id}`.
+pub fn unversioned_path(path: &str) -> Cow<'_, str> {
+    let versioned = path.strip_prefix("/api/")
+        .and_then(|rest| rest.split_once('/').map_or(Some((rest, "")), Some))
+        .filter(|(version, _)| API_VERSIONS.contains(version));
+    match versioned {
+        Some((_, "")) => Cow::Borrowed("/api"),
+        Some((_, rest)) => Cow::Owned(format!("/api/{}", rest)),
+        None => Cow::Borrowed(path),
+    }
+}
+
+
+/// Headers announcing the retirement of the routes under `prefix`, see [`deprecation_middleware`].
+pub struct Deprecation {
+    prefix: String,
+    successor_prefix: String,
+    deprecation: HeaderValue,
+    sunset: Option<HeaderValue>,
+}
+
+impl Deprecation {
+    /// Retirement of `version` as configured, clients being pointed to the same route of `successor`.
+    pub fn from_config(version: &str, successor: &str, deprecation_config: &AppApiDeprecationConfig) -> Self {
+        let deprecation = format!("@{}", deprecation_config.since.timestamp());
+        let sunset = deprecation_config.sunset
+            .map(|sunset| sunset.format("%a, %d %b %Y %H:%M:%S GMT").to_string());
+        Self {
+            prefix: api_prefix(version),
+            successor_prefix: api_prefix(successor),
+            deprecation: HeaderValue::from_str(&deprecation).expect("a number is a valid header value"),
+            sunset: sunset.map(|sunset| HeaderValue::from_str(&sunset).expect("an HTTP date is a valid header value")),
+        }
+    }
+
+    fn successor_link(&self, path: &str) -> Option<HeaderValue> {
+        let rest = path.strip_prefix(&self.prefix)?;
+        HeaderValue::from_str(&format!("<{}{}>; rel=\"successor-version\"", self.successor_prefix, rest)).ok()
+    }
+}
+
+/// Adds `Deprecation`, `Sunset` and a `Link` to the successor route to every response, errors included.
+pub async fn deprecation_middleware(
+    State(deprecation): State<Arc<Deprecation>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    // nested routers see the path without their prefix
+    let path = request.extensions().get::<OriginalUri>()
+        .map(|original_uri| original_uri.path().to_string())
+        .unwrap_or_else(|| request.uri().path().to_string());
+
+    let mut response = next.run(request).await;
+    let headers = response.headers_mut();
+    headers.insert(DEPRECATION_HEADER, deprecation.deprecation.clone());
+    if let Some(sunset) = &deprecation.sunset {
+        headers.insert(SUNSET_HEADER, sunset.clone());
+    }
+    if let Some(link) = deprecation.successor_link(&path) {
+        headers.append(header::LINK, link);
+    }
+    response
+}
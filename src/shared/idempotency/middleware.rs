@@ -0,0 +1,152 @@
+use std::time::{Duration, Instant};
+
+use axum::{
+    body::{to_bytes, Body},
+    extract::{Request, State},
+    http::{Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use sha2::{Digest, Sha256};
+use tracing::warn;
+
+use crate::shared::cache::store::CacheStore;
+use crate::shared::idempotency::{is_valid_key, StoredResponse, IDEMPOTENCY_KEY_HEADER};
+use crate::shared::state::AppState;
+
+
+/// Delay between two reads of the stored response while another request holds the key.
+const LOCK_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+
+enum KeyLock {
+    Acquired(String),
+    Held,
+    /// Redis is failing, the request runs without idempotency
+    Unavailable,
+}
+
+
+/// Makes `POST` requests carrying an `Idempotency-Key` safe to retry.
+///
+/// The first request with a key runs and its response is stored for the caller; retries with the same
+/// key get that response back, or `422 Unprocessable Entity` when their method, URI or body differ.
+/// Concurrent duplicates wait for the first one to finish, `409 Conflict` after the lock timeout.
+/// Server errors are not stored, a retry runs the request again.
+pub async fn idempotency_middleware(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if request.method() != Method::POST {
+        return next.run(request).await;
+    }
+    let key = match request.headers().get(IDEMPOTENCY_KEY_HEADER).map(|value| value.to_str()) {
+        None => return next.run(request).await,
+        Some(Ok(key)) if is_valid_key(key) => key.to_string(),
+        Some(_) => return StatusCode::BAD_REQUEST.into_response(),
+    };
+    let Some(cache) = state.cache.clone() else {
+        return next.run(request).await;
+    };
+
+    let caller = state.rate_limiter.subject_of(&request, &state.jwt_verifier);
+    let record_key = state.cache_keys.idempotency(&caller, &hex::encode(Sha256::digest(key.as_bytes())));
+    let (parts, body) = request.into_parts();
+    let Ok(body) = to_bytes(body, state.config.storage.max_upload_bytes).await else {
+        return StatusCode::PAYLOAD_TOO_LARGE.into_response();
+    };
+    let fingerprint = {
+        let mut hasher = Sha256::new();
+        hasher.update(parts.method.as_str());
+        hasher.update(b" ");
+        hasher.update(parts.uri.to_string());
+        hasher.update(b"\n");
+        hasher.update(&body);
+        hex::encode(hasher.finalize())
+    };
+    let request = Request::from_parts(parts, Body::from(body));
+
+    let lock_key = state.cache_keys.lock(&record_key);
+    let lock_ttl = Duration::from_secs(state.config.idempotency.lock_seconds);
+    let deadline = Instant::now() + lock_ttl;
+    loop {
+        if let Some(stored) = stored_response(&cache, &record_key).await {
+            if stored.fingerprint != fingerprint {
+                return StatusCode::UNPROCESSABLE_ENTITY.into_response();
+            }
+            return stored.replay();
+        }
+
+        match lock(&cache, &lock_key, lock_ttl).await {
+            KeyLock::Acquired(token) => {
+                let response = next.run(request).await;
+                let response = store_response(&cache, &record_key, fingerprint, state.config.idempotency.ttl_seconds, response).await;
+                unlock(&cache, &lock_key, &token).await;
+                return response;
+            },
+            KeyLock::Unavailable => return next.run(request).await,
+            KeyLock::Held if Instant::now() >= deadline => {
+                warn!("[IDEMPOTENCY] {} is still being processed for {}", key, caller);
+                return StatusCode::CONFLICT.into_response();
+            },
+            KeyLock::Held => tokio::time::sleep(LOCK_POLL_INTERVAL).await,
+        }
+    }
+}
+
+
+async fn stored_response(cache: &CacheStore, record_key: &str) -> Option<StoredResponse> {
+    let stored = cache.record("GET", cache.backend()?.get(record_key).await)??;
+    match serde_json::from_str(&stored) {
+        Ok(stored) => Some(stored),
+        Err(e) => {
+            warn!("[IDEMPOTENCY] Unreadable stored response {}: {}", record_key, e);
+            None
+        },
+    }
+}
+
+/// Stores the response unless it is a server error, and hands it back.
+async fn store_response(cache: &CacheStore, record_key: &str, fingerprint: String, ttl_seconds: u64, response: Response) -> Response {
+    if response.status().is_server_error() {
+        return response;
+    }
+    let (parts, body) = response.into_parts();
+    let body = match to_bytes(body, usize::MAX).await {
+        Ok(body) => body,
+        Err(e) => {
+            warn!("[IDEMPOTENCY] Cannot read the response of {}: {}", record_key, e);
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        },
+    };
+
+    let stored = StoredResponse::new(fingerprint, parts.status, &parts.headers, &body)
+        .and_then(|stored| serde_json::to_string(&stored).ok());
+    if let (Some(stored), Some(backend)) = (stored, cache.backend()) {
+        let result = backend.set_tagged(record_key, &stored, ttl_seconds, &[]).await;
+        cache.record("SET", result);
+    }
+    Response::from_parts(parts, Body::from(body))
+}
+
+async fn lock(cache: &CacheStore, lock_key: &str, ttl: Duration) -> KeyLock {
+    let Some(backend) = cache.backend() else {
+        return KeyLock::Unavailable;
+    };
+    let token = uuid::Uuid::new_v4().to_string();
+    let acquired = backend.set_if_absent(lock_key, &token, ttl).await;
+    match cache.record("LOCK", acquired) {
+        Some(true) => KeyLock::Acquired(token),
+        Some(false) => KeyLock::Held,
+        None => KeyLock::Unavailable,
+    }
+}
+
+async fn unlock(cache: &CacheStore, lock_key: &str, token: &str) {
+    let Some(backend) = cache.backend() else {
+        return;
+    };
+    let released = backend.delete_if_equals(lock_key, token).await;
+    cache.record("UNLOCK", released);
+}
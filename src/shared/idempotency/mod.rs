@@ -0,0 +1,62 @@
+pub mod middleware;
+
+use axum::http::{header, HeaderMap, HeaderName, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+use serde::{Deserialize, Serialize};
+
+
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+
+/// Set on responses replayed from a previous request with the same key.
+pub const IDEMPOTENT_REPLAYED_HEADER: &str = "idempotent-replayed";
+
+/// Longest accepted `Idempotency-Key`, a UUID is the expected value.
+pub const MAX_KEY_LENGTH: usize = 255;
+
+/// Response headers worth replaying, the others are recomputed by the middlewares.
+const REPLAYED_HEADERS: [HeaderName; 3] = [header::CONTENT_TYPE, header::ETAG, header::LOCATION];
+
+
+/// Response of a request with an `Idempotency-Key`, replayed for its retries.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StoredResponse {
+    /// Hash of the method, URI and body of the request, retries must send the same
+    pub fingerprint: String,
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl StoredResponse {
+    /// `None` for bodies that are not text, such responses are not stored.
+    pub fn new(fingerprint: String, status: StatusCode, headers: &HeaderMap, body: &[u8]) -> Option<Self> {
+        let body = String::from_utf8(body.to_vec()).ok()?;
+        let headers = REPLAYED_HEADERS.iter()
+            .filter_map(|name| {
+                let value = headers.get(name)?.to_str().ok()?;
+                Some((name.to_string(), value.to_string()))
+            })
+            .collect();
+        Some(Self { fingerprint, status: status.as_u16(), headers, body })
+    }
+
+    pub fn replay(self) -> Response {
+        let status = StatusCode::from_u16(self.status).unwrap_or(StatusCode::OK);
+        let mut response = (status, self.body).into_response();
+        let headers = response.headers_mut();
+        headers.remove(header::CONTENT_TYPE);
+        for (name, value) in self.headers {
+            if let (Ok(name), Ok(value)) = (HeaderName::try_from(name), HeaderValue::try_from(value)) {
+                headers.insert(name, value);
+            }
+        }
+        headers.insert(IDEMPOTENT_REPLAYED_HEADER, HeaderValue::from_static("true"));
+        response
+    }
+}
+
+
+/// Keys are opaque to the server, but must be short visible ASCII.
+pub fn is_valid_key(key: &str) -> bool {
+    !key.is_empty() && key.len() <= MAX_KEY_LENGTH && key.bytes().all(|byte| byte.is_ascii_graphic())
+}
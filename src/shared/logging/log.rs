@@ -142,6 +142,12 @@ pub struct TimePrinter {
     message: String,
 }
 
+impl Default for TimePrinter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl TimePrinter {
     pub fn new() -> Self {
         Self {
@@ -0,0 +1,124 @@
+use std::io::Cursor;
+
+use image::{imageops::FilterType, ImageFormat, ImageReader, Limits};
+
+use crate::shared::errors::ServiceError;
+
+
+/// Edge lengths, in pixels, of the square boxes profile picture thumbnails are fitted in.
+pub const THUMBNAIL_SIZES: [u32; 2] = [64, 256];
+
+/// Largest width or height accepted for an uploaded image.
+const MAX_IMAGE_DIMENSION: u32 = 8192;
+
+
+/// Uploaded image after validation, with its encoded thumbnails.
+#[derive(Debug)]
+pub struct ProcessedImage {
+    pub format: ImageFormat,
+    pub original: Vec<u8>,
+    /// `(size, bytes)` for each entry of `THUMBNAIL_SIZES`, encoded in `thumbnail_format`
+    pub thumbnails: Vec<(u32, Vec<u8>)>,
+    pub thumbnail_format: ImageFormat,
+}
+
+impl ProcessedImage {
+    pub fn extension(&self) -> &'static str {
+        extension_of(self.format)
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        self.format.to_mime_type()
+    }
+
+    pub fn thumbnail_extension(&self) -> &'static str {
+        extension_of(self.thumbnail_format)
+    }
+
+    pub fn thumbnail_content_type(&self) -> &'static str {
+        self.thumbnail_format.to_mime_type()
+    }
+}
+
+
+/// Validates an uploaded picture and renders its thumbnails.
+///
+/// The format is sniffed from the content, the client provided content type is not trusted.
+/// Decoding is CPU bound, call it from `spawn_blocking`.
+pub fn process_profile_picture(bytes: Vec<u8>, max_bytes: usize) -> Result<ProcessedImage, ServiceError> {
+    if bytes.is_empty() {
+        return Err(ServiceError::Validation("Uploaded file is empty".to_string()));
+    }
+    if bytes.len() > max_bytes {
+        return Err(ServiceError::PayloadTooLarge(format!("Uploaded file exceeds {} bytes", max_bytes)));
+    }
+
+    let format = image::guess_format(&bytes)
+        .map_err(|_| ServiceError::UnsupportedMediaType("Uploaded file is not a supported image".to_string()))?;
+    if !matches!(format, ImageFormat::Png | ImageFormat::Jpeg | ImageFormat::Gif | ImageFormat::WebP) {
+        return Err(ServiceError::UnsupportedMediaType(format!("Unsupported image type {}", format.to_mime_type())));
+    }
+
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(MAX_IMAGE_DIMENSION);
+    limits.max_image_height = Some(MAX_IMAGE_DIMENSION);
+
+    let mut reader = ImageReader::with_format(Cursor::new(&bytes), format);
+    reader.limits(limits);
+    let image = reader.decode()
+        .map_err(|e| ServiceError::Validation(format!("Invalid image: {}", e)))?;
+
+    let thumbnail_format = thumbnail_format_of(format);
+
+    let mut thumbnails = Vec::with_capacity(THUMBNAIL_SIZES.len());
+    for size in THUMBNAIL_SIZES {
+        let thumbnail = image.resize(size, size, FilterType::Lanczos3);
+        let thumbnail = match thumbnail_format {
+            ImageFormat::Jpeg => image::DynamicImage::ImageRgb8(thumbnail.to_rgb8()),
+            _ => thumbnail,
+        };
+
+        let mut encoded = Cursor::new(Vec::new());
+        thumbnail.write_to(&mut encoded, thumbnail_format)
+            .map_err(|e| ServiceError::Validation(format!("Cannot encode thumbnail: {}", e)))?;
+        thumbnails.push((size, encoded.into_inner()));
+    }
+
+    Ok(ProcessedImage {
+        format,
+        original: bytes,
+        thumbnails,
+        thumbnail_format,
+    })
+}
+
+/// Key of the thumbnail of `size` of the picture stored as `<name>.<extension>`.
+pub fn thumbnail_key(name: &str, size: u32, thumbnail_extension: &str) -> String {
+    format!("{}_{}.{}", name, size, thumbnail_extension)
+}
+
+/// Keys of a stored picture and of the thumbnails rendered for it.
+pub fn picture_keys(key: &str) -> Vec<String> {
+    let Some((name, extension)) = key.rsplit_once('.') else {
+        return vec![key.to_string()];
+    };
+    let thumbnail_extension = ImageFormat::from_extension(extension)
+        .map(|format| extension_of(thumbnail_format_of(format)))
+        .unwrap_or("png");
+
+    std::iter::once(key.to_string())
+        .chain(THUMBNAIL_SIZES.iter().map(|size| thumbnail_key(name, *size, thumbnail_extension)))
+        .collect()
+}
+
+/// Photos stay JPEG, everything else keeps transparency as PNG.
+fn thumbnail_format_of(format: ImageFormat) -> ImageFormat {
+    match format {
+        ImageFormat::Jpeg => ImageFormat::Jpeg,
+        _ => ImageFormat::Png,
+    }
+}
+
+fn extension_of(format: ImageFormat) -> &'static str {
+    format.extensions_str().first().copied().unwrap_or("bin")
+}
@@ -2,7 +2,6 @@ use axum::{
     extract::Request,
     middleware::Next,
     response::Response,
-    http::{Method, StatusCode},
 };
 use std::time::Instant;
 
@@ -15,7 +14,7 @@ pub async fn metrics_and_logging_middleware(
 ) -> Response {
     let method = request.method().clone();
     let uri = request.uri().clone();
-    let path = uri.path().to_string();
+    let _path = uri.path().to_string();
 
     // Start timer for metrics
     let start_time = Instant::now();
@@ -1,2 +1,3 @@
 pub mod registry;
-pub mod metrics_logger;
\ No newline at end of file
+pub mod metrics_logger;
+pub mod pool;
@@ -0,0 +1,39 @@
+use std::time::Duration;
+
+use metrics::{describe_gauge, gauge};
+use sqlx::MySqlPool;
+
+
+pub const DATABASE_POOL_CONNECTIONS: &str = "database_pool_connections";
+pub const DATABASE_POOL_MAX_CONNECTIONS: &str = "database_pool_max_connections";
+pub const DATABASE_POOL_UTILIZATION: &str = "database_pool_utilization";
+
+/// Interval between two samples of the pool state.
+const REPORT_INTERVAL: Duration = Duration::from_secs(15);
+
+
+/// Samples the connection counts of `pool` in the background, labelled with `name`.
+///
+/// Reports idle and in-use connections, the configured maximum and the in-use ratio.
+pub fn spawn_mysql_pool_reporter(pool: MySqlPool, name: String) {
+    describe_gauge!(DATABASE_POOL_CONNECTIONS, "Open database connections by state");
+    describe_gauge!(DATABASE_POOL_MAX_CONNECTIONS, "Maximum size of the database pool");
+    describe_gauge!(DATABASE_POOL_UTILIZATION, "Share of the database pool currently in use");
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(REPORT_INTERVAL);
+        while !pool.is_closed() {
+            interval.tick().await;
+
+            let size = pool.size();
+            let idle = pool.num_idle() as u32;
+            let in_use = size.saturating_sub(idle);
+            let max = pool.options().get_max_connections();
+
+            gauge!(DATABASE_POOL_CONNECTIONS, "pool" => name.clone(), "state" => "idle").set(idle as f64);
+            gauge!(DATABASE_POOL_CONNECTIONS, "pool" => name.clone(), "state" => "in_use").set(in_use as f64);
+            gauge!(DATABASE_POOL_MAX_CONNECTIONS, "pool" => name.clone()).set(max as f64);
+            gauge!(DATABASE_POOL_UTILIZATION, "pool" => name.clone()).set(in_use as f64 / max.max(1) as f64);
+        }
+    });
+}
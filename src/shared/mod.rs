@@ -5,4 +5,14 @@ pub mod metrics;
 pub mod database;
 pub mod openapi;
 pub mod logging;
-pub mod repository;
\ No newline at end of file
+pub mod repository;
+pub mod errors;
+pub mod http;
+pub mod security;
+pub mod storage;
+pub mod media;
+pub mod cache;
+pub mod health;
+pub mod shutdown;
+pub mod rate_limit;
+pub mod idempotency;
@@ -0,0 +1,41 @@
+use std::fmt;
+use std::str::FromStr;
+
+
+/// Exact decimal number, e.g. a price, for `DECIMAL` columns and procedure parameters.
+///
+/// Kept as its text so that no digit is lost to a binary float; MySQL converts it to `DECIMAL`
+/// without rounding.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Decimal(String);
+
+#[derive(Debug, thiserror::Error)]
+#[error("`{0}` is not a decimal number")]
+pub struct InvalidDecimal(String);
+
+impl Decimal {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl FromStr for Decimal {
+    type Err = InvalidDecimal;
+
+    /// Accepts an optional sign, digits and an optional fraction: `-12`, `0.50`.
+    fn from_str(value: &str) -> Result<Self, InvalidDecimal> {
+        let unsigned = value.strip_prefix(['-', '+']).unwrap_or(value);
+        let (integer, fraction) = unsigned.split_once('.').unwrap_or((unsigned, "0"));
+        let is_digits = |part: &str| !part.is_empty() && part.bytes().all(|byte| byte.is_ascii_digit());
+        match is_digits(integer) && is_digits(fraction) {
+            true => Ok(Self(value.to_string())),
+            false => Err(InvalidDecimal(value.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
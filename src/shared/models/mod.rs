@@ -1,2 +1,4 @@
 pub mod response;
-pub mod utils_model;
\ No newline at end of file
+pub mod utils_model;
+pub mod patch;
+pub mod decimal;
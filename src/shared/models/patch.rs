@@ -0,0 +1,58 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use crate::shared::errors::ServiceError;
+
+
+/// A field of a JSON Merge Patch (RFC 7396) document.
+///
+/// Tells apart a member that is missing from the patch (`Absent`, keep the current value),
+/// a member explicitly set to `null` (`Null`, remove the value) and a new value.
+/// Fields must be annotated with `#[serde(default)]` so that missing members become `Absent`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Patch<T> {
+    #[default]
+    Absent,
+    Null,
+    Value(T),
+}
+
+impl<T> Patch<T> {
+    pub fn is_absent(&self) -> bool {
+        matches!(self, Patch::Absent)
+    }
+
+    /// Applies the patch to an optional field.
+    pub fn apply(self, current: Option<T>) -> Option<T> {
+        match self {
+            Patch::Absent => current,
+            Patch::Null => None,
+            Patch::Value(value) => Some(value),
+        }
+    }
+
+    /// Applies the patch to a required field, rejecting a `null` that would remove it.
+    pub fn apply_required(self, current: T, field: &str) -> Result<T, ServiceError> {
+        match self {
+            Patch::Absent => Ok(current),
+            Patch::Null => Err(ServiceError::Validation(format!("{} cannot be null", field))),
+            Patch::Value(value) => Ok(value),
+        }
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Patch<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Option::<T>::deserialize(deserializer).map(|value| match value {
+            Some(value) => Patch::Value(value),
+            None => Patch::Null,
+        })
+    }
+}
+
+impl<T: Serialize> Serialize for Patch<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Patch::Value(value) => serializer.serialize_some(value),
+            _ => serializer.serialize_none(),
+        }
+    }
+}
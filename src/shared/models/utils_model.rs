@@ -1,10 +1,9 @@
-use std::collections::HashMap;
+use e_commerce_system_macros::FromSqlRow;
 use serde::{Deserialize, Serialize};
-use sqlx::{mysql::MySqlRow, Row, Error as SqlxError};
-use crate::shared::database::mysql::FromSqlRow;
+use utoipa::ToSchema;
 
 
-#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, FromSqlRow)]
 pub struct OptIdModel {
     pub id: Option<i64>,
 }
@@ -15,13 +14,7 @@ impl OptIdModel {
     }
 }
 
-impl FromSqlRow for OptIdModel {
-    fn map_row_to_entity(row: MySqlRow, index_map: &HashMap<String, usize>) -> Result<Self, SqlxError> {
-        Ok(OptIdModel { id: row.try_get(index_map["id"])? })
-    }
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, FromSqlRow)]
 pub struct IdModel {
     pub id: i64,
 }
@@ -32,13 +25,7 @@ impl IdModel {
     }
 }
 
-impl FromSqlRow for IdModel {
-    fn map_row_to_entity(row: MySqlRow, index_map: &HashMap<String, usize>) -> Result<Self, SqlxError> {
-        Ok(IdModel { id: row.try_get(index_map["id"])? })
-    }
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, ToSchema)]
 pub struct IdListModel {
     pub ids: Vec<i64>,
 }
@@ -50,7 +37,7 @@ impl IdListModel {
 }
 
 
-#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, FromSqlRow)]
 pub struct CountModel {
     pub count: Option<i64>,
 }
@@ -61,10 +48,4 @@ impl CountModel {
     }
 }
 
-impl FromSqlRow for CountModel {
-    fn map_row_to_entity(row: MySqlRow, index_map: &HashMap<String, usize>) -> Result<Self, SqlxError> {
-        Ok(CountModel { count: row.try_get(index_map["count"])? })
-    }
-}
-
 
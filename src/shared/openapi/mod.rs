@@ -1 +1 @@
-pub mod spec;
\ No newline at end of file
+pub mod spec;pub mod versions;
@@ -1,12 +1,18 @@
 
 use utoipa::{
+    Modify,
     OpenApi,
+    openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme},
 };
 
-use crate::services::user::controller::{user_controller, user_auth_controller, user_status_controller};
-use crate::services::user::dto::user_dto::{UserCreateRequest, UserUpdateRequest, UserResponse, SearchCountryRequest, SearchTitleRequest};
-use crate::services::user::dto::user_auth_dto::{UserAuthCreateRequest, UserAuthUpdateRequest, UserAuthResponse};
-use crate::services::user::dto::user_status_dto::{UserStatusCreateRequest, UserStatusUpdateRequest, UserStatusResponse};
+use crate::services::user::controller::{user_controller, user_v2_controller, manager_v2_controller, customer_v2_controller, user_auth_controller, user_status_controller, manager_controller, customer_controller};
+use crate::services::user::dto::user_dto::{ManagerCreateRequest, ManagerUserResponse, CustomerCreateRequest, CustomerUserResponse, UserCreateRequest, UserUpdateRequest, UserPatchRequest, UserResponse, UserUpdateStatusRequest, UserUpdateProfilePicUrlRequest, UserProfilePicUploadRequest, UserUpdatePasswordRequest, SearchCountryRequest, SearchTitleRequest};
+use crate::services::user::dto::user_v2_dto::{UserV2Response, ManagerUserV2Response, CustomerUserV2Response};
+use crate::services::user::dto::user_auth_dto::{UserAuthCreateRequest, UserAuthUpdateRequest, UserAuthPatchRequest, UserAuthResponse};
+use crate::shared::health::checks::{DependencyHealth, HealthReport, HealthStatus};
+use crate::shared::health::controller as health_controller;
+use crate::shared::models::utils_model::IdListModel;
+use crate::services::user::dto::user_status_dto::{UserStatusCreateRequest, UserStatusUpdateRequest, UserStatusPatchRequest, UserStatusResponse};
 
 #[derive(OpenApi)]
 #[openapi(
@@ -14,22 +20,65 @@ use crate::services::user::dto::user_status_dto::{UserStatusCreateRequest, UserS
     tags(
         (name = "User", description = "User API endpoints"),
         (name = "UserAuth", description = "User Auth API endpoints"),
-        (name = "UserStatus", description = "User Status API endpoints")
+        (name = "UserStatus", description = "User Status API endpoints"),
+        (name = "Manager", description = "Manager API endpoints"),
+        (name = "Customer", description = "Customer API endpoints"),
+        (name = "Health", description = "Liveness and readiness probes")
     ),
     paths(
-        user_controller::get_users, user_controller::post_user,
-        user_controller::get_user_by_id, user_controller::put_user, user_controller::delete_user,
+        user_controller::get_users, user_controller::post_user, user_controller::get_users_by_ids,
+        user_controller::get_user_by_id, user_controller::put_user, user_controller::patch_user, user_controller::delete_user,
+        user_controller::update_user_status, user_controller::update_user_profile_pic, user_controller::upload_user_profile_pic, user_controller::update_user_password, user_controller::restore_user,
         user_auth_controller::get_user_auths, user_auth_controller::post_user_auth,
-        user_auth_controller::get_user_auth_by_id, user_auth_controller::put_user_auth, user_auth_controller::delete_user_auth,
+        user_auth_controller::get_user_auth_by_id, user_auth_controller::put_user_auth, user_auth_controller::patch_user_auth, user_auth_controller::delete_user_auth,
         user_status_controller::get_user_statuses, user_status_controller::post_user_status,
-        user_status_controller::get_user_status_by_id, user_status_controller::put_user_status, user_status_controller::delete_user_status
+        user_status_controller::get_user_status_by_id, user_status_controller::put_user_status, user_status_controller::patch_user_status, user_status_controller::delete_user_status,
+        manager_controller::get_managers, manager_controller::post_manager, manager_controller::get_manager_by_id,
+        customer_controller::get_customers, customer_controller::post_customer, customer_controller::get_customer_by_id,
+        health_controller::liveness, health_controller::readiness
     ),
     components(
         schemas(
-            UserCreateRequest, UserUpdateRequest, UserResponse, SearchCountryRequest, SearchTitleRequest,
-            UserAuthCreateRequest, UserAuthUpdateRequest, UserAuthResponse,
-            UserStatusCreateRequest, UserStatusUpdateRequest, UserStatusResponse
+            UserCreateRequest, UserUpdateRequest, UserPatchRequest, UserResponse, SearchCountryRequest, SearchTitleRequest,
+            UserUpdateStatusRequest, UserUpdateProfilePicUrlRequest, UserProfilePicUploadRequest, UserUpdatePasswordRequest,
+            UserAuthCreateRequest, UserAuthUpdateRequest, UserAuthPatchRequest, UserAuthResponse,
+            UserStatusCreateRequest, UserStatusUpdateRequest, UserStatusPatchRequest, UserStatusResponse,
+            ManagerCreateRequest, ManagerUserResponse, CustomerCreateRequest, CustomerUserResponse,
+            IdListModel,
+            HealthReport, DependencyHealth, HealthStatus
         )
-    )
+    ),
+    modifiers(&BearerAuth)
 )]
 pub struct ApiDoc;
+
+/// What v2 changes over [`ApiDoc`], see [`crate::shared::openapi::versions`].
+#[derive(OpenApi)]
+#[openapi(
+    info(version = "2.0.0", title = "E-Commerce API", description = "E-Commerce API description"),
+    paths(
+        user_v2_controller::get_users, user_v2_controller::post_user, user_v2_controller::get_users_by_ids,
+        user_v2_controller::get_user_by_id, user_v2_controller::put_user, user_v2_controller::patch_user,
+        user_v2_controller::update_user_status, user_v2_controller::update_user_profile_pic, user_v2_controller::upload_user_profile_pic, user_v2_controller::restore_user,
+        manager_v2_controller::get_managers, manager_v2_controller::post_manager, manager_v2_controller::get_manager_by_id,
+        customer_v2_controller::get_customers, customer_v2_controller::post_customer, customer_v2_controller::get_customer_by_id
+    ),
+    components(
+        schemas(UserV2Response, ManagerUserV2Response, CustomerUserV2Response)
+    )
+)]
+pub struct ApiDocV2;
+
+/// Registers the `bearer_auth` JWT scheme referenced by protected endpoints.
+struct BearerAuth;
+
+impl Modify for BearerAuth {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        if let Some(components) = openapi.components.as_mut() {
+            components.add_security_scheme(
+                "bearer_auth",
+                SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Bearer).bearer_format("JWT").build()),
+            );
+        }
+    }
+}
@@ -0,0 +1,61 @@
+use utoipa::OpenApi;
+use utoipa::openapi::{self, Deprecated, PathItem};
+
+use crate::shared::http::versioning::api_prefix;
+use crate::shared::openapi::spec::{ApiDoc, ApiDocV2};
+
+/// OpenAPI document of `version`: the routes of [`ApiDoc`] under `/api/<version>`, overridden by
+/// what the version changes. Every API operation is marked deprecated when `deprecated` is set.
+pub fn api_doc(version: &str, deprecated: bool) -> openapi::OpenApi {
+    let mut api = ApiDoc::openapi();
+    let prefix = api_prefix(version);
+    api.paths.paths = std::mem::take(&mut api.paths.paths).into_iter()
+        .map(|(path, item)| match path.strip_prefix("/api/") {
+            Some(rest) => (format!("{}/{}", prefix, rest), item),
+            None => (path, item),
+        })
+        .collect();
+
+    if let Some(overrides) = overrides_of(version) {
+        apply_overrides(&mut api, overrides);
+    }
+
+    if deprecated {
+        for (_, item) in api.paths.paths.iter_mut().filter(|(path, _)| path.starts_with(&prefix)) {
+            for operation in operations_of(item).into_iter().flatten() {
+                operation.deprecated = Some(Deprecated::True);
+            }
+        }
+    }
+    api
+}
+
+fn overrides_of(version: &str) -> Option<openapi::OpenApi> {
+    match version {
+        "v2" => Some(ApiDocV2::openapi()),
+        _ => None,
+    }
+}
+
+/// Replaces the operations and schemas `overrides` documents, keeping the other operations of the same paths.
+fn apply_overrides(api: &mut openapi::OpenApi, overrides: openapi::OpenApi) {
+    api.info.version = overrides.info.version;
+    for (path, mut item) in overrides.paths.paths {
+        let base = api.paths.paths.entry(path).or_default();
+        for (slot, operation) in operations_of(base).into_iter().zip(operations_of(&mut item)) {
+            if operation.is_some() {
+                *slot = operation.take();
+            }
+        }
+    }
+    if let Some(components) = overrides.components {
+        api.components.get_or_insert_default().schemas.extend(components.schemas);
+    }
+}
+
+fn operations_of(item: &mut PathItem) -> [&mut Option<openapi::path::Operation>; 8] {
+    [
+        &mut item.get, &mut item.put, &mut item.post, &mut item.delete,
+        &mut item.options, &mut item.head, &mut item.patch, &mut item.trace,
+    ]
+}
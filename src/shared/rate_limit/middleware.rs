@@ -0,0 +1,73 @@
+use std::time::Duration;
+
+use axum::{
+    extract::{MatchedPath, Request, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use tracing::warn;
+
+use crate::shared::cache::backend::TokenBucket;
+use crate::shared::rate_limit::RateLimitPolicy;
+use crate::shared::state::AppState;
+
+
+/// Counts the request against its caller under the policy of its route, answers `429 Too Many Requests`
+/// once the bucket is empty and reports the quota in `RateLimit-*` headers.
+///
+/// Requests go through unlimited when Redis is not configured or failing: rate limiting must not
+/// take the API down with it.
+pub async fn rate_limit_middleware(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let limiter = &state.rate_limiter;
+    let cache = match &state.cache {
+        Some(cache) if limiter.is_enabled() => cache,
+        _ => return next.run(request).await,
+    };
+
+    let path = request.extensions().get::<MatchedPath>()
+        .map(|matched_path| matched_path.as_str().to_string())
+        .unwrap_or_else(|| request.uri().path().to_string());
+    let policy = limiter.policy_for(request.method(), &path);
+    let subject = limiter.subject_of(&request, &state.jwt_verifier);
+    let key = state.cache_keys.rate_limit(&policy.name, &subject);
+
+    let bucket = match cache.backend() {
+        Some(backend) => cache.record("rate limit", backend.take_token(&key, policy.limit, policy.window).await),
+        None => None,
+    };
+    let Some(bucket) = bucket else {
+        return next.run(request).await;
+    };
+
+    if !bucket.allowed {
+        warn!("[RATE LIMIT] {} exceeded the {} policy on {} {}", subject, policy.name, request.method(), path);
+        let mut response = StatusCode::TOO_MANY_REQUESTS.into_response();
+        insert_headers(response.headers_mut(), policy, &bucket);
+        response.headers_mut().insert(header::RETRY_AFTER, HeaderValue::from(seconds(bucket.retry_after)));
+        return response;
+    }
+
+    let mut response = next.run(request).await;
+    insert_headers(response.headers_mut(), policy, &bucket);
+    response
+}
+
+
+fn insert_headers(headers: &mut HeaderMap, policy: &RateLimitPolicy, bucket: &TokenBucket) {
+    headers.insert("ratelimit-limit", HeaderValue::from(policy.limit));
+    headers.insert("ratelimit-remaining", HeaderValue::from(bucket.remaining));
+    headers.insert("ratelimit-reset", HeaderValue::from(seconds(bucket.reset_after)));
+    if let Ok(value) = HeaderValue::from_str(&format!("{};w={}", policy.limit, policy.window.as_secs())) {
+        headers.insert("ratelimit-policy", value);
+    }
+}
+
+/// Whole seconds, rounded up so that a client waiting that long is not limited again.
+fn seconds(duration: Duration) -> u64 {
+    duration.as_millis().div_ceil(1000) as u64
+}
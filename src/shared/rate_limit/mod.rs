@@ -0,0 +1,103 @@
+pub mod middleware;
+
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use axum::extract::Request;
+use axum::http::Method;
+
+use crate::shared::configuration::{AppRateLimitConfig, AppRateLimitPolicyConfig};
+use crate::shared::http::versioning::unversioned_path;
+use crate::shared::security::api_key::ApiKeyStore;
+use crate::shared::security::caller::caller_of;
+use crate::shared::security::jwt::JwtVerifier;
+
+
+#[derive(Debug, Clone)]
+pub struct RateLimitPolicy {
+    pub name: String,
+    pub limit: u32,
+    pub window: Duration,
+}
+
+impl RateLimitPolicy {
+    fn from_config(name: &str, policy_config: &AppRateLimitPolicyConfig) -> Self {
+        Self {
+            name: name.to_string(),
+            limit: policy_config.limit,
+            window: Duration::from_secs(policy_config.window_seconds),
+        }
+    }
+}
+
+struct RateLimitRule {
+    path: Option<String>,
+    methods: Vec<Method>,
+    policy: RateLimitPolicy,
+}
+
+impl RateLimitRule {
+    fn matches(&self, method: &Method, path: &str) -> bool {
+        self.path.as_deref().is_none_or(|rule_path| rule_path == path)
+            && (self.methods.is_empty() || self.methods.contains(method))
+    }
+}
+
+
+/// Picks the policy and the counted caller of each request, built once from the configuration.
+pub struct RateLimiter {
+    enabled: bool,
+    trust_forwarded_for: bool,
+    api_keys: ApiKeyStore,
+    default_policy: RateLimitPolicy,
+    rules: Vec<RateLimitRule>,
+}
+
+impl RateLimiter {
+    pub fn from_config(rate_limit_config: &AppRateLimitConfig) -> Result<Self> {
+        let policy = |name: &str| {
+            rate_limit_config.policies.get(name)
+                .map(|policy_config| RateLimitPolicy::from_config(name, policy_config))
+                .ok_or_else(|| anyhow!("Unknown rate limit policy {}", name))
+        };
+
+        let mut rules = Vec::new();
+        for rule_config in &rate_limit_config.rules {
+            let methods = rule_config.methods.iter()
+                .map(|method| method.parse::<Method>())
+                .collect::<Result<Vec<_>, _>>()?;
+            rules.push(RateLimitRule {
+                path: rule_config.path.clone(),
+                methods,
+                policy: policy(&rule_config.policy)?,
+            });
+        }
+
+        Ok(Self {
+            enabled: rate_limit_config.enabled,
+            trust_forwarded_for: rate_limit_config.trust_forwarded_for,
+            api_keys: ApiKeyStore::from_hashes(&rate_limit_config.api_key_hashes)?,
+            default_policy: policy(&rate_limit_config.default_policy)?,
+            rules,
+        })
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Policy of the first rule matching the request, `path` being the matched route template.
+    /// Rules name unversioned paths and apply to the route in every API version.
+    pub fn policy_for(&self, method: &Method, path: &str) -> &RateLimitPolicy {
+        let path = unversioned_path(path);
+        self.rules.iter()
+            .find(|rule| rule.matches(method, &path))
+            .map(|rule| &rule.policy)
+            .unwrap_or(&self.default_policy)
+    }
+
+    /// Who the request is counted against, see [`caller_of`].
+    pub fn subject_of(&self, request: &Request, jwt_verifier: &JwtVerifier) -> String {
+        caller_of(request, &self.api_keys, jwt_verifier, self.trust_forwarded_for)
+    }
+}
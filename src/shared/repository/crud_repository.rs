@@ -1,41 +1,51 @@
 use async_trait::async_trait;
 use anyhow::{Error, Result};
-use sqlx::mysql::MySqlRow;
+use sqlx::mysql::{MySqlArguments, MySqlRow};
+use sqlx::query::Query;
+use sqlx::{MySql, Row};
 
-use crate::shared::database::mysql::{FromSqlRow, GenericRepository, MySqlParam};
+use crate::shared::database::mysql::{map_duplicate_key, FromSqlRow, GenericRepository, MySqlParam};
+use crate::shared::database::procedures::ProcedureCall;
 use crate::shared::logging::log::TimePrinter;
 
+
+fn procedure_call(procedure_name: &str, params_count: usize) -> String {
+    format!("CALL {}({})", procedure_name, vec!["?"; params_count].join(", "))
+}
+
+fn bind_params(query: &str, params: Vec<MySqlParam>) -> Query<'_, MySql, MySqlArguments> {
+    params.into_iter().fold(sqlx::query(query), |sql_query, param| param.bind(sql_query))
+}
+
+
 #[async_trait]
 pub trait CrudRepository<T>: GenericRepository<T>
 where
     T: FromSqlRow + Send + Sync,
 {
     // ============ CALL PROCEDURE OPERATIONS ============
+    // Procedures are called through the typed bindings of `shared::database::procedures`.
+    // Inside a unit of work every call runs in its transaction. Otherwise every call runs on the
+    // primary, except `call_procedure_for_optional_from_replica` and `call_procedure_for_list`
+    // which read from the replicas. Reads whose result is cached stay on the primary: after an
+    // invalidation, a lagging replica would put the old row back in the cache.
+    // Writes rejected by a unique index fail with `mysql::DuplicateKey`.
 
     async fn call_procedure(
         &self,
-        procedure_name: &str,
-        params: Vec<MySqlParam>
+        call: ProcedureCall,
     ) -> Result<(), Error> {
         let timer = TimePrinter::with_message(&format!(
             "[REPOSITORY] [CALL PROCEDURE] Procedure: {} ",
-            procedure_name
+            call.name()
         ));
 
-        let mut query = format!("CALL {}(", procedure_name);
-        let placeholders = vec!["?"; params.len()].join(", ");
-        query.push_str(&placeholders);
-        query.push(')');
-
-        let mut sql_query = sqlx::query(&query);
-        for param in params {
-            sql_query = param.bind(sql_query);
-        }
-
-        let entity_row_result = sql_query
-            .execute(self.get_pool())
-            .await
-            .map_err(|e| Error::msg(e.to_string()));
+        let query = procedure_call(call.name(), call.params_count());
+        let params = call.into_params();
+        let entity_row_result = match self.get_pools().unit_of_work() {
+            Some(unit_of_work) => unit_of_work.execute(bind_params(&query, params)).await,
+            None => bind_params(&query, params).execute(self.get_pool()).await.map_err(Error::from),
+        };
 
         match entity_row_result {
             Ok(_) => {
@@ -44,91 +54,127 @@ where
             },
             Err(e) => {
                 timer.error_with_message(format!("Failed to execute procedure: {}", e).as_str());
-                Err(Error::msg(e.to_string()))
+                Err(e)
+            }
+        }
+    }
+
+    /// Calls a write procedure ending with `select row_count() as affected_rows` on the primary
+    /// and returns the number of rows it changed.
+    async fn call_procedure_for_affected_rows(
+        &self,
+        call: ProcedureCall,
+    ) -> Result<u64, Error> {
+        let timer = TimePrinter::with_message(&format!(
+            "[REPOSITORY] [CALL PROCEDURE] [FOR AFFECTED ROWS] Procedure: {} ",
+            call.name()
+        ));
+
+        let query = procedure_call(call.name(), call.params_count());
+        let params = call.into_params();
+        let row_result = match self.get_pools().unit_of_work() {
+            Some(unit_of_work) => unit_of_work.fetch_one(bind_params(&query, params)).await,
+            None => bind_params(&query, params).fetch_one(self.get_pool()).await.map_err(Error::from),
+        };
+
+        match row_result.and_then(|row| row.try_get::<i64, _>("affected_rows").map_err(Error::from)) {
+            Ok(affected_rows) => {
+                timer.log();
+                Ok(affected_rows.max(0) as u64)
+            },
+            Err(e) => {
+                timer.error_with_message(format!("Failed to execute procedure: {}", e).as_str());
+                Err(e)
             }
         }
     }
     
     async fn call_procedure_for_optional(
         &self,
-        procedure_name: &str,
-        params: Vec<MySqlParam>,
+        call: ProcedureCall,
     ) -> Result<Option<T>, Error> {
         let timer = TimePrinter::with_message(&format!(
             "[REPOSITORY] [CALL PROCEDURE] [FOR OPTIONAL] Procedure: {} ",
-            procedure_name
+            call.name()
         ));
 
-        let mut query = format!("CALL {}(", procedure_name);
-        let placeholders = vec!["?"; params.len()].join(", ");
-        query.push_str(&placeholders);
-        query.push(')');
+        let query = procedure_call(call.name(), call.params_count());
+        let params = call.into_params();
+        let entity_row_result = match self.get_pools().unit_of_work() {
+            Some(unit_of_work) => unit_of_work.fetch_optional(bind_params(&query, params)).await,
+            None => bind_params(&query, params).fetch_optional(self.get_pool()).await.map_err(Error::from),
+        };
 
-        let mut sql_query = sqlx::query(&query);
-        for param in params {
-            sql_query = param.bind(sql_query);
-        }
+        self.parse_entity_from_option_result_sql(timer, entity_row_result)
+    }
+
+    /// Like `call_procedure_for_optional` but on a replica, for reads that tolerate replication lag
+    /// and are not cached.
+    async fn call_procedure_for_optional_from_replica(
+        &self,
+        call: ProcedureCall,
+    ) -> Result<Option<T>, Error> {
+        let timer = TimePrinter::with_message(&format!(
+            "[REPOSITORY] [CALL PROCEDURE] [FOR OPTIONAL] [REPLICA] Procedure: {} ",
+            call.name()
+        ));
 
-        let entity_row_result= sql_query
-            .fetch_optional(self.get_pool())
-            .await
-            .map_err(|e| Error::msg(e.to_string()));
+        let query = procedure_call(call.name(), call.params_count());
+        let params = call.into_params();
+        let entity_row_result = match self.get_pools().unit_of_work() {
+            Some(unit_of_work) => unit_of_work.fetch_optional(bind_params(&query, params)).await,
+            None => self.get_pools()
+                .read(|pool| {
+                    let (query, params) = (&query, params.clone());
+                    async move { bind_params(query, params).fetch_optional(&pool).await }
+                })
+                .await
+                .map_err(Error::from),
+        };
 
         self.parse_entity_from_option_result_sql(timer, entity_row_result)
     }
     
     async fn call_procedure_for_one(
         &self,
-        procedure_name: &str,
-        params: Vec<MySqlParam>,
+        call: ProcedureCall,
     ) -> Result<T, Error> {
         let timer = TimePrinter::with_message(&format!(
             "[REPOSITORY] [CALL PROCEDURE] [FOR ONE] Procedure: {} ",
-            procedure_name
+            call.name()
         ));
 
-        let mut query = format!("CALL {}(", procedure_name);
-        let placeholders = vec!["?"; params.len()].join(", ");
-        query.push_str(&placeholders);
-        query.push(')');
-
-        let mut sql_query = sqlx::query(&query);
-        for param in params {
-            sql_query = param.bind(sql_query);
-        }
+        let query = procedure_call(call.name(), call.params_count());
+        let params = call.into_params();
+        let entity_row_result = match self.get_pools().unit_of_work() {
+            Some(unit_of_work) => unit_of_work.fetch_one(bind_params(&query, params)).await,
+            None => bind_params(&query, params).fetch_one(self.get_pool()).await.map_err(Error::from),
+        };
 
-        let entity_row_result= sql_query
-            .fetch_one(self.get_pool())
-            .await
-            .map_err(|e| Error::msg(e.to_string()));
-
-        self.parse_entity_from_result_sql(timer, entity_row_result)
+        self.parse_entity_from_result_sql(timer, entity_row_result).map_err(map_duplicate_key)
     }
 
     async fn call_procedure_for_list(
         &self,
-        procedure_name: &str,
-        params: Vec<MySqlParam>,
+        call: ProcedureCall,
     ) -> Result<Vec<T>, Error> {
         let timer = TimePrinter::with_message(&format!(
             "[REPOSITORY] [CALL PROCEDURE] [FOR LIST] Procedure: {} ",
-            procedure_name
+            call.name()
         ));
 
-        let mut query = format!("CALL {}(", procedure_name);
-        let placeholders = vec!["?"; params.len()].join(", ");
-        query.push_str(&placeholders);
-        query.push(')');
-
-        let mut sql_query = sqlx::query(&query);
-        for param in params {
-            sql_query = param.bind(sql_query);
-        }
-
-        let entity_row_result: Result<Vec<MySqlRow>, Error> = sql_query
-            .fetch_all(self.get_pool())
-            .await
-            .map_err(|e| Error::msg(e.to_string()));
+        let query = procedure_call(call.name(), call.params_count());
+        let params = call.into_params();
+        let entity_row_result: Result<Vec<MySqlRow>, Error> = match self.get_pools().unit_of_work() {
+            Some(unit_of_work) => unit_of_work.fetch_all(bind_params(&query, params)).await,
+            None => self.get_pools()
+                .read(|pool| {
+                    let (query, params) = (&query, params.clone());
+                    async move { bind_params(query, params).fetch_all(&pool).await }
+                })
+                .await
+                .map_err(Error::from),
+        };
 
         self.parse_entity_from_result_sql_list(timer, entity_row_result)
     }
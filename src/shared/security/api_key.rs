@@ -0,0 +1,36 @@
+use std::collections::HashSet;
+
+use anyhow::{bail, Result};
+use sha2::{Digest, Sha256};
+
+
+/// API keys of the machine clients, known by their SHA-256 only so that the keys themselves are never stored.
+#[derive(Debug, Clone, Default)]
+pub struct ApiKeyStore {
+    hashes: HashSet<String>,
+}
+
+impl ApiKeyStore {
+    /// Builds the store from hex SHA-256 hashes, as printed by `printf %s "$KEY" | sha256sum`.
+    pub fn from_hashes(hashes: &[String]) -> Result<Self> {
+        let mut store = Self::default();
+        for hash in hashes {
+            let hash = hash.trim().to_ascii_lowercase();
+            if hash.len() != 64 || !hash.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+                bail!("{:?} is not the hex SHA-256 of an API key", hash);
+            }
+            store.hashes.insert(hash);
+        }
+        Ok(store)
+    }
+
+    pub fn hash(api_key: &str) -> String {
+        hex::encode(Sha256::digest(api_key.trim().as_bytes()))
+    }
+
+    /// Hash of `api_key` when it is a known key, `None` for any other value.
+    pub fn identify(&self, api_key: &str) -> Option<String> {
+        let hash = Self::hash(api_key);
+        self.hashes.contains(&hash).then_some(hash)
+    }
+}
@@ -0,0 +1,49 @@
+use axum::{
+    extract::FromRequestParts,
+    http::{header, request::Parts, StatusCode},
+};
+
+use crate::services::user::model::user_model::USER_AUTH_MANAGER;
+use crate::shared::state::AppState;
+
+
+/// Caller identified by the bearer token of the request.
+/// Extracting it answers `401 Unauthorized` when the token is missing or invalid.
+#[derive(Debug, Clone)]
+pub struct AuthenticatedUser {
+    pub id: i64,
+    pub auth: String,
+}
+
+impl AuthenticatedUser {
+    pub fn is_manager(&self) -> bool {
+        self.auth.eq_ignore_ascii_case(USER_AUTH_MANAGER)
+    }
+
+    pub fn is_self(&self, user_id: i64) -> bool {
+        self.id == user_id
+    }
+
+    /// Managers may act on any user, other callers only on themselves.
+    pub fn can_manage(&self, user_id: i64) -> bool {
+        self.is_self(user_id) || self.is_manager()
+    }
+}
+
+impl FromRequestParts<AppState> for AuthenticatedUser {
+    type Rejection = StatusCode;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        let token = parts
+            .headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .ok_or(StatusCode::UNAUTHORIZED)?;
+
+        let claims = state.jwt_verifier.verify(token.trim()).map_err(|_| StatusCode::UNAUTHORIZED)?;
+        let id = claims.sub.parse::<i64>().map_err(|_| StatusCode::UNAUTHORIZED)?;
+
+        Ok(Self { id, auth: claims.auth })
+    }
+}
@@ -0,0 +1,48 @@
+use std::net::SocketAddr;
+
+use axum::extract::{ConnectInfo, Request};
+use axum::http::header;
+
+use crate::shared::security::api_key::ApiKeyStore;
+use crate::shared::security::jwt::JwtVerifier;
+
+
+/// Header carrying the API key of machine clients.
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+
+/// Identifies the caller of `request`: its API key, else its authenticated user, else its client IP,
+/// as `key:<sha256>`, `user:<id>` or `ip:<address>`.
+///
+/// Only the keys of `api_keys` identify a caller, and invalid bearer tokens fall back to the IP: a made-up
+/// key or a forged token cannot buy a fresh bucket or pass for another user. `X-Forwarded-For` is only
+/// read when `trust_forwarded_for` is set.
+pub fn caller_of(request: &Request, api_keys: &ApiKeyStore, jwt_verifier: &JwtVerifier, trust_forwarded_for: bool) -> String {
+    let headers = request.headers();
+
+    let known_key = headers.get(API_KEY_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|api_key| api_keys.identify(api_key));
+    if let Some(hash) = known_key {
+        return format!("key:{}", hash);
+    }
+
+    let user_id = headers.get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .and_then(|token| jwt_verifier.verify(token.trim()).ok())
+        .map(|claims| claims.sub);
+    if let Some(user_id) = user_id {
+        return format!("user:{}", user_id);
+    }
+
+    let forwarded_for = trust_forwarded_for
+        .then(|| headers.get("x-forwarded-for").and_then(|value| value.to_str().ok()))
+        .flatten()
+        .and_then(|value| value.split(',').next())
+        .map(|ip| ip.trim().to_string());
+    let ip = forwarded_for.or_else(|| {
+        request.extensions().get::<ConnectInfo<SocketAddr>>().map(|ConnectInfo(addr)| addr.ip().to_string())
+    });
+    format!("ip:{}", ip.as_deref().unwrap_or("unknown"))
+}
@@ -0,0 +1,48 @@
+use anyhow::{Context, Result};
+use jsonwebtoken::{decode, Algorithm, DecodingKey, Validation};
+use serde::{Deserialize, Serialize};
+
+use crate::shared::configuration::AppConfigJWT;
+
+
+/// Claims carried by the access tokens issued by the authentication service.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Claims {
+    /// Id of the authenticated user
+    pub sub: String,
+    /// Name of the user authentication level: user, customer, manager
+    pub auth: String,
+    pub iss: String,
+    pub aud: String,
+    pub exp: i64,
+    pub iat: Option<i64>,
+}
+
+
+/// Verifies bearer tokens against the issuer public key.
+pub struct JwtVerifier {
+    decoding_key: DecodingKey,
+    validation: Validation,
+}
+
+impl JwtVerifier {
+    pub fn new(decoding_key: DecodingKey, algorithm: Algorithm, issuer: &str, audience: &str) -> Self {
+        let mut validation = Validation::new(algorithm);
+        validation.set_issuer(&[issuer]);
+        validation.set_audience(&[audience]);
+        Self { decoding_key, validation }
+    }
+
+    /// Builds an RS256 verifier from the public key referenced by the configuration.
+    pub fn from_config(jwt_config: &AppConfigJWT) -> Result<Self> {
+        let pem = std::fs::read(&jwt_config.public_secret_pem_path)
+            .with_context(|| format!("Cannot read JWT public key {}", jwt_config.public_secret_pem_path))?;
+        let decoding_key = DecodingKey::from_rsa_pem(&pem)?;
+        Ok(Self::new(decoding_key, Algorithm::RS256, &jwt_config.issuer, &jwt_config.audience))
+    }
+
+    pub fn verify(&self, token: &str) -> Result<Claims> {
+        let token_data = decode::<Claims>(token, &self.decoding_key, &self.validation)?;
+        Ok(token_data.claims)
+    }
+}
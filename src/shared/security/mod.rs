@@ -0,0 +1,5 @@
+pub mod jwt;
+pub mod password;
+pub mod authenticated_user;
+pub mod caller;
+pub mod api_key;
@@ -0,0 +1,39 @@
+use anyhow::{Error, Result};
+use argon2::{
+    password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
+    Argon2,
+};
+
+
+/// Hashes a password into a PHC string suitable for the `user.password` column.
+pub fn hash_password(password: &str) -> Result<String> {
+    let salt = SaltString::generate(&mut OsRng);
+    Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|e| Error::msg(format!("Failed to hash password: {}", e)))
+}
+
+/// Checks a password against the stored `user.password`.
+///
+/// Rows written before passwords were hashed still hold the plain text, which is anything
+/// that is not a PHC string (those start with `$`): they are compared as is until the
+/// password is next changed. An empty stored value never matches.
+pub fn verify_password(password: &str, password_hash: &str) -> bool {
+    if !is_password_hash(password_hash) {
+        return !password_hash.is_empty() && constant_time_eq(password.as_bytes(), password_hash.as_bytes());
+    }
+    match PasswordHash::new(password_hash) {
+        Ok(parsed_hash) => Argon2::default().verify_password(password.as_bytes(), &parsed_hash).is_ok(),
+        Err(_) => false,
+    }
+}
+
+/// Whether a stored password is a PHC string rather than a legacy plain text one.
+pub fn is_password_hash(stored_password: &str) -> bool {
+    stored_password.starts_with('$')
+}
+
+fn constant_time_eq(left: &[u8], right: &[u8]) -> bool {
+    left.len() == right.len() && left.iter().zip(right).fold(0u8, |diff, (l, r)| diff | (l ^ r)) == 0
+}
@@ -0,0 +1,86 @@
+use std::sync::Arc;
+
+use tokio::sync::watch;
+use tracing::{error, info};
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShutdownPhase {
+    Serving,
+    /// Still serving, but reported as not ready so that load balancers stop routing new requests here
+    Draining,
+    /// No new connection is accepted, in-flight requests are finishing
+    Stopping,
+}
+
+/// Shutdown progress of the instance, shared by the server, the readiness probe and the background workers.
+#[derive(Clone)]
+pub struct Shutdown {
+    phase: Arc<watch::Sender<ShutdownPhase>>,
+}
+
+impl Default for Shutdown {
+    fn default() -> Self {
+        Self { phase: Arc::new(watch::Sender::new(ShutdownPhase::Serving)) }
+    }
+}
+
+impl Shutdown {
+    pub fn phase(&self) -> ShutdownPhase {
+        *self.phase.borrow()
+    }
+
+    pub fn is_draining(&self) -> bool {
+        self.phase() != ShutdownPhase::Serving
+    }
+
+    pub fn start_draining(&self) {
+        self.phase.send_if_modified(|phase| {
+            let serving = *phase == ShutdownPhase::Serving;
+            if serving {
+                *phase = ShutdownPhase::Draining;
+            }
+            serving
+        });
+    }
+
+    pub fn stop(&self) {
+        self.phase.send_replace(ShutdownPhase::Stopping);
+    }
+
+    /// Resolves once `stop` has been called.
+    pub async fn stopped(&self) {
+        let mut phase = self.phase.subscribe();
+        // The sender lives as long as `self`, so waiting cannot fail
+        let _ = phase.wait_for(|phase| *phase == ShutdownPhase::Stopping).await;
+    }
+}
+
+
+/// Resolves on the first SIGINT (Ctrl+C) or SIGTERM received by the process.
+pub async fn signal() {
+    let interrupt = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            error!("[SHUTDOWN] Failed to listen for SIGINT: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut terminate) => { terminate.recv().await; },
+            Err(e) => {
+                error!("[SHUTDOWN] Failed to listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            },
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = interrupt => info!("[SHUTDOWN] SIGINT received"),
+        _ = terminate => info!("[SHUTDOWN] SIGTERM received"),
+    }
+}
@@ -1,33 +1,116 @@
-use anyhow::Result;
+pub mod services;
+
+use std::sync::Arc;
+use std::time::Duration;
+use anyhow::{Context, Result};
 use bb8::Pool;
 use bb8_redis::RedisConnectionManager;
-use sqlx::MySqlPool;
+use tracing::warn;
+use crate::shared::cache::key::CacheKeys;
+use crate::shared::cache::local::{spawn_invalidation_listener, LocalCache, DEFAULT_LOCAL_CAPACITY, DEFAULT_LOCAL_TTL};
+use crate::shared::cache::store::CacheStore;
 use crate::shared::configuration::AppConfig;
 use crate::shared::database::mysql as my_mysql;
+use crate::shared::database::mysql_pools::MySqlPools;
 use crate::shared::database::redis as my_redis;
+use crate::shared::health::checks::HealthChecks;
+use crate::shared::rate_limit::RateLimiter;
+use crate::shared::shutdown::Shutdown;
+use crate::shared::metrics::pool::spawn_mysql_pool_reporter;
+use crate::shared::security::jwt::JwtVerifier;
+use crate::shared::state::services::{Repositories, Services};
+use crate::shared::storage;
+use crate::shared::storage::blob_store::BlobStore;
 // use crate::shared::metrics::prometheus::Metrics;
 
 #[derive(Clone)]
 pub struct AppState {
     pub config: AppConfig,
-    pub mysql_pool: MySqlPool,
-    pub redis_pool: Pool<RedisConnectionManager>,
+    pub mysql_pools: MySqlPools,
+    /// `None` when Redis is not configured, the services then always hit the database
+    pub redis_pool: Option<Pool<RedisConnectionManager>>,
+    pub cache: Option<CacheStore>,
+    pub cache_keys: CacheKeys,
+    pub jwt_verifier: Arc<JwtVerifier>,
+    pub blob_store: Arc<dyn BlobStore>,
+    pub repositories: Repositories,
+    pub services: Services,
+    pub health_checks: Arc<HealthChecks>,
+    pub shutdown: Shutdown,
+    pub rate_limiter: Arc<RateLimiter>,
     // pub metrics: Metrics,
 }
 
 impl AppState {
     pub async fn new(config: AppConfig) -> Result<Self> {
         let config_clone = config.clone();
+        let shutdown = Shutdown::default();
         
-        let mysql_pool = my_mysql::connect(&config_clone.database.mysql.unwrap()).await?;
-        let redis_pool = my_redis::connect(&config_clone.database.redis.unwrap()).await?;
+        let mysql_config = config_clone.database.mysql.context("database.mysql is not configured")?;
+        let mysql_primary = my_mysql::connect(&mysql_config).await?;
+        let mysql_replicas = my_mysql::connect_replicas(&mysql_config)?;
+        spawn_mysql_pool_reporter(mysql_primary.clone(), "mysql".to_string());
+        for (index, replica) in mysql_replicas.iter().enumerate() {
+            spawn_mysql_pool_reporter(replica.clone(), format!("mysql_replica_{}", index));
+        }
+        let mysql_pools = MySqlPools::new(mysql_primary, mysql_replicas);
+        let (redis_pool, cache_keys) = match &config_clone.database.redis {
+            Some(redis_config) => (Some(my_redis::connect(redis_config).await?), CacheKeys::from_config(redis_config)),
+            None => {
+                warn!("database.redis is not configured, caching is disabled");
+                (None, CacheKeys::default())
+            },
+        };
+        let cache = match (&redis_pool, &config_clone.database.redis) {
+            (Some(redis_pool), Some(redis_config)) => {
+                let local = Arc::new(LocalCache::new(
+                    redis_config.local_cache_capacity.unwrap_or(DEFAULT_LOCAL_CAPACITY),
+                    redis_config.local_cache_ttl.map(Duration::from_secs).unwrap_or(DEFAULT_LOCAL_TTL),
+                ));
+                let channel = cache_keys.local_invalidation_channel();
+                spawn_invalidation_listener(local.clone(), redis_config.uri.clone(), channel.clone(), shutdown.clone());
+                Some(CacheStore::new(redis_pool.clone()).with_local_cache(local, channel))
+            },
+            _ => None,
+        };
+        let rate_limiter = Arc::new(RateLimiter::from_config(&config_clone.rate_limit)?);
+        let jwt_verifier = Arc::new(JwtVerifier::from_config(&config_clone.jwt)?);
+        let blob_store = storage::from_config(&config_clone.storage)?;
+        let repositories = Repositories::mysql(&mysql_pools);
+        let services = Services::new(
+            &repositories,
+            cache.clone(),
+            cache_keys.clone(),
+            blob_store.clone(),
+            config_clone.storage.max_upload_bytes
+        );
+        let health_checks = HealthChecks::new(mysql_pools.primary().clone(), cache.clone())
+            .with_config(&config.database)
+            .await?;
         // let metrics = Metrics::new();
 
         Ok(Self {
             config,
-            mysql_pool,
+            mysql_pools,
             redis_pool,
+            cache,
+            cache_keys,
+            jwt_verifier,
+            blob_store,
+            repositories,
+            services,
+            health_checks: Arc::new(health_checks),
+            shutdown,
+            rate_limiter,
             // metrics,
         })
     }
+
+    /// Closes the database pools and clients, once the server has stopped.
+    ///
+    /// The Redis pool has no explicit close, its connections are dropped with the last clone of the state.
+    pub async fn close(&self) {
+        self.mysql_pools.close().await;
+        self.health_checks.close().await;
+    }
 }
@@ -0,0 +1,75 @@
+use std::sync::Arc;
+use crate::services::user::repository::user_auth_repo::{UserAuthRepository, UserAuthRepositoryInterface};
+use crate::services::user::repository::user_repo::{UserRepository, UserRepositoryInterface};
+use crate::services::user::repository::user_status_repo::{UserStatusRepository, UserStatusRepositoryInterface};
+use crate::services::user::service::user_auth_service::{UserAuthService, UserAuthServiceInterface};
+use crate::services::user::service::user_role_service::{Customer, Manager, UserRoleService, UserRoleServiceInterface};
+use crate::services::user::service::user_service::{UserService, UserServiceInterface};
+use crate::services::user::service::user_status_service::{UserStatusService, UserStatusServiceInterface};
+use crate::shared::cache::key::CacheKeys;
+use crate::shared::cache::store::CacheStore;
+use crate::shared::database::mysql_pools::MySqlPools;
+use crate::shared::storage::blob_store::BlobStore;
+
+
+/// Repositories shared by every service, built once at startup.
+#[derive(Clone)]
+pub struct Repositories {
+    pub user: Arc<dyn UserRepositoryInterface>,
+    pub user_auth: Arc<dyn UserAuthRepositoryInterface>,
+    pub user_status: Arc<dyn UserStatusRepositoryInterface>,
+}
+
+impl Repositories {
+    /// Repositories calling the stored procedures of the MySQL database.
+    pub fn mysql(pools: &MySqlPools) -> Self {
+        Self {
+            user: Arc::new(UserRepository::new(pools.clone())),
+            user_auth: Arc::new(UserAuthRepository::new(pools.clone())),
+            user_status: Arc::new(UserStatusRepository::new(pools.clone())),
+        }
+    }
+}
+
+
+/// Services the controllers call, built once at startup on top of `Repositories`.
+#[derive(Clone)]
+pub struct Services {
+    pub user: Arc<dyn UserServiceInterface>,
+    pub user_auth: Arc<dyn UserAuthServiceInterface>,
+    pub user_status: Arc<dyn UserStatusServiceInterface>,
+    pub manager: Arc<dyn UserRoleServiceInterface<Manager>>,
+    pub customer: Arc<dyn UserRoleServiceInterface<Customer>>,
+}
+
+impl Services {
+    pub fn new(
+        repositories: &Repositories,
+        cache: Option<CacheStore>,
+        cache_keys: CacheKeys,
+        blob_store: Arc<dyn BlobStore>,
+        max_upload_bytes: usize
+    ) -> Self {
+        let Repositories { user, user_auth, user_status } = repositories.clone();
+        Self {
+            user: Arc::new(UserService::new(
+                user.clone(),
+                user_status.clone(),
+                cache.clone(),
+                cache_keys.clone(),
+                blob_store,
+                max_upload_bytes
+            )),
+            user_auth: Arc::new(UserAuthService::new(user_auth.clone(), cache.clone(), cache_keys.clone())),
+            user_status: Arc::new(UserStatusService::new(user_status.clone(), cache.clone(), cache_keys.clone())),
+            manager: Arc::new(UserRoleService::new(
+                user.clone(),
+                user_auth.clone(),
+                user_status.clone(),
+                cache.clone(),
+                cache_keys.clone()
+            )),
+            customer: Arc::new(UserRoleService::new(user, user_auth, user_status, cache, cache_keys)),
+        }
+    }
+}
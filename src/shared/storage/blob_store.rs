@@ -0,0 +1,52 @@
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+
+
+/// Content of a stored object.
+#[derive(Debug, Clone)]
+pub struct Blob {
+    pub bytes: Vec<u8>,
+    pub content_type: Option<String>,
+}
+
+
+/// Binary object storage addressed by slash separated keys, e.g. `users/1/profile/<uuid>.png`.
+#[async_trait]
+pub trait BlobStore: Send + Sync {
+    async fn put(&self, key: &str, bytes: Vec<u8>, content_type: &str) -> Result<()>;
+
+    async fn get(&self, key: &str) -> Result<Option<Blob>>;
+
+    async fn delete(&self, key: &str) -> Result<()>;
+
+    /// URL under which clients can download the object.
+    fn public_url(&self, key: &str) -> String;
+
+    /// Key of the object served at `url`, `None` for URLs outside of the store.
+    fn key_of(&self, url: &str) -> Option<String>;
+}
+
+
+/// Rejects keys that could escape the store root or produce ambiguous paths.
+pub fn validate_key(key: &str) -> Result<()> {
+    let valid = !key.is_empty()
+        && !key.starts_with('/')
+        && key.split('/').all(|segment| !segment.is_empty() && segment != "." && segment != "..")
+        && key.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '/' | '-' | '_' | '.'));
+
+    if !valid {
+        bail!("Invalid blob key: {}", key);
+    }
+    Ok(())
+}
+
+/// Inverse of `join_url`, the key must still pass `validate_key`.
+pub fn strip_url(base_url: &str, url: &str) -> Option<String> {
+    let key = url.strip_prefix(base_url.trim_end_matches('/'))?.strip_prefix('/')?;
+    validate_key(key).ok().map(|_| key.to_string())
+}
+
+/// Joins the configured public base URL and an object key.
+pub fn join_url(base_url: &str, key: &str) -> String {
+    format!("{}/{}", base_url.trim_end_matches('/'), key)
+}
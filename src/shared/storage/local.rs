@@ -0,0 +1,84 @@
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use tokio::fs;
+
+use crate::shared::storage::blob_store::{join_url, strip_url, validate_key, Blob, BlobStore};
+
+
+/// Stores objects as plain files below a root directory.
+pub struct LocalBlobStore {
+    root: PathBuf,
+    public_base_url: String,
+}
+
+impl LocalBlobStore {
+    pub fn new(root: impl AsRef<Path>, public_base_url: &str) -> Self {
+        Self {
+            root: root.as_ref().to_path_buf(),
+            public_base_url: public_base_url.to_string(),
+        }
+    }
+
+    fn path_of(&self, key: &str) -> Result<PathBuf> {
+        validate_key(key)?;
+        Ok(self.root.join(key))
+    }
+}
+
+#[async_trait]
+impl BlobStore for LocalBlobStore {
+    async fn put(&self, key: &str, bytes: Vec<u8>, _content_type: &str) -> Result<()> {
+        let path = self.path_of(key)?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).await?;
+        }
+
+        // write next to the target and rename, readers never see a partial file
+        let tmp_path = path.with_extension("part");
+        fs::write(&tmp_path, bytes).await?;
+        fs::rename(&tmp_path, &path).await?;
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Blob>> {
+        let path = self.path_of(key)?;
+        match fs::read(&path).await {
+            Ok(bytes) => Ok(Some(Blob {
+                bytes,
+                content_type: content_type_of(&path).map(str::to_string),
+            })),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        let path = self.path_of(key)?;
+        match fs::remove_file(&path).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn public_url(&self, key: &str) -> String {
+        join_url(&self.public_base_url, key)
+    }
+
+    fn key_of(&self, url: &str) -> Option<String> {
+        strip_url(&self.public_base_url, url)
+    }
+}
+
+fn content_type_of(path: &Path) -> Option<&'static str> {
+    match path.extension()?.to_str()? {
+        "png" => Some("image/png"),
+        "jpg" | "jpeg" => Some("image/jpeg"),
+        "gif" => Some("image/gif"),
+        "webp" => Some("image/webp"),
+        _ => None,
+    }
+}
@@ -0,0 +1,28 @@
+pub mod blob_store;
+pub mod local;
+pub mod s3;
+
+use std::sync::Arc;
+
+use anyhow::{bail, Context, Result};
+
+use crate::shared::configuration::AppStorageConfig;
+use crate::shared::storage::blob_store::BlobStore;
+use crate::shared::storage::local::LocalBlobStore;
+use crate::shared::storage::s3::S3BlobStore;
+
+
+/// Builds the blob store selected by `storage.backend`.
+pub fn from_config(storage_config: &AppStorageConfig) -> Result<Arc<dyn BlobStore>> {
+    match storage_config.backend.as_str() {
+        "local" => Ok(Arc::new(LocalBlobStore::new(
+            storage_config.local_path.as_str(),
+            storage_config.public_base_url.as_str(),
+        ))),
+        "s3" => {
+            let s3_config = storage_config.s3.as_ref().context("Missing S3 storage configuration")?;
+            Ok(Arc::new(S3BlobStore::new(s3_config, storage_config.public_base_url.as_str())))
+        },
+        backend => bail!("Unknown storage backend: {}", backend),
+    }
+}
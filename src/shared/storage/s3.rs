@@ -0,0 +1,139 @@
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use reqwest::{header, Client, Method, StatusCode, Url};
+use sha2::{Digest, Sha256};
+
+use crate::shared::configuration::AppStorageS3Config;
+use crate::shared::storage::blob_store::{join_url, strip_url, validate_key, Blob, BlobStore};
+
+
+type HmacSha256 = Hmac<Sha256>;
+
+
+/// Stores objects in an S3 compatible bucket (AWS S3, MinIO, ...) using path-style
+/// requests signed with AWS Signature Version 4.
+pub struct S3BlobStore {
+    client: Client,
+    endpoint: String,
+    bucket: String,
+    region: String,
+    access_key: String,
+    secret_key: String,
+    public_base_url: String,
+}
+
+impl S3BlobStore {
+    pub fn new(s3_config: &AppStorageS3Config, public_base_url: &str) -> Self {
+        Self {
+            client: Client::new(),
+            endpoint: s3_config.endpoint.trim_end_matches('/').to_string(),
+            bucket: s3_config.bucket.clone(),
+            region: s3_config.region.clone(),
+            access_key: s3_config.access_key.clone(),
+            secret_key: s3_config.secret_key.clone(),
+            public_base_url: public_base_url.to_string(),
+        }
+    }
+
+    /// Sends a signed request for `key`, the key charset is restricted by `validate_key`
+    /// so the path needs no further URI encoding.
+    async fn send(&self, method: Method, key: &str, body: Vec<u8>, content_type: Option<&str>) -> Result<reqwest::Response> {
+        validate_key(key)?;
+
+        let path = format!("/{}/{}", self.bucket, key);
+        let url = Url::parse(&format!("{}{}", self.endpoint, path))?;
+        let host = match (url.host_str(), url.port()) {
+            (Some(host), Some(port)) => format!("{}:{}", host, port),
+            (Some(host), None) => host.to_string(),
+            _ => bail!("Invalid S3 endpoint: {}", self.endpoint),
+        };
+
+        let now = Utc::now();
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let date = now.format("%Y%m%d").to_string();
+        let payload_hash = hex::encode(Sha256::digest(&body));
+
+        let canonical_request = format!(
+            "{}\n{}\n\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\nhost;x-amz-content-sha256;x-amz-date\n{}",
+            method.as_str(), path, host, payload_hash, amz_date, payload_hash
+        );
+        let scope = format!("{}/{}/s3/aws4_request", date, self.region);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date, scope, hex::encode(Sha256::digest(canonical_request.as_bytes()))
+        );
+
+        let signing_key = [date.as_str(), self.region.as_str(), "s3", "aws4_request"]
+            .iter()
+            .try_fold(format!("AWS4{}", self.secret_key).into_bytes(), |key, part| hmac_sha256(&key, part.as_bytes()))?;
+        let signature = hex::encode(hmac_sha256(&signing_key, string_to_sign.as_bytes())?);
+
+        let authorization = format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders=host;x-amz-content-sha256;x-amz-date, Signature={}",
+            self.access_key, scope, signature
+        );
+
+        let mut request = self.client
+            .request(method, url)
+            .header("x-amz-date", amz_date)
+            .header("x-amz-content-sha256", payload_hash)
+            .header(header::AUTHORIZATION, authorization);
+        if let Some(content_type) = content_type {
+            request = request.header(header::CONTENT_TYPE, content_type);
+        }
+
+        request.body(body).send().await.context("S3 request failed")
+    }
+}
+
+#[async_trait]
+impl BlobStore for S3BlobStore {
+    async fn put(&self, key: &str, bytes: Vec<u8>, content_type: &str) -> Result<()> {
+        let response = self.send(Method::PUT, key, bytes, Some(content_type)).await?;
+        if !response.status().is_success() {
+            bail!("S3 PUT {} failed with status {}", key, response.status());
+        }
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Blob>> {
+        let response = self.send(Method::GET, key, Vec::new(), None).await?;
+        match response.status() {
+            StatusCode::NOT_FOUND => Ok(None),
+            status if status.is_success() => {
+                let content_type = response.headers()
+                    .get(header::CONTENT_TYPE)
+                    .and_then(|value| value.to_str().ok())
+                    .map(str::to_string);
+                let bytes = response.bytes().await?.to_vec();
+                Ok(Some(Blob { bytes, content_type }))
+            },
+            status => bail!("S3 GET {} failed with status {}", key, status),
+        }
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        let response = self.send(Method::DELETE, key, Vec::new(), None).await?;
+        // S3 answers 204 for missing objects too
+        if !response.status().is_success() && response.status() != StatusCode::NOT_FOUND {
+            bail!("S3 DELETE {} failed with status {}", key, response.status());
+        }
+        Ok(())
+    }
+
+    fn public_url(&self, key: &str) -> String {
+        join_url(&self.public_base_url, key)
+    }
+
+    fn key_of(&self, url: &str) -> Option<String> {
+        strip_url(&self.public_base_url, url)
+    }
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Result<Vec<u8>> {
+    let mut mac = HmacSha256::new_from_slice(key)?;
+    mac.update(data);
+    Ok(mac.finalize().into_bytes().to_vec())
+}
@@ -0,0 +1,72 @@
+use std::time::Duration;
+
+use e_commerce_system::shared::cache::circuit_breaker::CircuitBreaker;
+
+
+const COOL_DOWN: Duration = Duration::from_millis(50);
+
+fn opened() -> CircuitBreaker {
+    let breaker = CircuitBreaker::new("test", 2, COOL_DOWN);
+    breaker.record_failure();
+    assert!(breaker.allow());
+    breaker.record_failure();
+    breaker
+}
+
+async fn cool_down() {
+    tokio::time::sleep(COOL_DOWN + Duration::from_millis(20)).await;
+}
+
+#[tokio::test]
+async fn circuit_opens_after_consecutive_failures() {
+    let breaker = CircuitBreaker::new("test", 2, COOL_DOWN);
+    breaker.record_failure();
+    breaker.record_success();
+    breaker.record_failure();
+    assert!(breaker.allow());
+
+    breaker.record_failure();
+    assert!(breaker.is_open());
+    assert!(!breaker.allow());
+}
+
+#[tokio::test]
+async fn single_probe_is_let_through_once_cooled_down() {
+    let breaker = opened();
+    cool_down().await;
+
+    assert!(breaker.allow());
+    // everybody else waits for the probe
+    assert!(!breaker.allow());
+    assert!(!breaker.allow());
+    assert!(breaker.is_open());
+
+    breaker.record_success();
+    assert!(!breaker.is_open());
+    assert!(breaker.allow());
+    assert!(breaker.allow());
+}
+
+#[tokio::test]
+async fn failed_probe_opens_the_circuit_again() {
+    let breaker = opened();
+    cool_down().await;
+
+    assert!(breaker.allow());
+    breaker.record_failure();
+    assert!(!breaker.allow());
+
+    cool_down().await;
+    assert!(breaker.allow());
+}
+
+#[tokio::test]
+async fn probe_that_never_reports_is_replaced() {
+    let breaker = opened();
+    cool_down().await;
+    assert!(breaker.allow());
+
+    cool_down().await;
+    assert!(breaker.allow());
+    assert!(!breaker.allow());
+}
@@ -0,0 +1,163 @@
+use std::path::{Path, PathBuf};
+
+use e_commerce_system::shared::configuration::AppConfig;
+use e_commerce_system::shared::configuration::cli::{CliOptions, ConfigSources};
+use e_commerce_system::shared::configuration::legacy::legacy_env_keys;
+
+
+/// Config directory holding a single `<environment>.toml` file.
+fn config_dir(environment: &str, content: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("e-commerce-system-config-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join(format!("{}.toml", environment)), content).unwrap();
+    dir
+}
+
+fn sources(dir: &Path, environment: &str, overrides: &[(&str, &str)]) -> ConfigSources {
+    ConfigSources {
+        config_dir: dir.display().to_string(),
+        environment: Some(environment.to_string()),
+        overrides: overrides.iter().map(|(key, value)| (key.to_string(), value.to_string())).collect(),
+    }
+}
+
+
+#[test]
+fn layers_override_each_other_in_order() {
+    let dir = config_dir("staging", r#"
+        log_level = "warn"
+
+        [database.mysql]
+        uri = "mysql://db:3306"
+        replica_uris = ["mysql://replica:3306"]
+
+        [database.redis]
+        uri = "redis://cache:6379"
+        default_ttl = 60
+    "#);
+
+    let config = AppConfig::load(&sources(&dir, "staging", &[("database.redis.default_ttl", "600")])).unwrap();
+
+    assert_eq!(config.environment, "staging");
+    assert!(!config.is_prod());
+    // default.toml
+    assert_eq!(config.storage.backend, "local");
+    assert_eq!(config.shutdown.drain_timeout_seconds, 30);
+    // staging.toml
+    assert_eq!(config.log_level, "warn");
+    assert_eq!(config.database.mysql.unwrap().replica_uris, vec!["mysql://replica:3306".to_string()]);
+    // --set
+    assert_eq!(config.database.redis.unwrap().default_ttl, Some(600));
+    assert!(config.database.mongo.is_none());
+}
+
+#[test]
+fn validation_reports_every_problem_at_once() {
+    let dir = config_dir("broken", r#"
+        bind_addr = "localhost"
+
+        [database.redis]
+        uri = "localhost:6379"
+
+        [storage]
+        backend = "s3"
+
+        [rate_limit]
+        api_key_hashes = ["plain-key"]
+    "#);
+
+    let error = AppConfig::load(&sources(&dir, "broken", &[])).unwrap_err().to_string();
+
+    assert!(error.contains("bind_addr"), "{}", error);
+    assert!(error.contains("database.mysql: is required"), "{}", error);
+    assert!(error.contains("database.redis.uri"), "{}", error);
+    assert!(error.contains("storage.s3: is required"), "{}", error);
+    assert!(error.contains("rate_limit.api_key_hashes[0]"), "{}", error);
+}
+
+#[test]
+fn redacted_config_masks_secrets() {
+    let dir = config_dir("production", r#"
+        [database.mysql]
+        uri = "mysql://app:hunter2@db:3306/shop"
+        password = "hunter2"
+
+        [database.neo4j]
+        uri = "bolt://graph:7687"
+        username = "neo4j"
+        password = "hunter2"
+        encrypted = true
+    "#);
+
+    let config = AppConfig::load(&sources(&dir, "production", &[])).unwrap();
+    assert!(config.is_prod());
+
+    let printed = serde_json::to_string(&config.redacted()).unwrap();
+    assert!(!printed.contains("hunter2"), "{}", printed);
+    assert!(printed.contains("mysql://app:***@db:3306/shop"), "{}", printed);
+    // the loaded configuration keeps the real values
+    assert_eq!(config.database.mysql.unwrap().password.as_deref(), Some("hunter2"));
+}
+
+fn vars(vars: &[(&str, &str)]) -> Vec<(String, String)> {
+    vars.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect()
+}
+
+#[test]
+fn legacy_variables_set_their_keys() {
+    let keys = legacy_env_keys(vars(&[
+        ("MYSQL_URL", "mysql://db:3306"),
+        ("MYSQL_HOST", "ignored"),
+        ("MYSQL_USERNAME", "app"),
+        ("MYSQL_POOL_MAX_CONNECTIONS", " 20 "),
+        ("MYSQL_REPLICA_URLS", "mysql://replica-1:3306,mysql://replica-2:3306"),
+        ("JWT_ISSUER", "shop-auth"),
+        ("REDIS_URL", "redis://cache:6379"),
+        ("HOME", "/root"),
+    ]));
+
+    assert_eq!(keys.into_iter().collect::<Vec<_>>(), vars(&[
+        ("database.mysql.pool_max_connections", "20"),
+        ("database.mysql.replica_uris", "mysql://replica-1:3306,mysql://replica-2:3306"),
+        ("database.mysql.uri", "mysql://db:3306"),
+        ("database.mysql.username", "app"),
+        ("database.redis.uri", "redis://cache:6379"),
+        ("jwt.issuer", "shop-auth"),
+    ]));
+}
+
+#[test]
+fn legacy_mysql_uri_is_composed_from_host_and_port() {
+    let uri = |legacy: &[(&str, &str)]| legacy_env_keys(vars(legacy)).remove("database.mysql.uri");
+
+    assert_eq!(uri(&[("MYSQL_HOST", "db"), ("MYSQL_PORT", "3307")]).as_deref(), Some("mysql://db:3307"));
+    assert_eq!(uri(&[("MYSQL_HOST", "db")]).as_deref(), Some("mysql://db:3306"));
+    assert_eq!(uri(&[("MYSQL_PORT", "3307")]), None);
+}
+
+#[test]
+fn legacy_keys_load_like_any_other_layer() {
+    let dir = config_dir("legacy", "");
+    let keys = legacy_env_keys(vars(&[("MYSQL_HOST", "db"), ("MYSQL_POOL_SIZE", "8"), ("JWT_ISSUER", "shop-auth")]));
+    let overrides = keys.iter().map(|(key, value)| (key.as_str(), value.as_str())).collect::<Vec<_>>();
+
+    let config = AppConfig::load(&sources(&dir, "legacy", &overrides)).unwrap();
+
+    let mysql = config.database.mysql.unwrap();
+    assert_eq!((mysql.uri.as_str(), mysql.pool_size), ("mysql://db:3306", Some(8)));
+    assert_eq!(config.jwt.issuer, "shop-auth");
+}
+
+#[test]
+fn command_line_options_are_parsed() {
+    let args = ["--env=production", "--config-dir", "/etc/shop", "--set", "jwt.kid=a=b", "--print-config"];
+    let options = CliOptions::parse(args.iter().map(|arg| arg.to_string())).unwrap();
+
+    assert!(options.print_config);
+    assert_eq!(options.sources.environment.as_deref(), Some("production"));
+    assert_eq!(options.sources.config_dir, "/etc/shop");
+    assert_eq!(options.sources.overrides, vec![("jwt.kid".to_string(), "a=b".to_string())]);
+
+    assert!(CliOptions::parse(["--verbose".to_string()]).is_err());
+    assert!(CliOptions::parse(["--set".to_string(), "no-value".to_string()]).is_err());
+}
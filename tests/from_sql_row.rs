@@ -0,0 +1,115 @@
+mod support;
+
+use sqlx::mysql::MySqlRow;
+
+use e_commerce_system::services::user::model::user_model::User;
+use e_commerce_system::shared::database::mysql::{get_column_index_map, FromSqlRow};
+use e_commerce_system_macros::FromSqlRow;
+
+use support::mysql::{MySqlStub, BIGINT, DATETIME, VARCHAR};
+
+
+#[derive(Debug, PartialEq, FromSqlRow)]
+struct Owner {
+    id: i64,
+    name: String,
+}
+
+#[derive(Debug, PartialEq, FromSqlRow)]
+struct Account {
+    id: i64,
+    #[sql(rename = "LOGIN")]
+    username: String,
+    #[sql(default)]
+    nickname: Option<String>,
+    #[sql(default = "default_locale")]
+    locale: String,
+    #[sql(nested, prefix = "owner_")]
+    owner: Owner,
+    #[sql(nested, prefix = "reviewer_")]
+    reviewer: Option<Owner>,
+    #[sql(skip)]
+    loaded: bool,
+}
+
+fn default_locale() -> String {
+    "en".to_string()
+}
+
+const ACCOUNT_COLUMNS: [(&str, u8); 6] = [
+    ("id", BIGINT), ("Login", VARCHAR), ("owner_id", BIGINT), ("owner_name", VARCHAR), ("reviewer_id", BIGINT), ("reviewer_name", VARCHAR),
+];
+
+/// Fetches the single row the stub returns for `statement`.
+async fn fetch_row(stub: &MySqlStub, statement: &str) -> MySqlRow {
+    sqlx::raw_sql(statement).fetch_one(stub.pools().primary()).await.unwrap()
+}
+
+fn map<T: FromSqlRow>(row: MySqlRow) -> Result<T, sqlx::Error> {
+    let index_map = get_column_index_map(&row);
+    T::map_row_to_entity(row, &index_map)
+}
+
+
+#[tokio::test]
+async fn fields_are_renamed_defaulted_nested_and_skipped() {
+    let stub = MySqlStub::start().await;
+    stub.returns("select account", &ACCOUNT_COLUMNS, &[&[Some("1"), Some("ada"), Some("7"), Some("grace"), None, None]]);
+
+    let account: Account = map(fetch_row(&stub, "select account").await).unwrap();
+
+    assert_eq!(account, Account {
+        id: 1,
+        username: "ada".to_string(),
+        nickname: None,
+        locale: "en".to_string(),
+        owner: Owner { id: 7, name: "grace".to_string() },
+        // the outer join found no reviewer
+        reviewer: None,
+        loaded: false,
+    });
+}
+
+#[tokio::test]
+async fn missing_columns_are_reported_instead_of_panicking() {
+    let stub = MySqlStub::start().await;
+    stub.returns("select without login", &[("id", BIGINT)], &[&[Some("1")]]);
+    stub.returns("select without owner name", &ACCOUNT_COLUMNS[..3], &[&[Some("1"), Some("ada"), Some("7")]]);
+    stub.returns("select without reviewer", &ACCOUNT_COLUMNS[..4], &[&[Some("1"), Some("ada"), Some("7"), Some("grace")]]);
+
+    for (statement, column) in [("select without login", "login"), ("select without owner name", "owner_name"), ("select without reviewer", "reviewer_id")] {
+        match map::<Account>(fetch_row(&stub, statement).await) {
+            Err(sqlx::Error::ColumnNotFound(missing)) => assert_eq!(missing, column),
+            other => panic!("expected ColumnNotFound({}), got {:?}", column, other),
+        }
+    }
+}
+
+#[tokio::test]
+async fn users_read_their_auth_and_status_from_user_view() {
+    let stub = MySqlStub::start().await;
+    let columns = [
+        ("id", BIGINT), ("first_name", VARCHAR), ("last_name", VARCHAR), ("username", VARCHAR), ("password", VARCHAR), ("profile_pic_url", VARCHAR),
+        ("auth", BIGINT), ("auth_id", BIGINT), ("auth_name", VARCHAR), ("auth_description", VARCHAR), ("auth_version", BIGINT),
+        ("status", BIGINT), ("status_id", BIGINT), ("status_name", VARCHAR), ("status_description", VARCHAR), ("status_version", BIGINT),
+        ("hired_date", DATETIME), ("title", VARCHAR), ("address", VARCHAR), ("country", VARCHAR), ("phone", VARCHAR),
+        ("created_at", DATETIME), ("updated_at", DATETIME), ("version", BIGINT),
+    ];
+    stub.returns("select * from user_view", &columns, &[&[
+        Some("1"), Some("Ada"), Some("Lovelace"), Some("ada"), Some("hash"), None,
+        Some("2"), Some("2"), Some("manager"), Some("Back office"), Some("3"),
+        Some("4"), None, None, None, None,
+        Some("2024-01-02 03:04:05"), Some("Engineer"), None, None, None,
+        Some("2024-01-02 03:04:05"), Some("2024-01-02 03:04:05"), Some("5"),
+    ]]);
+
+    let user: User = map(fetch_row(&stub, "select * from user_view").await).unwrap();
+
+    let user_auth = user.user_auth.as_ref().unwrap();
+    assert_eq!((user_auth.id, user_auth.name.as_str(), user_auth.description.as_deref(), user_auth.version), (Some(2), "manager", Some("Back office"), Some(3)));
+    assert!(user.has_auth("manager"));
+    // the status row is missing from the join
+    assert_eq!(user.status, 4);
+    assert!(user.user_status.is_none());
+    assert_eq!((user.title.as_deref(), user.version), (Some("Engineer"), Some(5)));
+}
@@ -0,0 +1,70 @@
+mod support;
+
+use axum::http::StatusCode;
+
+use e_commerce_system::shared::configuration::{AppDatabaseConfig, AppDatabaseMongoDBConfig};
+use e_commerce_system::shared::health::checks::{HealthChecks, HealthStatus};
+
+use support::app::TestApp;
+use support::mysql::MySqlStub;
+
+
+#[tokio::test]
+async fn liveness_does_not_check_dependencies() {
+    let app = TestApp::new().await;
+
+    let response = app.get("/healthz").send().await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["status"], "up");
+    assert_eq!(response.body["dependencies"].as_array().unwrap().len(), 0);
+}
+
+#[tokio::test]
+async fn readiness_fails_when_a_required_dependency_is_down() {
+    let app = TestApp::new().await;
+
+    // The harness MySQL pool points to a server that is not running
+    let response = app.get("/readyz").send().await;
+    assert_eq!(response.status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(response.body["status"], "down");
+
+    let dependencies = response.body["dependencies"].as_array().unwrap();
+    let mysql = dependencies.iter().find(|dependency| dependency["name"] == "mysql").unwrap();
+    assert_eq!(mysql["status"], "down");
+    assert_eq!(mysql["required"], true);
+    // the driver error is only logged
+    assert!(mysql.get("error").is_none());
+    let redis = dependencies.iter().find(|dependency| dependency["name"] == "redis").unwrap();
+    assert_eq!(redis["status"], "up");
+    assert_eq!(redis["required"], false);
+}
+
+#[tokio::test]
+async fn optional_databases_being_down_keeps_the_instance_ready() {
+    let stub = MySqlStub::start().await;
+    let database_config = AppDatabaseConfig {
+        // nothing listens on port 1
+        mongo: Some(AppDatabaseMongoDBConfig { uri: "mongodb://127.0.0.1:1/?serverSelectionTimeoutMS=200".to_string(), database: "app".to_string() }),
+        ..AppDatabaseConfig::default()
+    };
+    let checks = HealthChecks::new(stub.pools().primary().clone(), None).with_config(&database_config).await.unwrap();
+
+    let report = checks.readiness().await;
+    assert!(report.is_up());
+    let statuses: Vec<_> = report.dependencies.iter().map(|dependency| (dependency.name.as_str(), dependency.status, dependency.required)).collect();
+    assert_eq!(statuses, [("mysql", HealthStatus::Up, true), ("mongodb", HealthStatus::Down, false)]);
+    checks.close().await;
+}
+
+#[tokio::test]
+async fn readiness_reports_draining_during_shutdown() {
+    let app = TestApp::new().await;
+    app.shutdown.start_draining();
+
+    let response = app.get("/readyz").send().await;
+    assert_eq!(response.status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(response.body["status"], "draining");
+
+    // Liveness is unaffected, the process must not be restarted while it drains
+    assert_eq!(app.get("/healthz").send().await.status, StatusCode::OK);
+}
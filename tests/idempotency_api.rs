@@ -0,0 +1,90 @@
+mod support;
+
+use axum::http::StatusCode;
+use serde_json::{json, Value};
+
+use e_commerce_system::shared::security::api_key::ApiKeyStore;
+use support::app::TestApp;
+
+
+fn new_user(app: &TestApp, username: &str) -> Value {
+    json!({
+        "first_name": "First",
+        "last_name": "Last",
+        "username": username,
+        "password": "secret-password",
+        "auth": app.seed.customer_auth,
+        "status": app.seed.active_status,
+        "title": "Engineer",
+        "country": "CM",
+    })
+}
+
+async fn user_count(app: &TestApp) -> usize {
+    app.get("/api/user").send().await.body.as_array().unwrap().len()
+}
+
+
+#[tokio::test]
+async fn retried_creations_replay_the_first_response() {
+    let app = TestApp::new().await;
+    let body = new_user(&app, "jdoe");
+
+    let first = app.post("/api/user").header("idempotency-key", "create-jdoe").json(&body).send().await;
+    let retry = app.post("/api/user").header("idempotency-key", "create-jdoe").json(&body).send().await;
+
+    assert_eq!(first.status, StatusCode::OK);
+    assert!(!first.headers.contains_key("idempotent-replayed"));
+    assert_eq!(retry.status, StatusCode::OK);
+    assert_eq!(retry.headers["idempotent-replayed"], "true");
+    assert_eq!(retry.headers["content-type"], first.headers["content-type"]);
+    assert_eq!(retry.body, first.body);
+    assert_eq!(user_count(&app).await, 1);
+}
+
+#[tokio::test]
+async fn reusing_a_key_for_another_request_is_rejected() {
+    let app = TestApp::new().await;
+
+    let first = app.post("/api/user").header("idempotency-key", "key-1").json(&new_user(&app, "jdoe")).send().await;
+    let other = app.post("/api/user").header("idempotency-key", "key-1").json(&new_user(&app, "asmith")).send().await;
+    let invalid = app.post("/api/user").header("idempotency-key", "has spaces").json(&new_user(&app, "asmith")).send().await;
+
+    assert_eq!(first.status, StatusCode::OK);
+    assert_eq!(other.status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(invalid.status, StatusCode::BAD_REQUEST);
+    assert_eq!(user_count(&app).await, 1);
+}
+
+#[tokio::test]
+async fn concurrent_duplicates_create_once() {
+    let app = TestApp::new().await;
+    let body = new_user(&app, "jdoe");
+
+    let (a, b) = tokio::join!(
+        app.post("/api/user").header("idempotency-key", "concurrent").json(&body).send(),
+        app.post("/api/user").header("idempotency-key", "concurrent").json(&body).send(),
+    );
+
+    assert_eq!(a.status, StatusCode::OK);
+    assert_eq!(b.status, StatusCode::OK);
+    assert_eq!(a.body["id"], b.body["id"]);
+    assert_eq!(user_count(&app).await, 1);
+}
+
+#[tokio::test]
+async fn keys_are_scoped_to_the_caller() {
+    let app = TestApp::with_config(|config| {
+        config.rate_limit.api_key_hashes = vec![ApiKeyStore::hash("client-a"), ApiKeyStore::hash("client-b")];
+    }).await;
+
+    let first = app.post("/api/user").header("x-api-key", "client-a").header("idempotency-key", "same")
+        .json(&new_user(&app, "jdoe")).send().await;
+    let second = app.post("/api/user").header("x-api-key", "client-b").header("idempotency-key", "same")
+        .json(&new_user(&app, "asmith")).send().await;
+
+    assert_eq!(first.status, StatusCode::OK);
+    assert_eq!(second.status, StatusCode::OK);
+    assert_ne!(first.body["id"], second.body["id"]);
+    assert_eq!(user_count(&app).await, 2);
+}
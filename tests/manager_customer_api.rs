@@ -0,0 +1,76 @@
+mod support;
+
+use axum::http::StatusCode;
+use serde_json::{json, Value};
+
+use e_commerce_system::services::user::model::user_model::{USER_AUTH_CUSTOMER, USER_AUTH_MANAGER};
+use support::app::TestApp;
+
+
+fn customer(username: &str) -> Value {
+    json!({
+        "first_name": "Ada",
+        "last_name": "Lovelace",
+        "username": username,
+        "password": "secret-password",
+        "address": "1 Main Street",
+        "country": "CM",
+        "phone": "+237000000",
+    })
+}
+
+#[tokio::test]
+async fn customers_sign_up_with_a_unique_username() {
+    let app = TestApp::new().await;
+
+    let created = app.post("/api/customer").json(&customer("ada")).send().await;
+    let duplicate = app.post("/api/customer").json(&customer("ada")).send().await;
+    let blank = app.post("/api/customer").json(&customer(" ")).send().await;
+
+    assert_eq!(created.status, StatusCode::OK);
+    assert_eq!(created.body["user"]["auth"]["name"], USER_AUTH_CUSTOMER);
+    assert_eq!(created.body["country"], "CM");
+    assert_eq!(duplicate.status, StatusCode::CONFLICT);
+    assert_eq!(blank.status, StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[tokio::test]
+async fn customers_only_read_themselves() {
+    let app = TestApp::new().await;
+    let user_id = app.post("/api/customer").json(&customer("ada")).send().await.body["user"]["id"].as_i64().unwrap();
+    let uri = format!("/api/customer/{}", user_id);
+
+    let anonymous = app.get(&uri).send().await;
+    let other = app.get(&uri).bearer(&app.token(user_id + 1, USER_AUTH_CUSTOMER)).send().await;
+    let own = app.get(&uri).bearer(&app.token(user_id, USER_AUTH_CUSTOMER)).send().await;
+    let listed = app.get("/api/customer").bearer(&app.manager_token()).send().await;
+
+    assert_eq!(anonymous.status, StatusCode::UNAUTHORIZED);
+    assert_eq!(other.status, StatusCode::FORBIDDEN);
+    assert_eq!(own.status, StatusCode::OK);
+    assert_eq!(listed.body.as_array().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn managers_are_created_and_listed_by_managers() {
+    let app = TestApp::new().await;
+    let manager = json!({
+        "first_name": "Grace",
+        "last_name": "Hopper",
+        "username": "grace",
+        "password": "secret-password",
+        "hired_date": "2024-01-01T00:00:00Z",
+        "title": "Director",
+    });
+
+    let by_customer = app.post("/api/manager").bearer(&app.token(1, USER_AUTH_CUSTOMER)).json(&manager).send().await;
+    let created = app.post("/api/manager").bearer(&app.manager_token()).json(&manager).send().await;
+    let listed = app.get("/api/manager").bearer(&app.manager_token()).send().await;
+    let read = app.get(&format!("/api/manager/{}", created.body["user"]["id"])).bearer(&app.manager_token()).send().await;
+
+    assert_eq!(by_customer.status, StatusCode::FORBIDDEN);
+    assert_eq!(created.status, StatusCode::OK);
+    assert_eq!(created.body["user"]["auth"]["name"], USER_AUTH_MANAGER);
+    assert_eq!(listed.body.as_array().unwrap().len(), 1);
+    assert_eq!(read.body["title"], "Director");
+}
@@ -0,0 +1,54 @@
+mod support;
+
+use image::{GenericImageView, ImageFormat};
+
+use e_commerce_system::shared::errors::ServiceError;
+use e_commerce_system::shared::media::{picture_keys, process_profile_picture, THUMBNAIL_SIZES};
+use support::images::{jpeg, png};
+
+
+#[test]
+fn png_pictures_get_png_thumbnails_fitted_in_each_size() {
+    let image = process_profile_picture(png(600, 300), 1024 * 1024).unwrap();
+
+    assert_eq!(image.format, ImageFormat::Png);
+    assert_eq!(image.extension(), "png");
+    assert_eq!(image.thumbnail_content_type(), "image/png");
+    let sizes: Vec<u32> = image.thumbnails.iter().map(|(size, _)| *size).collect();
+    assert_eq!(sizes, THUMBNAIL_SIZES);
+    for (size, bytes) in &image.thumbnails {
+        let thumbnail = image::load_from_memory_with_format(bytes, ImageFormat::Png).unwrap();
+        assert_eq!(thumbnail.dimensions(), (*size, *size / 2));
+    }
+}
+
+#[test]
+fn jpeg_pictures_keep_jpeg_thumbnails() {
+    let image = process_profile_picture(jpeg(100, 100), 1024 * 1024).unwrap();
+
+    assert_eq!(image.content_type(), "image/jpeg");
+    assert_eq!(image.thumbnail_extension(), "jpg");
+    assert!(image.thumbnails.iter().all(|(_, bytes)| image::guess_format(bytes).unwrap() == ImageFormat::Jpeg));
+}
+
+#[test]
+fn pictures_are_validated_before_decoding() {
+    let empty = process_profile_picture(Vec::new(), 1024);
+    let too_large = process_profile_picture(png(64, 64), 16);
+    let text = process_profile_picture(b"not an image at all".to_vec(), 1024);
+    let bitmap = process_profile_picture(b"BM\x3a\0\0\0\0\0\0\0\x36\0\0\0".to_vec(), 1024);
+    let truncated = process_profile_picture(png(64, 64)[..40].to_vec(), 1024);
+
+    assert!(matches!(empty, Err(ServiceError::Validation(_))));
+    assert!(matches!(too_large, Err(ServiceError::PayloadTooLarge(_))));
+    assert!(matches!(text, Err(ServiceError::UnsupportedMediaType(_))));
+    assert!(matches!(bitmap, Err(ServiceError::UnsupportedMediaType(_))));
+    assert!(matches!(truncated, Err(ServiceError::Validation(_))));
+}
+
+#[test]
+fn picture_keys_include_the_thumbnails() {
+    assert_eq!(picture_keys("users/1/profile/a.png"), ["users/1/profile/a.png", "users/1/profile/a_64.png", "users/1/profile/a_256.png"]);
+    assert_eq!(picture_keys("users/1/profile/a.jpg"), ["users/1/profile/a.jpg", "users/1/profile/a_64.jpg", "users/1/profile/a_256.jpg"]);
+    assert_eq!(picture_keys("users/1/profile/a.gif"), ["users/1/profile/a.gif", "users/1/profile/a_64.png", "users/1/profile/a_256.png"]);
+}
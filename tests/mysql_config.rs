@@ -0,0 +1,103 @@
+use std::time::Duration;
+
+use e_commerce_system::shared::configuration::AppDatabaseMySQLConfig;
+use e_commerce_system::shared::database::mysql::{connect_options, pool_options};
+
+
+fn mysql_config() -> AppDatabaseMySQLConfig {
+    AppDatabaseMySQLConfig {
+        uri: "mysql://root:root@db:3306/app".to_string(),
+        replica_uris: Vec::new(),
+        username: None,
+        password: None,
+        database: None,
+        pool_size: None,
+        pool_min_idle: None,
+        pool_max_lifetime: None,
+        pool_idle_timeout: None,
+        pool_connection_timeout: None,
+        pool_max_connections: None,
+        pool_connection_lifetime: None,
+        pool_connection_acquisition_timeout: None,
+    }
+}
+
+
+#[test]
+fn newer_pool_settings_take_priority() {
+    let options = pool_options(&AppDatabaseMySQLConfig {
+        pool_size: Some(5),
+        pool_max_connections: Some(20),
+        pool_connection_lifetime: Some(60),
+        pool_max_lifetime: Some(600),
+        pool_connection_timeout: Some(3),
+        pool_connection_acquisition_timeout: Some(7),
+        ..mysql_config()
+    }).unwrap();
+
+    assert_eq!(options.get_max_connections(), 20);
+    assert_eq!(options.get_max_lifetime(), Some(Duration::from_secs(600)));
+    assert_eq!(options.get_acquire_timeout(), Duration::from_secs(7));
+}
+
+#[test]
+fn legacy_pool_settings_apply_alone() {
+    let options = pool_options(&AppDatabaseMySQLConfig {
+        pool_size: Some(5),
+        pool_min_idle: Some(2),
+        pool_connection_lifetime: Some(60),
+        pool_idle_timeout: Some(30),
+        pool_connection_timeout: Some(3),
+        ..mysql_config()
+    }).unwrap();
+
+    assert_eq!(options.get_max_connections(), 5);
+    assert_eq!(options.get_min_connections(), 2);
+    assert_eq!(options.get_max_lifetime(), Some(Duration::from_secs(60)));
+    assert_eq!(options.get_idle_timeout(), Some(Duration::from_secs(30)));
+    assert_eq!(options.get_acquire_timeout(), Duration::from_secs(3));
+}
+
+#[test]
+fn more_idle_than_maximum_connections_is_rejected() {
+    let error = pool_options(&AppDatabaseMySQLConfig {
+        pool_max_connections: Some(4),
+        pool_min_idle: Some(8),
+        ..mysql_config()
+    }).unwrap_err();
+
+    assert!(error.to_string().contains("pool_min_idle (8)"), "{}", error);
+    assert!(pool_options(&AppDatabaseMySQLConfig { pool_size: Some(4), pool_min_idle: Some(4), ..mysql_config() }).is_ok());
+}
+
+#[test]
+fn negative_pool_settings_are_rejected() {
+    let configs = [
+        ("pool_size", AppDatabaseMySQLConfig { pool_size: Some(-1), ..mysql_config() }),
+        ("pool_min_idle", AppDatabaseMySQLConfig { pool_min_idle: Some(-1), ..mysql_config() }),
+        ("pool_max_lifetime", AppDatabaseMySQLConfig { pool_connection_lifetime: Some(-1), ..mysql_config() }),
+        ("pool_idle_timeout", AppDatabaseMySQLConfig { pool_idle_timeout: Some(-1), ..mysql_config() }),
+        ("pool_connection_acquisition_timeout", AppDatabaseMySQLConfig { pool_connection_timeout: Some(-1), ..mysql_config() }),
+    ];
+
+    for (field, config) in configs {
+        let error = pool_options(&config).unwrap_err();
+        assert_eq!(error.to_string(), format!("MySQL {} must not be negative, got -1", field));
+    }
+}
+
+#[test]
+fn credentials_set_apart_override_the_uri() {
+    let from_uri = connect_options(&mysql_config(), "mysql://root:root@db:3306/app").unwrap();
+    let overridden = connect_options(&AppDatabaseMySQLConfig {
+        username: Some("shop".to_string()),
+        password: Some("secret".to_string()),
+        database: Some("shop".to_string()),
+        ..mysql_config()
+    }, "mysql://replica:3307").unwrap();
+
+    assert_eq!((from_uri.get_host(), from_uri.get_username(), from_uri.get_database()), ("db", "root", Some("app")));
+    assert_eq!((overridden.get_host(), overridden.get_port()), ("replica", 3307));
+    assert_eq!((overridden.get_username(), overridden.get_database()), ("shop", Some("shop")));
+    assert!(connect_options(&mysql_config(), "not a uri").is_err());
+}
@@ -0,0 +1,86 @@
+use std::sync::Mutex;
+
+use sqlx::mysql::MySqlPoolOptions;
+use sqlx::MySqlPool;
+
+use e_commerce_system::shared::database::mysql_pools::MySqlPools;
+
+
+/// Pool that never connects, told apart from the others by its host.
+fn pool(host: &str) -> MySqlPool {
+    MySqlPoolOptions::new().connect_lazy(&format!("mysql://{}:3306/app", host)).unwrap()
+}
+
+fn host_of(pool: &MySqlPool) -> String {
+    pool.connect_options().get_host().to_string()
+}
+
+/// Reads the host of the pool `read` picks, the hosts of `unreachable` failing as if they were down.
+async fn read_host(pools: &MySqlPools, unreachable: &[&str], attempts: &Mutex<Vec<String>>) -> Result<String, sqlx::Error> {
+    pools.read(|pool| {
+        let host = host_of(&pool);
+        attempts.lock().unwrap().push(host.clone());
+        let reachable = !unreachable.contains(&host.as_str());
+        async move { if reachable { Ok(host) } else { Err(sqlx::Error::PoolTimedOut) } }
+    }).await
+}
+
+
+#[tokio::test]
+async fn reads_go_round_robin_to_the_replicas() {
+    let pools = MySqlPools::new(pool("primary"), vec![pool("replica-1"), pool("replica-2")]);
+    let attempts = Mutex::new(Vec::new());
+
+    for _ in 0..4 {
+        read_host(&pools, &[], &attempts).await.unwrap();
+    }
+    read_host(&pools.primary_only(), &[], &attempts).await.unwrap();
+    read_host(&MySqlPools::new(pool("primary"), Vec::new()), &[], &attempts).await.unwrap();
+
+    assert_eq!(*attempts.lock().unwrap(), ["replica-1", "replica-2", "replica-1", "replica-2", "primary", "primary"]);
+}
+
+#[tokio::test]
+async fn unreachable_replica_falls_back_to_the_primary_until_it_is_skipped() {
+    let pools = MySqlPools::new(pool("primary"), vec![pool("replica")]);
+    let attempts = Mutex::new(Vec::new());
+
+    for _ in 0..3 {
+        assert_eq!(read_host(&pools, &["replica"], &attempts).await.unwrap(), "primary");
+    }
+    assert_eq!(attempts.lock().unwrap().drain(..).collect::<Vec<_>>(), ["replica", "primary", "replica", "primary", "replica", "primary"]);
+
+    // the replica failed 3 times in a row, reads no longer try it
+    assert_eq!(read_host(&pools, &["replica"], &attempts).await.unwrap(), "primary");
+    assert_eq!(*attempts.lock().unwrap(), ["primary"]);
+}
+
+#[tokio::test]
+async fn skipped_replica_leaves_its_reads_to_the_healthy_ones() {
+    let pools = MySqlPools::new(pool("primary"), vec![pool("replica-1"), pool("replica-2")]);
+    let attempts = Mutex::new(Vec::new());
+
+    for _ in 0..6 {
+        read_host(&pools, &["replica-1"], &attempts).await.unwrap();
+    }
+    attempts.lock().unwrap().clear();
+
+    for _ in 0..4 {
+        assert_eq!(read_host(&pools, &["replica-1"], &attempts).await.unwrap(), "replica-2");
+    }
+    assert_eq!(*attempts.lock().unwrap(), ["replica-2"; 4]);
+}
+
+#[tokio::test]
+async fn query_errors_are_not_retried_on_the_primary() {
+    let pools = MySqlPools::new(pool("primary"), vec![pool("replica")]);
+    let attempts = Mutex::new(Vec::new());
+
+    let result = pools.read(|pool| {
+        attempts.lock().unwrap().push(host_of(&pool));
+        async { Err::<(), _>(sqlx::Error::RowNotFound) }
+    }).await;
+
+    assert!(matches!(result, Err(sqlx::Error::RowNotFound)));
+    assert_eq!(*attempts.lock().unwrap(), ["replica"]);
+}
@@ -0,0 +1,106 @@
+#[path = "../build/procedure_parser.rs"]
+mod procedure_parser;
+
+use procedure_parser::{collect_procedures, generate, parse_procedures, Procedure};
+
+
+fn parse_one(sql: &str) -> Procedure {
+    let mut procedures = parse_procedures(sql, "test.sql").unwrap();
+    assert_eq!(procedures.len(), 1);
+    procedures.remove(0)
+}
+
+/// `(name, rust type, nullable)` of each parameter.
+fn params(procedure: &Procedure) -> Vec<(&str, &str, bool)> {
+    procedure.params.iter().map(|param| (param.name.as_str(), param.rust_type, param.nullable)).collect()
+}
+
+
+#[test]
+fn parameters_are_typed_and_only_marked_ones_are_nullable() {
+    let procedure = parse_one("
+        CREATE PROCEDURE `app_order_insert`(IN __order_user bigint,
+                                           __order_price DECIMAL(10, 2),
+                                           IN __order_note varchar(200) /* nullable */,
+                                           IN __order_paid tinyint(1) /* required */,
+                                           IN __order_quantity int unsigned,
+                                           IN __order_due datetime /* Nullable */)
+        begin
+            select 1;
+        end;
+    ");
+
+    assert_eq!(procedure.name, "app_order_insert");
+    assert_eq!(params(&procedure), [
+        ("order_user", "i64", false),
+        ("order_price", "Decimal", false),
+        ("order_note", "String", true),
+        ("order_paid", "bool", false),
+        ("order_quantity", "u32", false),
+        ("order_due", "DateTime<Utc>", true),
+    ]);
+    assert_eq!(procedure.params[1].sql_type, "decimal(10, 2)");
+}
+
+#[test]
+fn parameters_named_after_keywords_are_raw_identifiers() {
+    let procedure = parse_one("create procedure app_get(IN __type varchar(20), IN __match int, IN __kind int) begin end;");
+
+    assert_eq!(params(&procedure).iter().map(|(name, _, _)| *name).collect::<Vec<_>>(), ["r#type", "r#match", "kind"]);
+}
+
+#[test]
+fn procedures_in_comments_and_strings_are_ignored() {
+    let procedures = parse_procedures("
+        -- create procedure commented_out(IN __id bigint)
+        # create procedure hashed_out(IN __id bigint)
+        /* create procedure
+           block_commented(IN __id bigint) */
+        create procedure app_real(IN __id bigint, IN __name varchar(20) /* nullable */)
+        begin
+            select 'create procedure quoted(IN __id bigint)', \"it's create procedure double_quoted()\";
+            select * from `create procedure`;
+        end;
+        create procedure_log_view as select 1;
+    ", "test.sql").unwrap();
+
+    assert_eq!(procedures.iter().map(|procedure| procedure.name.as_str()).collect::<Vec<_>>(), ["app_real"]);
+    assert_eq!(params(&procedures[0]), [("id", "i64", false), ("name", "String", true)]);
+}
+
+#[test]
+fn out_parameters_and_unknown_types_are_rejected() {
+    for (sql, error) in [
+        ("create procedure app_count(OUT __count int) begin end;", "test.sql: OUT parameters of app_count are not supported"),
+        ("create procedure app_swap(INOUT __value int) begin end;", "test.sql: OUT parameters of app_swap are not supported"),
+        ("create procedure app_area(IN __shape geometry) begin end;", "test.sql: unsupported type `geometry` of app_area.shape"),
+        ("create procedure app_broken(IN __id bigint begin end;", "test.sql: unbalanced parameter list of app_broken"),
+    ] {
+        assert_eq!(parse_procedures(sql, "test.sql").err().as_deref(), Some(error));
+    }
+}
+
+#[test]
+fn procedures_defined_twice_are_rejected() {
+    let get = "create procedure app_get(IN __id bigint) begin end;";
+    let list = "create procedure app_list() begin end;";
+
+    let procedures = collect_procedures([("get.sql", get), ("list.sql", list)]).unwrap();
+    assert_eq!(procedures.keys().collect::<Vec<_>>(), ["app_get", "app_list"]);
+
+    let error = collect_procedures([("get.sql", get), ("list.sql", list), ("copy.sql", get)]).err();
+    assert_eq!(error.as_deref(), Some("procedure app_get is defined in both get.sql and copy.sql"));
+}
+
+#[test]
+fn bindings_take_options_for_nullable_parameters_only() {
+    let procedures = collect_procedures([
+        ("app_get.sql", "create procedure app_get(IN __id bigint, IN __meta_user bigint /* nullable */) begin end;"),
+    ]).unwrap();
+
+    let code = generate(procedures.values());
+
+    assert!(code.contains("/// `app_get(id bigint, meta_user bigint)`, from `app_get.sql`"), "{}", code);
+    assert!(code.contains("pub fn app_get(id: impl Into<i64>, meta_user: impl Into<Option<i64>>) -> ProcedureCall {"), "{}", code);
+    assert!(code.contains("ProcedureCall::new(\"app_get\", vec![MySqlParam::from(id.into()), MySqlParam::from(meta_user.into())])"), "{}", code);
+}
@@ -0,0 +1,90 @@
+mod support;
+
+use axum::http::{header, StatusCode};
+use serde_json::json;
+
+use e_commerce_system::services::user::model::user_model::USER_AUTH_CUSTOMER;
+use support::app::TestApp;
+use support::images::{jpeg, png};
+
+
+const MEDIA_BASE_URL: &str = "http://localhost/media/";
+
+/// Keys of the files stored below `users/<user_id>/profile`, sorted.
+fn stored_keys(app: &TestApp, user_id: i64) -> Vec<String> {
+    let directory = app.storage_path.join(format!("users/{}/profile", user_id));
+    let mut keys: Vec<String> = std::fs::read_dir(directory)
+        .map(|entries| entries.map(|entry| format!("users/{}/profile/{}", user_id, entry.unwrap().file_name().to_string_lossy())).collect())
+        .unwrap_or_default();
+    keys.sort();
+    keys
+}
+
+#[tokio::test]
+async fn uploaded_picture_is_stored_with_its_thumbnails_and_served() {
+    let app = TestApp::new().await;
+    let user_id = app.create_user("jdoe", app.seed.customer_auth).await["id"].as_i64().unwrap();
+
+    let response = app.post(&format!("/api/user/{}/profile-pic", user_id))
+        .bearer(&app.token(user_id, USER_AUTH_CUSTOMER))
+        .multipart("file", "me.png", &png(320, 320))
+        .send()
+        .await;
+
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.etag(), "\"2\"");
+    let url = response.body["profile_pic_url"].as_str().unwrap();
+    let key = url.strip_prefix(MEDIA_BASE_URL).unwrap();
+    let name = key.strip_suffix(".png").unwrap();
+    assert_eq!(stored_keys(&app, user_id), [key.to_string(), format!("{}_256.png", name), format!("{}_64.png", name)]);
+
+    let media = app.get(&format!("/media/{}", key)).send().await;
+    assert_eq!(media.status, StatusCode::OK);
+    assert_eq!(media.headers[header::CONTENT_TYPE], "image/png");
+    assert_eq!(app.get(&format!("/media/{}_64.png", name)).send().await.status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn uploads_are_validated() {
+    let app = TestApp::with_config(|config| config.storage.max_upload_bytes = 4096).await;
+    let user_id = app.create_user("jdoe", app.seed.customer_auth).await["id"].as_i64().unwrap();
+    let uri = format!("/api/user/{}/profile-pic", user_id);
+    let token = app.token(user_id, USER_AUTH_CUSTOMER);
+
+    let other = app.post(&uri).bearer(&app.token(user_id + 1, USER_AUTH_CUSTOMER)).multipart("file", "me.png", &png(8, 8)).send().await;
+    let missing_part = app.post(&uri).bearer(&token).multipart("picture", "me.png", &png(8, 8)).send().await;
+    let text = app.post(&uri).bearer(&token).multipart("file", "me.png", b"not an image").send().await;
+    let too_large = app.post(&uri).bearer(&token).multipart("file", "me.png", &[0; 8192]).send().await;
+    let unknown_user = app.post("/api/user/404/profile-pic").bearer(&app.manager_token()).multipart("file", "me.png", &png(8, 8)).send().await;
+
+    assert_eq!(other.status, StatusCode::FORBIDDEN);
+    assert_eq!(missing_part.status, StatusCode::BAD_REQUEST);
+    assert_eq!(text.status, StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    assert_eq!(too_large.status, StatusCode::PAYLOAD_TOO_LARGE);
+    assert_eq!(unknown_user.status, StatusCode::NOT_FOUND);
+    assert!(stored_keys(&app, user_id).is_empty());
+    assert_eq!(app.database.user(user_id).unwrap().profile_pic_url, None);
+}
+
+#[tokio::test]
+async fn replaced_picture_and_its_thumbnails_are_deleted() {
+    let app = TestApp::new().await;
+    let user_id = app.create_user("jdoe", app.seed.customer_auth).await["id"].as_i64().unwrap();
+    let uri = format!("/api/user/{}/profile-pic", user_id);
+    let token = app.token(user_id, USER_AUTH_CUSTOMER);
+
+    let first = app.post(&uri).bearer(&token).multipart("file", "me.png", &png(100, 100)).send().await;
+    let second = app.post(&uri).bearer(&token).multipart("file", "me.jpg", &jpeg(100, 100)).send().await;
+
+    assert_eq!(second.status, StatusCode::OK);
+    let first_key = first.body["profile_pic_url"].as_str().unwrap().strip_prefix(MEDIA_BASE_URL).unwrap().to_string();
+    let second_key = second.body["profile_pic_url"].as_str().unwrap().strip_prefix(MEDIA_BASE_URL).unwrap().to_string();
+    let name = second_key.strip_suffix(".jpg").unwrap();
+    assert_eq!(stored_keys(&app, user_id), [second_key.clone(), format!("{}_256.jpg", name), format!("{}_64.jpg", name)]);
+    assert_eq!(app.get(&format!("/media/{}", first_key)).send().await.status, StatusCode::NOT_FOUND);
+
+    // pointing the user at an external picture also drops the uploaded one
+    let external = app.put(&uri).bearer(&token).json(&json!({ "profile_pic_url": "https://cdn.example.com/me.png" })).send().await;
+    assert_eq!(external.status, StatusCode::OK);
+    assert!(stored_keys(&app, user_id).is_empty());
+}
@@ -0,0 +1,78 @@
+mod support;
+
+use axum::http::StatusCode;
+use serde_json::json;
+
+use e_commerce_system::services::user::model::user_model::USER_AUTH_CUSTOMER;
+use e_commerce_system::shared::security::api_key::ApiKeyStore;
+use support::app::{TestApp, STRICT_RATE_LIMIT};
+
+
+#[tokio::test]
+async fn responses_report_the_quota_of_their_policy() {
+    let app = TestApp::new().await;
+    let token = app.manager_token();
+
+    let first = app.get("/api/user").bearer(&token).send().await;
+    let second = app.get("/api/user").bearer(&token).send().await;
+
+    assert_eq!(first.status, StatusCode::OK);
+    assert_eq!(first.headers["ratelimit-limit"], "600");
+    assert_eq!(first.headers["ratelimit-policy"], "600;w=60");
+    assert_eq!(first.headers["ratelimit-remaining"], "599");
+    assert_eq!(second.headers["ratelimit-remaining"], "598");
+    assert!(second.headers.contains_key("ratelimit-reset"));
+}
+
+#[tokio::test]
+async fn password_changes_are_limited_per_user() {
+    let app = TestApp::new().await;
+    let user_id = app.create_user("alice", app.seed.customer_auth).await["id"].as_i64().unwrap();
+    let token = app.token(user_id, USER_AUTH_CUSTOMER);
+    let uri = format!("/api/user/{}/password", user_id);
+    let attempt = json!({ "current_password": "guess", "password": "new-password" });
+
+    for _ in 0..STRICT_RATE_LIMIT {
+        let response = app.put(&uri).bearer(&token).json(&attempt).send().await;
+        assert_ne!(response.status, StatusCode::TOO_MANY_REQUESTS);
+    }
+    let limited = app.put(&uri).bearer(&token).json(&attempt).send().await;
+    assert_eq!(limited.status, StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(limited.headers["ratelimit-remaining"], "0");
+    let retry_after: u64 = limited.headers["retry-after"].to_str().unwrap().parse().unwrap();
+    assert!((1..=60).contains(&retry_after));
+
+    // Other routes and other callers have their own buckets
+    assert_eq!(app.get(&format!("/api/user/{}", user_id)).bearer(&token).send().await.status, StatusCode::OK);
+    let other = app.put(&uri).bearer(&app.manager_token()).json(&attempt).send().await;
+    assert_ne!(other.status, StatusCode::TOO_MANY_REQUESTS);
+}
+
+#[tokio::test]
+async fn api_keys_are_counted_apart_from_anonymous_callers() {
+    let app = TestApp::with_config(|config| {
+        config.rate_limit.api_key_hashes = vec![ApiKeyStore::hash("key-1"), ApiKeyStore::hash("key-2")];
+    }).await;
+    let uri = "/api/user/1/password";
+
+    for _ in 0..STRICT_RATE_LIMIT {
+        app.put(uri).header("x-api-key", "key-1").send().await;
+    }
+    assert_eq!(app.put(uri).header("x-api-key", "key-1").send().await.status, StatusCode::TOO_MANY_REQUESTS);
+    assert_ne!(app.put(uri).header("x-api-key", "key-2").send().await.status, StatusCode::TOO_MANY_REQUESTS);
+    assert_ne!(app.put(uri).send().await.status, StatusCode::TOO_MANY_REQUESTS);
+    assert!(app.cache.keys().is_empty(), "buckets are not cache entries");
+}
+
+#[tokio::test]
+async fn unknown_api_keys_count_as_their_ip() {
+    let app = TestApp::with_config(|config| config.rate_limit.api_key_hashes = vec![ApiKeyStore::hash("key-1")]).await;
+    let uri = "/api/user/1/password";
+
+    for attempt in 0..STRICT_RATE_LIMIT {
+        app.put(uri).header("x-api-key", &format!("rotated-{}", attempt)).send().await;
+    }
+    assert_eq!(app.put(uri).header("x-api-key", "rotated-again").send().await.status, StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(app.put(uri).send().await.status, StatusCode::TOO_MANY_REQUESTS);
+    assert_ne!(app.put(uri).header("x-api-key", "key-1").send().await.status, StatusCode::TOO_MANY_REQUESTS);
+}
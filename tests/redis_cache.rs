@@ -0,0 +1,214 @@
+//! Cache paths that only a real Redis exercises: the Lua scripts, the locks and MGET of the
+//! read-through cache, and the pub/sub invalidation of the in-process caches.
+//!
+//! Ignored by default, run them against a disposable server with
+//! `REDIS_TEST_URL=redis://127.0.0.1:6379 cargo test --test redis_cache -- --ignored`.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use futures_util::future::join_all;
+use futures_util::StreamExt;
+use redis::AsyncCommands;
+use redis::aio::MultiplexedConnection;
+
+use e_commerce_system::shared::cache::backend::{CacheBackend, RedisCacheBackend};
+use e_commerce_system::shared::cache::invalidation::invalidate_tags;
+use e_commerce_system::shared::cache::key::{CacheKeys, CACHE_SCHEMA_VERSION};
+use e_commerce_system::shared::cache::local::{spawn_invalidation_listener, LocalCache};
+use e_commerce_system::shared::cache::read_through::ReadThrough;
+use e_commerce_system::shared::cache::store::CacheStore;
+use e_commerce_system::shared::cache::tag::CacheTag;
+use e_commerce_system::shared::configuration::AppDatabaseRedisConfig;
+use e_commerce_system::shared::database::redis::{connect, RedisDatabase};
+use e_commerce_system::shared::shutdown::Shutdown;
+
+
+/// Redis server the tests write to, each test in its own key space.
+fn redis_url() -> String {
+    std::env::var("REDIS_TEST_URL").unwrap_or_else(|_| "redis://127.0.0.1:6379".to_string())
+}
+
+struct TestRedis {
+    pool: RedisDatabase,
+    client: redis::Client,
+    keys: CacheKeys,
+}
+
+impl TestRedis {
+    async fn new() -> Self {
+        let config = AppDatabaseRedisConfig {
+            uri: redis_url(),
+            default_ttl: None,
+            max_connections: Some(8),
+            app_space_name: None,
+            local_cache_capacity: None,
+            local_cache_ttl: None,
+        };
+        let app_space_name = format!("test-{}", uuid::Uuid::new_v4().simple());
+        Self {
+            pool: connect(&config).await.unwrap(),
+            client: redis::Client::open(config.uri.as_str()).unwrap(),
+            keys: CacheKeys::new(&app_space_name, CACHE_SCHEMA_VERSION, Duration::from_secs(60)),
+        }
+    }
+
+    /// Connection outside the pool, to inspect what the cache wrote.
+    async fn connection(&self) -> MultiplexedConnection {
+        self.client.get_multiplexed_async_connection().await.unwrap()
+    }
+}
+
+fn tagged(tag: CacheTag) -> impl Fn(&String) -> Vec<CacheTag> + Clone + Send + 'static {
+    move |_| vec![tag.clone()]
+}
+
+
+#[test]
+fn lock_keys_stay_in_the_app_space() {
+    let keys = CacheKeys::new("shop", CACHE_SCHEMA_VERSION, Duration::from_secs(60));
+
+    assert_eq!(keys.lock(&keys.entity("user", 7)), "shop:v1:lock:user:7");
+    assert_eq!(keys.lock(&keys.idempotency("user:7", "abc")), "shop:v1:lock:idempotency:user:7:abc");
+    assert_eq!(keys.lock("other:v1:user:7"), "shop:v1:lock:other:v1:user:7");
+}
+
+
+#[tokio::test]
+#[ignore = "needs a Redis server, see REDIS_TEST_URL"]
+async fn tagged_keys_are_evicted_with_their_tags() {
+    let redis = TestRedis::new().await;
+    let backend = RedisCacheBackend::new(redis.pool.clone());
+    let mut conn = redis.connection().await;
+    let (user_tag, list_tag) = (redis.keys.entity_tag("user", 1), redis.keys.list_tag("user"));
+    let (user_key, list_key, count_key) = (redis.keys.entity("user", 1), redis.keys.list("user"), redis.keys.list_count("user"));
+
+    backend.set_tagged(&user_key, "{}", 60, &[user_tag.redis_key()]).await.unwrap();
+    backend.set_tagged(&list_key, "[]", 120, &[user_tag.redis_key(), list_tag.redis_key()]).await.unwrap();
+    backend.set_tagged(&count_key, "1", 0, &[list_tag.redis_key()]).await.unwrap();
+
+    let members: HashSet<String> = conn.smembers(user_tag.redis_key()).await.unwrap();
+    assert_eq!(members, HashSet::from([user_key.clone(), list_key.clone()]));
+    assert!((1..=60).contains(&conn.ttl::<_, i64>(&user_key).await.unwrap()));
+    // a tag set outlives its longest-lived key, and never expires once a key does not
+    assert!(conn.ttl::<_, i64>(user_tag.redis_key()).await.unwrap() > 60);
+    assert_eq!(conn.ttl::<_, i64>(list_tag.redis_key()).await.unwrap(), -1);
+
+    let channel = redis.keys.local_invalidation_channel();
+    let mut subscriber = redis.client.get_async_pubsub().await.unwrap();
+    subscriber.subscribe(&channel).await.unwrap();
+
+    let removed = backend.invalidate_tags(&[user_tag.redis_key()], Some((&channel, "evicted"))).await.unwrap();
+    assert_eq!(removed, 2);
+    assert!(!conn.exists::<_, bool>(&user_key).await.unwrap());
+    assert!(!conn.exists::<_, bool>(&list_key).await.unwrap());
+    assert!(!conn.exists::<_, bool>(user_tag.redis_key()).await.unwrap());
+    assert!(conn.exists::<_, bool>(&count_key).await.unwrap());
+
+    let message = tokio::time::timeout(Duration::from_secs(2), subscriber.on_message().next()).await.unwrap().unwrap();
+    assert_eq!(message.get_payload::<String>().unwrap(), "evicted");
+
+    // keys already evicted through another tag are not counted again
+    let removed = backend.invalidate_tags(&[list_tag.redis_key()], None).await.unwrap();
+    assert_eq!(removed, 1);
+    assert!(!conn.exists::<_, bool>(&count_key).await.unwrap());
+}
+
+#[tokio::test]
+#[ignore = "needs a Redis server, see REDIS_TEST_URL"]
+async fn read_through_coalesces_misses_and_caches_missing_rows() {
+    let redis = TestRedis::new().await;
+    let cache = CacheStore::new(redis.pool.clone());
+    let read_through = ReadThrough::new(&redis.keys);
+    let mut conn = redis.connection().await;
+    let tag = redis.keys.list_tag("user");
+    let loads = Arc::new(AtomicUsize::new(0));
+    let loader = |value: Option<&str>| {
+        let (loads, value) = (loads.clone(), value.map(str::to_string));
+        move || async move {
+            loads.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            Result::Ok(value)
+        }
+    };
+
+    let key = redis.keys.entity("user", 1);
+    let values = join_all((0..5).map(|_| read_through.get_or_load(&cache, &key, tagged(tag.clone()), loader(Some("ada"))))).await;
+    assert!(values.into_iter().all(|value| value.unwrap().as_deref() == Some("ada")));
+    assert_eq!(loads.load(Ordering::SeqCst), 1);
+    assert!(!conn.exists::<_, bool>(redis.keys.lock(&key)).await.unwrap());
+    assert!(conn.sismember::<_, _, bool>(tag.redis_key(), &key).await.unwrap());
+
+    let missing = redis.keys.entity("user", 2);
+    for _ in 0..2 {
+        let value = read_through.get_or_load(&cache, &missing, tagged(tag.clone()), loader(None)).await.unwrap();
+        assert_eq!(value, None);
+    }
+    assert_eq!(loads.load(Ordering::SeqCst), 2);
+    assert!((1..=30).contains(&conn.ttl::<_, i64>(&missing).await.unwrap()));
+
+    let batch_loads = Arc::new(AtomicUsize::new(0));
+    let batch = |ids: Vec<i64>| {
+        let (batch_loads, cache, read_through, tag) = (batch_loads.clone(), &cache, &read_through, tag.clone());
+        let key_of = |id: &i64| redis.keys.entity("user", id);
+        async move {
+            read_through.get_many_or_load(cache, &ids, key_of, tagged(tag), move |ids: Vec<i64>| async move {
+                batch_loads.fetch_add(1, Ordering::SeqCst);
+                Result::Ok(ids.into_iter().filter(|id| *id == 3).map(|id| (id, format!("user {}", id))).collect::<HashMap<_, _>>())
+            }).await
+        }
+    };
+    // 1 is cached, 2 is cached as missing, only 3 is loaded
+    assert_eq!(batch(vec![1, 2, 3]).await.unwrap(), HashMap::from([(1, "ada".to_string()), (3, "user 3".to_string())]));
+    // a single key goes through MGET too
+    assert_eq!(batch(vec![3]).await.unwrap(), HashMap::from([(3, "user 3".to_string())]));
+    assert_eq!(batch_loads.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+#[ignore = "needs a Redis server, see REDIS_TEST_URL"]
+async fn in_process_caches_of_other_instances_are_invalidated() {
+    let redis = TestRedis::new().await;
+    let channel = redis.keys.local_invalidation_channel();
+    let (writer_local, reader_local) = (Arc::new(LocalCache::new(16, Duration::from_secs(60))), Arc::new(LocalCache::new(16, Duration::from_secs(60))));
+    let writer = CacheStore::new(redis.pool.clone()).with_local_cache(writer_local, channel.clone());
+    let reader = CacheStore::new(redis.pool.clone()).with_local_cache(reader_local.clone(), channel.clone());
+    let shutdown = Shutdown::default();
+    spawn_invalidation_listener(reader_local.clone(), redis_url(), channel, shutdown.clone());
+
+    // waits for the subscription, invalidations published before it are lost
+    let probe = redis.keys.entity_tag("probe", 1);
+    let mut subscribed = false;
+    for _ in 0..50 {
+        reader_local.insert("probe", &1, std::slice::from_ref(&probe));
+        invalidate_tags(&writer, std::slice::from_ref(&probe)).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        if reader_local.get::<i32>("probe").is_none() {
+            subscribed = true;
+            break;
+        }
+    }
+    assert!(subscribed, "the invalidation listener did not subscribe");
+
+    let tag = redis.keys.entity_tag("user_status", 1);
+    let key = redis.keys.entity("user_status", 1);
+    let read_through = ReadThrough::new(&redis.keys).with_local();
+    let value = read_through.get_or_load(&reader, &key, tagged(tag.clone()), || async { Result::Ok(Some("active".to_string())) }).await.unwrap();
+    assert_eq!(value.as_deref(), Some("active"));
+    assert_eq!(reader_local.get::<String>(&key).as_deref(), Some("active"));
+
+    assert_eq!(invalidate_tags(&writer, std::slice::from_ref(&tag)).await, Some(1));
+    let mut evicted = false;
+    for _ in 0..20 {
+        if reader_local.get::<String>(&key).is_none() {
+            evicted = true;
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    assert!(evicted, "the in-process entry of the other instance was not evicted");
+    shutdown.stop();
+}
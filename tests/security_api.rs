@@ -0,0 +1,70 @@
+mod support;
+
+use axum::http::StatusCode;
+use serde_json::json;
+
+use support::app::TestApp;
+
+
+#[tokio::test]
+async fn responses_carry_security_headers_and_a_request_id() {
+    let app = TestApp::new().await;
+
+    let generated = app.get("/healthz").send().await;
+    let propagated = app.get("/healthz").header("x-request-id", "trace-42").send().await;
+
+    assert_eq!(generated.headers["x-content-type-options"], "nosniff");
+    assert_eq!(generated.headers["x-frame-options"], "DENY");
+    assert_eq!(generated.headers["content-security-policy"], "default-src 'none'; frame-ancestors 'none'");
+    assert!(!generated.headers.contains_key("strict-transport-security"));
+    assert!(!generated.headers["x-request-id"].is_empty());
+    assert_eq!(propagated.headers["x-request-id"], "trace-42");
+}
+
+#[tokio::test]
+async fn hsts_is_sent_when_configured() {
+    let app = TestApp::with_config(|config| config.http.hsts_max_age_seconds = 600).await;
+
+    let response = app.get("/healthz").send().await;
+    assert_eq!(response.headers["strict-transport-security"], "max-age=600; includeSubDomains");
+}
+
+#[tokio::test]
+async fn cors_only_allows_the_configured_origins() {
+    let app = TestApp::new().await;
+    let preflight = |origin: &'static str| {
+        app.request(axum::http::Method::OPTIONS, "/api/user")
+            .header("origin", origin)
+            .header("access-control-request-method", "PATCH")
+            .send()
+    };
+
+    let allowed = preflight("https://shop.example.com").await;
+    let denied = preflight("https://evil.example.com").await;
+
+    assert_eq!(allowed.headers["access-control-allow-origin"], "https://shop.example.com");
+    assert!(allowed.headers["access-control-allow-methods"].to_str().unwrap().contains("PATCH"));
+    assert!(!denied.headers.contains_key("access-control-allow-origin"));
+}
+
+#[tokio::test]
+async fn oversized_bodies_are_rejected() {
+    let app = TestApp::new().await;
+    let body = json!({ "username": "x".repeat(128 * 1024) });
+
+    let response = app.post("/api/user").json(&body).send().await;
+    assert_eq!(response.status, StatusCode::PAYLOAD_TOO_LARGE);
+}
+
+#[tokio::test]
+async fn swagger_ui_can_be_disabled() {
+    let enabled = TestApp::new().await;
+    let disabled = TestApp::with_config(|config| config.http.swagger_ui = false).await;
+
+    let spec = enabled.get("/apidoc/v1/openapi.json").send().await;
+    assert_eq!(spec.status, StatusCode::OK);
+    assert!(spec.body["paths"].get("/readyz").is_some());
+    assert!(spec.headers["content-security-policy"].to_str().unwrap().starts_with("default-src 'self'"));
+    assert_eq!(disabled.get("/apidoc/v1/openapi.json").send().await.status, StatusCode::NOT_FOUND);
+    assert_eq!(disabled.get("/swagger-ui/").send().await.status, StatusCode::NOT_FOUND);
+}
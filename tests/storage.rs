@@ -0,0 +1,185 @@
+mod support;
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use axum::body::Bytes;
+use axum::extract::State;
+use axum::http::{header, HeaderMap, Method, StatusCode, Uri};
+use axum::response::IntoResponse;
+use axum::Router;
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+
+use e_commerce_system::shared::configuration::AppStorageS3Config;
+use e_commerce_system::shared::storage::blob_store::{Blob, BlobStore};
+use e_commerce_system::shared::storage::local::LocalBlobStore;
+use e_commerce_system::shared::storage::s3::S3BlobStore;
+
+
+const ACCESS_KEY: &str = "test-access-key";
+const SECRET_KEY: &str = "test-secret-key";
+const REGION: &str = "test-region";
+
+
+#[tokio::test]
+async fn local_store_round_trips_objects() {
+    let root = std::env::temp_dir().join(format!("e-commerce-system-storage-{}", uuid::Uuid::new_v4()));
+    let store = LocalBlobStore::new(&root, "http://localhost/media/");
+
+    store.put("users/1/profile/a.png", b"picture".to_vec(), "image/png").await.unwrap();
+    let blob = store.get("users/1/profile/a.png").await.unwrap().unwrap();
+    assert_eq!(blob.bytes, b"picture");
+    assert_eq!(blob.content_type.as_deref(), Some("image/png"));
+    assert!(!root.join("users/1/profile/a.part").exists());
+
+    store.delete("users/1/profile/a.png").await.unwrap();
+    assert!(store.get("users/1/profile/a.png").await.unwrap().is_none());
+    // deleting a missing object is not an error
+    store.delete("users/1/profile/a.png").await.unwrap();
+
+    assert_eq!(store.public_url("users/1/a.png"), "http://localhost/media/users/1/a.png");
+    assert_eq!(store.key_of("http://localhost/media/users/1/a.png").as_deref(), Some("users/1/a.png"));
+    assert_eq!(store.key_of("https://cdn.example.com/users/1/a.png"), None);
+}
+
+#[tokio::test]
+async fn keys_escaping_the_store_are_rejected() {
+    let store = LocalBlobStore::new(std::env::temp_dir(), "http://localhost/media");
+
+    for key in ["", "/etc/passwd", "users/../../etc/passwd", "users//a.png", "users/./a.png", "users/a b.png"] {
+        assert!(store.put(key, Vec::new(), "image/png").await.is_err(), "{}", key);
+        assert!(store.get(key).await.is_err(), "{}", key);
+    }
+    assert_eq!(store.key_of("http://localhost/media/users/../a.png"), None);
+}
+
+#[tokio::test]
+async fn s3_store_signs_its_requests() {
+    let stub = S3Stub::start().await;
+    let store = S3BlobStore::new(&stub.config(SECRET_KEY), "https://cdn.example.com");
+
+    store.put("users/1/profile/a.png", b"picture".to_vec(), "image/png").await.unwrap();
+    let blob = store.get("users/1/profile/a.png").await.unwrap().unwrap();
+    assert_eq!(blob.bytes, b"picture");
+    assert_eq!(blob.content_type.as_deref(), Some("image/png"));
+    assert!(store.get("users/1/profile/missing.png").await.unwrap().is_none());
+
+    store.delete("users/1/profile/a.png").await.unwrap();
+    assert!(stub.objects.lock().unwrap().is_empty());
+    assert_eq!(stub.paths(), [
+        "PUT /bucket/users/1/profile/a.png",
+        "GET /bucket/users/1/profile/a.png",
+        "GET /bucket/users/1/profile/missing.png",
+        "DELETE /bucket/users/1/profile/a.png",
+    ]);
+    assert_eq!(store.public_url("users/1/a.png"), "https://cdn.example.com/users/1/a.png");
+}
+
+#[tokio::test]
+async fn s3_store_fails_when_the_signature_is_rejected() {
+    let stub = S3Stub::start().await;
+    let store = S3BlobStore::new(&stub.config("wrong-secret"), "https://cdn.example.com");
+
+    assert!(store.put("users/1/profile/a.png", b"picture".to_vec(), "image/png").await.is_err());
+    assert!(store.get("users/1/profile/a.png").await.is_err());
+    assert!(stub.objects.lock().unwrap().is_empty());
+}
+
+
+/// In-process stand-in for an S3 bucket: checks the SigV4 signature of each request with
+/// `SECRET_KEY` and keeps the objects in memory.
+#[derive(Clone, Default)]
+struct S3Stub {
+    endpoint: String,
+    objects: Arc<Mutex<HashMap<String, Blob>>>,
+    requests: Arc<Mutex<Vec<String>>>,
+}
+
+impl S3Stub {
+    async fn start() -> Self {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let stub = Self { endpoint: format!("http://{}", listener.local_addr().unwrap()), ..Self::default() };
+        let router = Router::new().fallback(handle).with_state(stub.clone());
+        tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+        stub
+    }
+
+    fn config(&self, secret_key: &str) -> AppStorageS3Config {
+        AppStorageS3Config {
+            endpoint: self.endpoint.clone(),
+            bucket: "bucket".to_string(),
+            region: REGION.to_string(),
+            access_key: ACCESS_KEY.to_string(),
+            secret_key: secret_key.to_string(),
+        }
+    }
+
+    fn paths(&self) -> Vec<String> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+async fn handle(State(stub): State<S3Stub>, method: Method, uri: Uri, headers: HeaderMap, body: Bytes) -> impl IntoResponse {
+    if !signature_is_valid(&method, uri.path(), &headers, &body) {
+        return (StatusCode::FORBIDDEN, HeaderMap::new(), Vec::new());
+    }
+    stub.requests.lock().unwrap().push(format!("{} {}", method, uri.path()));
+
+    let mut objects = stub.objects.lock().unwrap();
+    let key = uri.path().to_string();
+    match method {
+        Method::PUT => {
+            let content_type = headers.get(header::CONTENT_TYPE).map(|value| value.to_str().unwrap().to_string());
+            objects.insert(key, Blob { bytes: body.to_vec(), content_type });
+            (StatusCode::OK, HeaderMap::new(), Vec::new())
+        },
+        Method::GET => match objects.get(&key) {
+            Some(blob) => {
+                let mut response_headers = HeaderMap::new();
+                if let Some(content_type) = &blob.content_type {
+                    response_headers.insert(header::CONTENT_TYPE, content_type.parse().unwrap());
+                }
+                (StatusCode::OK, response_headers, blob.bytes.clone())
+            },
+            None => (StatusCode::NOT_FOUND, HeaderMap::new(), Vec::new()),
+        },
+        Method::DELETE => {
+            objects.remove(&key);
+            (StatusCode::NO_CONTENT, HeaderMap::new(), Vec::new())
+        },
+        _ => (StatusCode::METHOD_NOT_ALLOWED, HeaderMap::new(), Vec::new()),
+    }
+}
+
+/// Verifies the request the way S3 does for the signed headers `host;x-amz-content-sha256;x-amz-date`.
+fn signature_is_valid(method: &Method, path: &str, headers: &HeaderMap, body: &[u8]) -> bool {
+    let header = |name: &str| headers.get(name).and_then(|value| value.to_str().ok()).unwrap_or_default().to_string();
+    let (host, payload_hash, amz_date, authorization) = (header("host"), header("x-amz-content-sha256"), header("x-amz-date"), header("authorization"));
+    if payload_hash != hex::encode(Sha256::digest(body)) || amz_date.len() < 8 {
+        return false;
+    }
+
+    let date = &amz_date[..8];
+    let scope = format!("{}/{}/s3/aws4_request", date, REGION);
+    let canonical_request = format!(
+        "{}\n{}\n\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\nhost;x-amz-content-sha256;x-amz-date\n{}",
+        method, path, host, payload_hash, amz_date, payload_hash
+    );
+    let string_to_sign = format!("AWS4-HMAC-SHA256\n{}\n{}\n{}", amz_date, scope, hex::encode(Sha256::digest(canonical_request.as_bytes())));
+
+    let hmac = |key: &[u8], data: &str| {
+        let mut mac = Hmac::<Sha256>::new_from_slice(key).unwrap();
+        mac.update(data.as_bytes());
+        mac.finalize().into_bytes().to_vec()
+    };
+    let signing_key = [date, REGION, "s3", "aws4_request"]
+        .iter()
+        .fold(format!("AWS4{}", SECRET_KEY).into_bytes(), |key, part| hmac(&key, part));
+    let signature = hex::encode(hmac(&signing_key, &string_to_sign));
+
+    authorization == format!(
+        "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders=host;x-amz-content-sha256;x-amz-date, Signature={}",
+        ACCESS_KEY, scope, signature
+    )
+}
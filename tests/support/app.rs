@@ -0,0 +1,304 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use axum::body::{to_bytes, Body};
+use axum::http::{header, HeaderMap, Method, Request, StatusCode};
+use axum::Router;
+use chrono::Utc;
+use jsonwebtoken::{encode, Algorithm, DecodingKey, EncodingKey, Header};
+use serde_json::Value;
+use sqlx::mysql::MySqlPoolOptions;
+use tower::ServiceExt;
+
+use e_commerce_system::app::build_router;
+use e_commerce_system::services::user::model::user_model::{
+    USER_AUTH_CUSTOMER, USER_AUTH_MANAGER, USER_STATUS_ACTIVE, USER_STATUS_DELETED,
+};
+use e_commerce_system::shared::cache::key::CacheKeys;
+use e_commerce_system::shared::cache::store::CacheStore;
+use e_commerce_system::shared::configuration::{
+    AppApiConfig, AppApiDeprecationConfig, AppConfig, AppConfigJWT, AppDatabaseConfig, AppHttpConfig, AppIdempotencyConfig, AppRateLimitConfig,
+    AppRateLimitPolicyConfig, AppRateLimitRuleConfig, AppShutdownConfig, AppStorageConfig,
+};
+use e_commerce_system::shared::database::mysql_pools::MySqlPools;
+use e_commerce_system::shared::health::checks::HealthChecks;
+use e_commerce_system::shared::http::versioning::UNVERSIONED;
+use e_commerce_system::shared::rate_limit::RateLimiter;
+use e_commerce_system::shared::shutdown::Shutdown;
+use e_commerce_system::shared::security::jwt::{Claims, JwtVerifier};
+use e_commerce_system::shared::state::services::Services;
+use e_commerce_system::shared::state::AppState;
+use e_commerce_system::shared::storage::local::LocalBlobStore;
+
+use crate::support::cache::MemoryCacheBackend;
+use crate::support::memory::MemoryDatabase;
+
+
+const JWT_SECRET: &[u8] = b"test-secret";
+const JWT_ISSUER: &str = "test-issuer";
+const JWT_AUDIENCE: &str = "test-audience";
+
+/// Requests per minute allowed on the password route.
+pub const STRICT_RATE_LIMIT: u32 = 5;
+
+
+/// Ids of the reference rows every test app starts with.
+pub struct Seed {
+    pub manager_auth: i64,
+    pub customer_auth: i64,
+    pub active_status: i64,
+    pub deleted_status: i64,
+}
+
+/// The application router over in-memory repositories and cache, driven without a socket.
+pub struct TestApp {
+    pub router: Router,
+    pub database: MemoryDatabase,
+    pub cache: Arc<MemoryCacheBackend>,
+    pub shutdown: Shutdown,
+    pub seed: Seed,
+    /// Root directory of the local blob store
+    pub storage_path: PathBuf,
+}
+
+impl TestApp {
+    pub async fn new() -> Self {
+        Self::with_config(|_| {}).await
+    }
+
+    /// Test app over the test configuration changed by `configure`.
+    pub async fn with_config(configure: impl FnOnce(&mut AppConfig)) -> Self {
+        let database = MemoryDatabase::default();
+        let seed = Seed {
+            manager_auth: database.insert_user_auth(USER_AUTH_MANAGER),
+            customer_auth: database.insert_user_auth(USER_AUTH_CUSTOMER),
+            active_status: database.insert_user_status(USER_STATUS_ACTIVE),
+            deleted_status: database.insert_user_status(USER_STATUS_DELETED),
+        };
+
+        let mut config = config();
+        configure(&mut config);
+        let cache_backend = Arc::new(MemoryCacheBackend::default());
+        let cache = CacheStore::with_backend(cache_backend.clone());
+        let cache_keys = CacheKeys::default();
+        let storage_path = PathBuf::from(&config.storage.local_path);
+        let blob_store = Arc::new(LocalBlobStore::new(&storage_path, &config.storage.public_base_url));
+        let repositories = database.repositories();
+        let services = Services::new(
+            &repositories,
+            Some(cache.clone()),
+            cache_keys.clone(),
+            blob_store.clone(),
+            config.storage.max_upload_bytes
+        );
+        // never connected, the in-memory repositories do not use it
+        let mysql_pool = MySqlPoolOptions::new().connect_lazy("mysql://localhost/unused").unwrap();
+
+        let health_checks = Arc::new(HealthChecks::new(mysql_pool.clone(), Some(cache.clone())));
+        let shutdown = Shutdown::default();
+        let rate_limiter = Arc::new(RateLimiter::from_config(&config.rate_limit).unwrap());
+        let app_state = AppState {
+            config,
+            mysql_pools: MySqlPools::new(mysql_pool, Vec::new()),
+            redis_pool: None,
+            cache: Some(cache),
+            cache_keys,
+            jwt_verifier: Arc::new(JwtVerifier::new(DecodingKey::from_secret(JWT_SECRET), Algorithm::HS256, JWT_ISSUER, JWT_AUDIENCE)),
+            blob_store,
+            repositories,
+            services,
+            health_checks,
+            shutdown: shutdown.clone(),
+            rate_limiter,
+        };
+
+        Self { router: build_router(app_state), database, cache: cache_backend, shutdown, seed, storage_path }
+    }
+
+    /// Bearer token of user `user_id` with the `auth` level.
+    pub fn token(&self, user_id: i64, auth: &str) -> String {
+        let now = Utc::now().timestamp();
+        let claims = Claims {
+            sub: user_id.to_string(),
+            auth: auth.to_string(),
+            iss: JWT_ISSUER.to_string(),
+            aud: JWT_AUDIENCE.to_string(),
+            exp: now + 3600,
+            iat: Some(now),
+        };
+        encode(&Header::new(Algorithm::HS256), &claims, &EncodingKey::from_secret(JWT_SECRET)).unwrap()
+    }
+
+    pub fn manager_token(&self) -> String {
+        self.token(0, USER_AUTH_MANAGER)
+    }
+
+    pub fn request(&self, method: Method, uri: &str) -> TestRequest<'_> {
+        TestRequest { app: self, builder: Request::builder().method(method).uri(uri), body: Body::empty() }
+    }
+
+    pub fn get(&self, uri: &str) -> TestRequest<'_> {
+        self.request(Method::GET, uri)
+    }
+
+    pub fn post(&self, uri: &str) -> TestRequest<'_> {
+        self.request(Method::POST, uri)
+    }
+
+    pub fn put(&self, uri: &str) -> TestRequest<'_> {
+        self.request(Method::PUT, uri)
+    }
+
+    pub fn patch(&self, uri: &str) -> TestRequest<'_> {
+        self.request(Method::PATCH, uri)
+    }
+
+    pub fn delete(&self, uri: &str) -> TestRequest<'_> {
+        self.request(Method::DELETE, uri)
+    }
+
+    /// Creates a user with `auth` and the active status through the API, returns its JSON.
+    pub async fn create_user(&self, username: &str, auth: i64) -> Value {
+        let response = self.post("/api/user")
+            .json(&serde_json::json!({
+                "first_name": "First",
+                "last_name": "Last",
+                "username": username,
+                "password": "secret-password",
+                "auth": auth,
+                "status": self.seed.active_status,
+                "title": "Engineer",
+                "country": "CM",
+            }))
+            .send()
+            .await;
+        assert_eq!(response.status, StatusCode::OK, "{}", response.body);
+        response.body
+    }
+}
+
+
+pub struct TestRequest<'a> {
+    app: &'a TestApp,
+    builder: axum::http::request::Builder,
+    body: Body,
+}
+
+impl TestRequest<'_> {
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.builder = self.builder.header(name, value);
+        self
+    }
+
+    pub fn bearer(self, token: &str) -> Self {
+        self.header(header::AUTHORIZATION.as_str(), &format!("Bearer {}", token))
+    }
+
+    pub fn if_match(self, etag: &str) -> Self {
+        self.header(header::IF_MATCH.as_str(), etag)
+    }
+
+    pub fn json(mut self, body: &Value) -> Self {
+        self.builder = self.builder.header(header::CONTENT_TYPE, "application/json");
+        self.body = Body::from(body.to_string());
+        self
+    }
+
+    /// `multipart/form-data` body with a single file part.
+    pub fn multipart(mut self, field: &str, file_name: &str, content: &[u8]) -> Self {
+        let boundary = "test-boundary";
+        let mut body = format!(
+            "--{boundary}\r\nContent-Disposition: form-data; name=\"{field}\"; filename=\"{file_name}\"\r\nContent-Type: application/octet-stream\r\n\r\n"
+        ).into_bytes();
+        body.extend_from_slice(content);
+        body.extend_from_slice(format!("\r\n--{boundary}--\r\n").as_bytes());
+
+        self.builder = self.builder.header(header::CONTENT_TYPE, format!("multipart/form-data; boundary={}", boundary));
+        self.body = Body::from(body);
+        self
+    }
+
+    pub async fn send(self) -> TestResponse {
+        let request = self.builder.body(self.body).unwrap();
+        let response = self.app.router.clone().oneshot(request).await.unwrap();
+
+        let status = response.status();
+        let headers = response.headers().clone();
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body = serde_json::from_slice(&bytes)
+            .unwrap_or_else(|_| Value::String(String::from_utf8_lossy(&bytes).into_owned()));
+        TestResponse { status, headers, body }
+    }
+}
+
+
+pub struct TestResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    /// JSON body, or the raw text as a JSON string when it is not JSON
+    pub body: Value,
+}
+
+impl TestResponse {
+    pub fn etag(&self) -> String {
+        self.headers.get(header::ETAG).expect("ETag header").to_str().unwrap().to_string()
+    }
+}
+
+
+fn config() -> AppConfig {
+    AppConfig {
+        environment: "test".to_string(),
+        log_level: "info".to_string(),
+        jwt: AppConfigJWT {
+            private_secret_pem_path: None,
+            public_secret_pem_path: String::new(),
+            issuer: JWT_ISSUER.to_string(),
+            audience: JWT_AUDIENCE.to_string(),
+            expires_in_minutes: 60,
+            kid: None,
+        },
+        database: AppDatabaseConfig { mysql: None, mongo: None, redis: None, neo4j: None },
+        storage: AppStorageConfig {
+            backend: "local".to_string(),
+            local_path: std::env::temp_dir().join(format!("e-commerce-system-test-{}", uuid::Uuid::new_v4())).display().to_string(),
+            public_base_url: "http://localhost/media".to_string(),
+            max_upload_bytes: 1024 * 1024,
+            s3: None,
+        },
+        bind_addr: "127.0.0.1:0".to_string(),
+        shutdown: AppShutdownConfig { readiness_delay_seconds: 0, drain_timeout_seconds: 1 },
+        rate_limit: AppRateLimitConfig {
+            enabled: true,
+            trust_forwarded_for: false,
+            api_key_hashes: Vec::new(),
+            default_policy: "default".to_string(),
+            policies: BTreeMap::from([
+                ("default".to_string(), AppRateLimitPolicyConfig { limit: 120, window_seconds: 60 }),
+                ("strict".to_string(), AppRateLimitPolicyConfig { limit: STRICT_RATE_LIMIT, window_seconds: 60 }),
+                ("read".to_string(), AppRateLimitPolicyConfig { limit: 600, window_seconds: 60 }),
+            ]),
+            rules: vec![
+                AppRateLimitRuleConfig { policy: "strict".to_string(), path: Some("/api/user/{user_id}/password".to_string()), methods: Vec::new() },
+                AppRateLimitRuleConfig { policy: "read".to_string(), path: None, methods: vec!["GET".to_string(), "HEAD".to_string()] },
+            ],
+        },
+        idempotency: AppIdempotencyConfig { ttl_seconds: 60, lock_seconds: 2 },
+        http: AppHttpConfig {
+            cors_allowed_origins: vec!["https://shop.example.com".to_string()],
+            cors_allow_credentials: false,
+            cors_max_age_seconds: 600,
+            max_body_bytes: 64 * 1024,
+            request_timeout_seconds: 5,
+            hsts_max_age_seconds: 0,
+            swagger_ui: true,
+        },
+        api: AppApiConfig {
+            deprecations: BTreeMap::from([(UNVERSIONED.to_string(), AppApiDeprecationConfig {
+                since: "2026-10-19T00:00:00Z".parse().unwrap(),
+                sunset: Some("2027-04-30T00:00:00Z".parse().unwrap()),
+            })]),
+        },
+    }
+}
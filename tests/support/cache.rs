@@ -0,0 +1,165 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use redis::RedisResult;
+
+use e_commerce_system::shared::cache::backend::{CacheBackend, TokenBucket};
+
+
+enum Value {
+    String(String),
+    Set(HashSet<String>),
+    Bucket { tokens: f64, at: Instant },
+}
+
+struct Entry {
+    value: Value,
+    expires_at: Option<Instant>,
+}
+
+impl Entry {
+    fn is_live(&self) -> bool {
+        self.expires_at.is_none_or(|expires_at| expires_at > Instant::now())
+    }
+}
+
+
+/// Fake Redis: the operations of [`CacheBackend`] over a map, with the same expiry and tag rules.
+#[derive(Default)]
+pub struct MemoryCacheBackend {
+    entries: Mutex<HashMap<String, Entry>>,
+    published: Mutex<Vec<(String, String)>>,
+}
+
+impl MemoryCacheBackend {
+    /// Live string keys, tag sets and locks excluded.
+    pub fn keys(&self) -> Vec<String> {
+        let entries = self.entries.lock().unwrap();
+        let mut keys: Vec<String> = entries.iter()
+            .filter(|(key, entry)| entry.is_live() && !key.contains(":lock:") && matches!(entry.value, Value::String(_)))
+            .map(|(key, _)| key.clone())
+            .collect();
+        keys.sort();
+        keys
+    }
+
+    pub fn contains(&self, key: &str) -> bool {
+        self.entries.lock().unwrap().get(key).is_some_and(Entry::is_live)
+    }
+
+    /// Evicts `key`, as if it had expired.
+    pub fn remove(&self, key: &str) {
+        self.entries.lock().unwrap().remove(key);
+    }
+
+    /// Messages published so far, as `(channel, message)`.
+    pub fn published(&self) -> Vec<(String, String)> {
+        self.published.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl CacheBackend for MemoryCacheBackend {
+    async fn ping(&self) -> RedisResult<()> {
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> RedisResult<Option<String>> {
+        let entries = self.entries.lock().unwrap();
+        Ok(match entries.get(key) {
+            Some(Entry { value: Value::String(value), .. }) if entries[key].is_live() => Some(value.clone()),
+            _ => None,
+        })
+    }
+
+    async fn get_many(&self, keys: &[&str]) -> RedisResult<Vec<Option<String>>> {
+        let mut values = Vec::with_capacity(keys.len());
+        for key in keys {
+            values.push(self.get(key).await?);
+        }
+        Ok(values)
+    }
+
+    async fn set_if_absent(&self, key: &str, value: &str, ttl: Duration) -> RedisResult<bool> {
+        let mut entries = self.entries.lock().unwrap();
+        if entries.get(key).is_some_and(Entry::is_live) {
+            return Ok(false);
+        }
+        entries.insert(key.to_string(), Entry { value: Value::String(value.to_string()), expires_at: Some(Instant::now() + ttl) });
+        Ok(true)
+    }
+
+    async fn delete_if_equals(&self, key: &str, value: &str) -> RedisResult<bool> {
+        let mut entries = self.entries.lock().unwrap();
+        let owned = matches!(entries.get(key), Some(Entry { value: Value::String(current), .. }) if current == value);
+        if owned {
+            entries.remove(key);
+        }
+        Ok(owned)
+    }
+
+    async fn set_tagged(&self, key: &str, value: &str, ttl_seconds: u64, tag_keys: &[&str]) -> RedisResult<()> {
+        let expires_at = (ttl_seconds > 0).then(|| Instant::now() + Duration::from_secs(ttl_seconds));
+        let mut entries = self.entries.lock().unwrap();
+        entries.insert(key.to_string(), Entry { value: Value::String(value.to_string()), expires_at });
+
+        for tag_key in tag_keys {
+            let tag = entries.entry(tag_key.to_string())
+                .and_modify(|tag| if !tag.is_live() { *tag = Entry { value: Value::Set(HashSet::new()), expires_at } })
+                .or_insert(Entry { value: Value::Set(HashSet::new()), expires_at });
+            if let Value::Set(members) = &mut tag.value {
+                members.insert(key.to_string());
+            }
+            // tag sets live at least as long as the keys they reference
+            tag.expires_at = match (tag.expires_at, expires_at) {
+                (Some(current), Some(expires_at)) => Some(current.max(expires_at)),
+                _ => None,
+            };
+        }
+        Ok(())
+    }
+
+    async fn invalidate_tags(&self, tag_keys: &[&str], notification: Option<(&str, &str)>) -> RedisResult<i64> {
+        let mut removed = 0;
+        let mut entries = self.entries.lock().unwrap();
+        for tag_key in tag_keys {
+            if let Some(Entry { value: Value::Set(members), .. }) = entries.remove(*tag_key) {
+                for member in members {
+                    removed += i64::from(entries.remove(&member).is_some());
+                }
+            }
+        }
+        if let Some((channel, message)) = notification {
+            self.published.lock().unwrap().push((channel.to_string(), message.to_string()));
+        }
+        Ok(removed)
+    }
+
+    async fn take_token(&self, key: &str, capacity: u32, window: Duration) -> RedisResult<TokenBucket> {
+        let mut entries = self.entries.lock().unwrap();
+        let now = Instant::now();
+        let capacity = capacity as f64;
+        let rate = capacity / window.as_millis().max(1) as f64;
+
+        let mut tokens = match entries.get(key) {
+            Some(entry @ Entry { value: Value::Bucket { tokens, at }, .. }) if entry.is_live() => {
+                (tokens + now.duration_since(*at).as_millis() as f64 * rate).min(capacity)
+            },
+            _ => capacity,
+        };
+        let allowed = tokens >= 1.0;
+        if allowed {
+            tokens -= 1.0;
+        }
+        entries.insert(key.to_string(), Entry { value: Value::Bucket { tokens, at: now }, expires_at: Some(now + window) });
+
+        Ok(TokenBucket {
+            allowed,
+            remaining: tokens.floor() as u32,
+            reset_after: Duration::from_millis(((capacity - tokens) / rate).ceil() as u64),
+            retry_after: if allowed { Duration::ZERO } else { Duration::from_millis(((1.0 - tokens) / rate).ceil() as u64) },
+        })
+    }
+}
@@ -0,0 +1,356 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use anyhow::{Error, Result};
+use async_trait::async_trait;
+use chrono::Utc;
+
+use e_commerce_system::services::user::model::user_model::{User, UserAuth, UserStatus};
+use e_commerce_system::services::user::repository::user_auth_repo::UserAuthRepositoryInterface;
+use e_commerce_system::services::user::repository::user_repo::UserRepositoryInterface;
+use e_commerce_system::services::user::repository::user_status_repo::UserStatusRepositoryInterface;
+use e_commerce_system::shared::database::mysql::DuplicateKey;
+use e_commerce_system::shared::database::mysql_pools::MySqlPools;
+use e_commerce_system::shared::database::unit_of_work::UnitOfWork;
+use e_commerce_system::shared::state::services::Repositories;
+
+
+#[derive(Default)]
+struct Tables {
+    users: BTreeMap<i64, User>,
+    user_auths: BTreeMap<i64, UserAuth>,
+    user_statuses: BTreeMap<i64, UserStatus>,
+    last_id: i64,
+    batch_lookups: Vec<Vec<i64>>,
+    concurrent_writes: Vec<i64>,
+}
+
+impl Tables {
+    fn next_id(&mut self) -> i64 {
+        self.last_id += 1;
+        self.last_id
+    }
+
+    /// A user as returned by `user_view`, with its auth and status rows.
+    fn view(&self, user: &User) -> User {
+        let auth = self.user_auths.get(&user.auth);
+        let status = self.user_statuses.get(&user.status);
+        User {
+            user_auth: auth.cloned(),
+            user_status: status.cloned(),
+            ..user.clone()
+        }
+    }
+
+    fn select(&self, filter: impl Fn(&User) -> bool, limit: Option<u32>, offset: Option<u32>) -> Vec<User> {
+        let users = self.users.values().filter(|user| filter(user)).map(|user| self.view(user));
+        // the procedures only paginate when both values are given
+        match (limit, offset) {
+            (Some(limit), Some(offset)) => users.skip(offset as usize).take(limit as usize).collect(),
+            _ => users.collect(),
+        }
+    }
+
+    /// Applies the writes queued by `MemoryDatabase::write_concurrently` before a versioned write.
+    fn apply_concurrent_writes(&mut self) {
+        for user_id in std::mem::take(&mut self.concurrent_writes) {
+            self.update_user(user_id, |_| ());
+        }
+    }
+
+    fn update_user(&mut self, user_id: i64, update: impl FnOnce(&mut User)) -> Option<User> {
+        let user = self.users.get_mut(&user_id)?;
+        update(user);
+        user.version = Some(user.version.unwrap_or_default() + 1);
+        user.updated_at = Some(Utc::now());
+        let user = user.clone();
+        Some(self.view(&user))
+    }
+}
+
+
+/// Tables of the user service kept in memory, shared by the repositories built from it.
+#[derive(Clone, Default)]
+pub struct MemoryDatabase {
+    tables: Arc<Mutex<Tables>>,
+}
+
+impl MemoryDatabase {
+    pub fn repositories(&self) -> Repositories {
+        Repositories {
+            user: Arc::new(MemoryUserRepository { database: self.clone() }),
+            user_auth: Arc::new(MemoryUserAuthRepository { database: self.clone() }),
+            user_status: Arc::new(MemoryUserStatusRepository { database: self.clone() }),
+        }
+    }
+
+    fn tables(&self) -> MutexGuard<'_, Tables> {
+        self.tables.lock().unwrap()
+    }
+
+    pub fn insert_user_auth(&self, name: &str) -> i64 {
+        let mut tables = self.tables();
+        let id = tables.next_id();
+        tables.user_auths.insert(id, UserAuth { id: Some(id), version: Some(1), ..UserAuth::new(name.to_string(), None) });
+        id
+    }
+
+    pub fn insert_user_status(&self, name: &str) -> i64 {
+        let mut tables = self.tables();
+        let id = tables.next_id();
+        tables.user_statuses.insert(id, UserStatus { id: Some(id), version: Some(1), ..UserStatus::new(name.to_string(), None) });
+        id
+    }
+
+    pub fn user(&self, user_id: i64) -> Option<User> {
+        self.tables().users.get(&user_id).cloned()
+    }
+
+    /// Changes a stored user behind the back of the API, bumping its version as a write does.
+    pub fn update_user(&self, user_id: i64, update: impl FnOnce(&mut User)) {
+        self.tables().update_user(user_id, update);
+    }
+
+    /// Bumps the version of the user right before the next versioned write, as another request
+    /// writing between the read of the service and its write would.
+    pub fn write_concurrently(&self, user_id: i64) {
+        self.tables().concurrent_writes.push(user_id);
+    }
+
+    /// Ids of each `get_users_by_ids` call so far.
+    pub fn batch_lookups(&self) -> Vec<Vec<i64>> {
+        self.tables().batch_lookups.clone()
+    }
+}
+
+
+pub struct MemoryUserRepository {
+    database: MemoryDatabase,
+}
+
+#[async_trait]
+impl UserRepositoryInterface for MemoryUserRepository {
+    fn on_primary(&self) -> Arc<dyn UserRepositoryInterface> {
+        Arc::new(Self { database: self.database.clone() })
+    }
+
+    fn in_unit_of_work(&self, _: &UnitOfWork) -> Arc<dyn UserRepositoryInterface> {
+        self.on_primary()
+    }
+
+    fn unit_of_work_pools(&self) -> Option<&MySqlPools> {
+        None
+    }
+
+    async fn get_user(&self, user_id: i64) -> Result<Option<User>, Error> {
+        let tables = self.database.tables();
+        Ok(tables.users.get(&user_id).map(|user| tables.view(user)))
+    }
+
+    async fn get_user_for_update(&self, user_id: i64) -> Result<Option<User>, Error> {
+        self.get_user(user_id).await
+    }
+
+    async fn create_user(&self, user: User) -> Result<User, Error> {
+        let mut tables = self.database.tables();
+        // the unique index on `user.username`
+        if tables.users.values().any(|existing| existing.username == user.username) {
+            return Err(Error::new(DuplicateKey(format!("Duplicate entry '{}' for key 'user.username'", user.username))));
+        }
+        let id = tables.next_id();
+        let now = Some(Utc::now());
+        let user = User { id: Some(id), version: Some(1), created_at: now, updated_at: now, ..user };
+        tables.users.insert(id, user.clone());
+        Ok(tables.view(&user))
+    }
+
+    async fn update_user(&self, user_id: i64, version: i64, user: User) -> Result<Option<User>, Error> {
+        let mut tables = self.database.tables();
+        tables.apply_concurrent_writes();
+        if tables.users.get(&user_id).and_then(|current| current.version) != Some(version) {
+            return Ok(None);
+        }
+        Ok(tables.update_user(user_id, |current| {
+            current.first_name = user.first_name;
+            current.last_name = user.last_name;
+            current.hired_date = user.hired_date;
+            current.title = user.title;
+            current.address = user.address;
+            current.country = user.country;
+            current.phone = user.phone;
+        }))
+    }
+
+    async fn update_user_password(&self, user_id: i64, user_password: Option<String>) -> Result<Option<User>, Error> {
+        Ok(self.database.tables().update_user(user_id, |current| current.password = user_password.unwrap_or_default()))
+    }
+
+    async fn update_user_profile_pic_url(&self, user_id: i64, profile_pic_url: Option<String>) -> Result<Option<User>, Error> {
+        Ok(self.database.tables().update_user(user_id, |current| current.profile_pic_url = profile_pic_url))
+    }
+
+    async fn update_user_status(&self, user_id: i64, status: i64) -> Result<Option<User>, Error> {
+        Ok(self.database.tables().update_user(user_id, |current| current.status = status))
+    }
+
+    async fn delete_user(&self, user_id: i64, status: i64, version: i64) -> Result<bool, Error> {
+        let mut tables = self.database.tables();
+        tables.apply_concurrent_writes();
+        if tables.users.get(&user_id).is_none_or(|current| current.version != Some(version)) {
+            return Ok(false);
+        }
+        tables.update_user(user_id, |current| current.status = status);
+        Ok(true)
+    }
+
+    async fn get_all_users(&self, limit: Option<u32>, offset: Option<u32>) -> Result<Vec<User>, Error> {
+        Ok(self.database.tables().select(|_| true, limit, offset))
+    }
+
+    async fn get_users_by_ids(&self, user_ids: Vec<i64>) -> Result<Vec<User>, Error> {
+        let mut tables = self.database.tables();
+        let users = tables.select(|user| user.id.is_some_and(|id| user_ids.contains(&id)), None, None);
+        tables.batch_lookups.push(user_ids);
+        Ok(users)
+    }
+
+    async fn get_user_by_username(&self, username: String) -> Result<Option<User>, Error> {
+        Ok(self.database.tables().select(|user| user.username == username, None, None).pop())
+    }
+
+    async fn get_user_by_title(&self, title: String, limit: Option<u32>, offset: Option<u32>) -> Result<Vec<User>, Error> {
+        Ok(self.database.tables().select(|user| user.title.as_ref() == Some(&title), limit, offset))
+    }
+
+    async fn get_user_by_country(&self, country: String, limit: Option<u32>, offset: Option<u32>) -> Result<Vec<User>, Error> {
+        Ok(self.database.tables().select(|user| user.country.as_ref() == Some(&country), limit, offset))
+    }
+
+    async fn get_user_by_country_and_or_title(&self, country: Option<String>, title: Option<String>, limit: Option<u32>, offset: Option<u32>) -> Result<Vec<User>, Error> {
+        let filter = |user: &User| {
+            country.as_ref().is_none_or(|country| user.country.as_ref() == Some(country))
+                && title.as_ref().is_none_or(|title| user.title.as_ref() == Some(title))
+        };
+        Ok(self.database.tables().select(filter, limit, offset))
+    }
+
+    async fn search_user_by_username(&self, username: String, limit: Option<u32>, offset: Option<u32>) -> Result<Vec<User>, Error> {
+        Ok(self.database.tables().select(|user| user.username.starts_with(&username), limit, offset))
+    }
+
+    async fn search_user_by_title(&self, title: String, limit: Option<u32>, offset: Option<u32>) -> Result<Vec<User>, Error> {
+        Ok(self.database.tables().select(|user| user.title.as_ref().is_some_and(|value| value.starts_with(&title)), limit, offset))
+    }
+
+    async fn search_user_by_country(&self, country: String, limit: Option<u32>, offset: Option<u32>) -> Result<Vec<User>, Error> {
+        Ok(self.database.tables().select(|user| user.country.as_ref().is_some_and(|value| value.starts_with(&country)), limit, offset))
+    }
+
+    async fn get_user_by_auth(&self, auth: i64, limit: Option<u32>, offset: Option<u32>) -> Result<Vec<User>, Error> {
+        Ok(self.database.tables().select(|user| user.auth == auth, limit, offset))
+    }
+
+    async fn get_user_by_status(&self, status: i64, limit: Option<u32>, offset: Option<u32>) -> Result<Vec<User>, Error> {
+        Ok(self.database.tables().select(|user| user.status == status, limit, offset))
+    }
+}
+
+
+pub struct MemoryUserAuthRepository {
+    database: MemoryDatabase,
+}
+
+#[async_trait]
+impl UserAuthRepositoryInterface for MemoryUserAuthRepository {
+    fn on_primary(&self) -> Arc<dyn UserAuthRepositoryInterface> {
+        Arc::new(Self { database: self.database.clone() })
+    }
+
+    async fn get_user_auth(&self, user_auth_id: i64) -> Result<Option<UserAuth>, Error> {
+        Ok(self.database.tables().user_auths.get(&user_auth_id).cloned())
+    }
+
+    async fn get_user_auth_by_name(&self, name: String) -> Result<Option<UserAuth>, Error> {
+        Ok(self.database.tables().user_auths.values().find(|user_auth| user_auth.name == name).cloned())
+    }
+
+    async fn create_user_auth(&self, user_auth: UserAuth) -> Result<UserAuth, Error> {
+        let mut tables = self.database.tables();
+        let id = tables.next_id();
+        let user_auth = UserAuth { id: Some(id), version: Some(1), ..user_auth };
+        tables.user_auths.insert(id, user_auth.clone());
+        Ok(user_auth)
+    }
+
+    async fn update_user_auth(&self, user_auth_id: i64, version: i64, user_auth: UserAuth) -> Result<Option<UserAuth>, Error> {
+        let mut tables = self.database.tables();
+        let Some(current) = tables.user_auths.get_mut(&user_auth_id).filter(|current| current.version == Some(version)) else {
+            return Ok(None);
+        };
+        *current = UserAuth { id: Some(user_auth_id), version: Some(version + 1), ..user_auth };
+        Ok(Some(current.clone()))
+    }
+
+    async fn delete_user_auth(&self, user_auth_id: i64, version: i64) -> Result<bool, Error> {
+        let mut tables = self.database.tables();
+        if tables.user_auths.get(&user_auth_id).is_none_or(|current| current.version != Some(version)) {
+            return Ok(false);
+        }
+        tables.user_auths.remove(&user_auth_id);
+        Ok(true)
+    }
+
+    async fn get_all_user_auths(&self) -> Result<Vec<UserAuth>, Error> {
+        Ok(self.database.tables().user_auths.values().cloned().collect())
+    }
+}
+
+
+pub struct MemoryUserStatusRepository {
+    database: MemoryDatabase,
+}
+
+#[async_trait]
+impl UserStatusRepositoryInterface for MemoryUserStatusRepository {
+    fn on_primary(&self) -> Arc<dyn UserStatusRepositoryInterface> {
+        Arc::new(Self { database: self.database.clone() })
+    }
+
+    async fn get_user_status(&self, user_status_id: i64) -> Result<Option<UserStatus>, Error> {
+        Ok(self.database.tables().user_statuses.get(&user_status_id).cloned())
+    }
+
+    async fn get_user_status_by_name(&self, name: String) -> Result<Option<UserStatus>, Error> {
+        Ok(self.database.tables().user_statuses.values().find(|user_status| user_status.name == name).cloned())
+    }
+
+    async fn create_user_status(&self, user_status: UserStatus) -> Result<UserStatus, Error> {
+        let mut tables = self.database.tables();
+        let id = tables.next_id();
+        let user_status = UserStatus { id: Some(id), version: Some(1), ..user_status };
+        tables.user_statuses.insert(id, user_status.clone());
+        Ok(user_status)
+    }
+
+    async fn update_user_status(&self, user_status_id: i64, version: i64, user_status: UserStatus) -> Result<Option<UserStatus>, Error> {
+        let mut tables = self.database.tables();
+        let Some(current) = tables.user_statuses.get_mut(&user_status_id).filter(|current| current.version == Some(version)) else {
+            return Ok(None);
+        };
+        *current = UserStatus { id: Some(user_status_id), version: Some(version + 1), ..user_status };
+        Ok(Some(current.clone()))
+    }
+
+    async fn delete_user_status(&self, user_status_id: i64, version: i64) -> Result<bool, Error> {
+        let mut tables = self.database.tables();
+        if tables.user_statuses.get(&user_status_id).is_none_or(|current| current.version != Some(version)) {
+            return Ok(false);
+        }
+        tables.user_statuses.remove(&user_status_id);
+        Ok(true)
+    }
+
+    async fn get_all_user_status(&self) -> Result<Vec<UserStatus>, Error> {
+        Ok(self.database.tables().user_statuses.values().cloned().collect())
+    }
+}
@@ -0,0 +1,8 @@
+//! Offline test harness: the full router over in-memory repositories and an in-memory cache.
+#![allow(dead_code)]
+
+pub mod app;
+pub mod cache;
+pub mod images;
+pub mod memory;
+pub mod mysql;
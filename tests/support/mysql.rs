@@ -0,0 +1,255 @@
+//! In-process stand-in for a MySQL server, speaking just enough of the protocol for sqlx.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+use sqlx::mysql::MySqlPoolOptions;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+use e_commerce_system::shared::database::mysql_pools::MySqlPools;
+
+
+/// Column types of scripted result sets.
+pub const BIGINT: u8 = 0x08;
+pub const DATETIME: u8 = 0x0c;
+pub const VARCHAR: u8 = 0xfd;
+
+const COM_QUIT: u8 = 0x01;
+const COM_QUERY: u8 = 0x03;
+const COM_STMT_PREPARE: u8 = 0x16;
+const COM_STMT_EXECUTE: u8 = 0x17;
+const COM_STMT_CLOSE: u8 = 0x19;
+
+const CLIENT_MYSQL: u32 = 0x1;
+const CLIENT_PROTOCOL_41: u32 = 0x200;
+const CLIENT_TRANSACTIONS: u32 = 0x2000;
+const CLIENT_SECURE_CONNECTION: u32 = 0x8000;
+const CLIENT_PLUGIN_AUTH: u32 = 0x80000;
+
+const SERVER_STATUS_IN_TRANS: u16 = 0x1;
+const SERVER_STATUS_AUTOCOMMIT: u16 = 0x2;
+
+const COLLATION_UTF8MB4: u16 = 255;
+const COLLATION_BINARY: u16 = 63;
+
+
+/// Accepts any user and answers every statement with OK, unless an error or a result set was
+/// scripted for it, recording the statements it was sent.
+///
+/// Result sets are only sent through the text protocol, so tests fetch them with a plain `&str`
+/// rather than `sqlx::query`.
+#[derive(Clone, Default)]
+pub struct MySqlStub {
+    url: String,
+    statements: Arc<Mutex<Vec<String>>>,
+    errors: Arc<Mutex<HashMap<String, VecDeque<u16>>>>,
+    result_sets: Arc<Mutex<HashMap<String, ResultSet>>>,
+}
+
+#[derive(Clone)]
+struct ResultSet {
+    columns: Vec<(String, u8)>,
+    rows: Vec<Vec<Option<String>>>,
+}
+
+impl MySqlStub {
+    pub async fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let stub = Self { url: format!("mysql://root@{}/app?ssl-mode=disabled", listener.local_addr().unwrap()), ..Self::default() };
+        let server = stub.clone();
+        tokio::spawn(async move {
+            while let Ok((socket, _)) = listener.accept().await {
+                tokio::spawn(server.clone().serve(socket));
+            }
+        });
+        stub
+    }
+
+    /// Single connection, so that every statement of a test goes through the same session.
+    pub fn pools(&self) -> MySqlPools {
+        MySqlPools::new(MySqlPoolOptions::new().max_connections(1).connect_lazy(&self.url).unwrap(), Vec::new())
+    }
+
+    /// Makes the next executions of `statement` fail with the MySQL error numbers of `errors`, in order.
+    pub fn fail(&self, statement: &str, errors: &[u16]) {
+        self.errors.lock().unwrap().entry(statement.to_string()).or_default().extend(errors);
+    }
+
+    /// Answers `statement` with `rows`, `None` values being `NULL`.
+    pub fn returns(&self, statement: &str, columns: &[(&str, u8)], rows: &[&[Option<&str>]]) {
+        let result_set = ResultSet {
+            columns: columns.iter().map(|(name, column_type)| (name.to_string(), *column_type)).collect(),
+            rows: rows.iter().map(|row| row.iter().map(|value| value.map(str::to_string)).collect()).collect(),
+        };
+        self.result_sets.lock().unwrap().insert(statement.to_string(), result_set);
+    }
+
+    pub fn statements(&self) -> Vec<String> {
+        self.statements.lock().unwrap().clone()
+    }
+
+    async fn serve(self, mut socket: TcpStream) {
+        write_packet(&mut socket, 0, &handshake()).await;
+        // user, database and auth plugin of the handshake response are all accepted
+        if read_packet(&mut socket).await.is_none() {
+            return;
+        }
+        write_packet(&mut socket, 2, &ok(false)).await;
+
+        let mut in_transaction = false;
+        let mut prepared = HashMap::new();
+        while let Some(packet) = read_packet(&mut socket).await {
+            let replies = match packet[0] {
+                COM_QUIT => return,
+                COM_STMT_CLOSE => continue,
+                COM_QUERY => match String::from_utf8_lossy(&packet[1..]).to_string() {
+                    // session variables sqlx sets on connect
+                    statement if statement.starts_with("SET ") => vec![ok(in_transaction)],
+                    statement => self.run(statement, &mut in_transaction),
+                },
+                COM_STMT_PREPARE => {
+                    let id = prepared.len() as u32 + 1;
+                    prepared.insert(id, String::from_utf8_lossy(&packet[1..]).to_string());
+                    vec![prepare_ok(id)]
+                },
+                COM_STMT_EXECUTE => {
+                    let id = u32::from_le_bytes(packet[1..5].try_into().unwrap());
+                    self.run(prepared[&id].clone(), &mut in_transaction)
+                },
+                _ => vec![ok(in_transaction)],
+            };
+            for (sequence, reply) in replies.iter().enumerate() {
+                write_packet(&mut socket, sequence as u8 + 1, reply).await;
+            }
+        }
+    }
+
+    fn run(&self, statement: String, in_transaction: &mut bool) -> Vec<Vec<u8>> {
+        let error = self.errors.lock().unwrap().get_mut(&statement).and_then(VecDeque::pop_front);
+        let result_set = self.result_sets.lock().unwrap().get(&statement).cloned();
+        self.statements.lock().unwrap().push(statement.clone());
+        if let Some(number) = error {
+            return vec![err(number)];
+        }
+
+        match statement.as_str() {
+            "BEGIN" => *in_transaction = true,
+            "COMMIT" | "ROLLBACK" => *in_transaction = false,
+            _ => {},
+        }
+        match result_set {
+            Some(result_set) => result_set.packets(*in_transaction),
+            None => vec![ok(*in_transaction)],
+        }
+    }
+}
+
+impl ResultSet {
+    /// Column count, column definitions, EOF, text rows and EOF.
+    fn packets(&self, in_transaction: bool) -> Vec<Vec<u8>> {
+        let mut packets = vec![vec![self.columns.len() as u8]];
+        packets.extend(self.columns.iter().map(|(name, column_type)| column_definition(name, *column_type)));
+        packets.push(eof(in_transaction));
+        packets.extend(self.rows.iter().map(|row| {
+            row.iter().fold(Vec::new(), |mut packet, value| {
+                match value {
+                    Some(value) => lenenc(&mut packet, value.as_bytes()),
+                    None => packet.push(0xfb),
+                }
+                packet
+            })
+        }));
+        packets.push(eof(in_transaction));
+        packets
+    }
+}
+
+async fn read_packet(socket: &mut TcpStream) -> Option<Vec<u8>> {
+    let mut header = [0; 4];
+    socket.read_exact(&mut header).await.ok()?;
+    let mut payload = vec![0; u32::from_le_bytes([header[0], header[1], header[2], 0]) as usize];
+    socket.read_exact(&mut payload).await.ok()?;
+    Some(payload)
+}
+
+async fn write_packet(socket: &mut TcpStream, sequence: u8, payload: &[u8]) {
+    let mut packet = (payload.len() as u32).to_le_bytes()[..3].to_vec();
+    packet.push(sequence);
+    packet.extend_from_slice(payload);
+    let _ = socket.write_all(&packet).await;
+}
+
+/// Protocol 10 greeting offering `mysql_native_password` and no TLS.
+fn handshake() -> Vec<u8> {
+    let capabilities = CLIENT_MYSQL | CLIENT_PROTOCOL_41 | CLIENT_TRANSACTIONS | CLIENT_SECURE_CONNECTION | CLIENT_PLUGIN_AUTH;
+    let mut packet = vec![10];
+    packet.extend_from_slice(b"8.0.36\0");
+    packet.extend_from_slice(&1u32.to_le_bytes());
+    packet.extend_from_slice(b"abcdefgh\0");
+    packet.extend_from_slice(&(capabilities as u16).to_le_bytes());
+    packet.push(COLLATION_UTF8MB4 as u8);
+    packet.extend_from_slice(&SERVER_STATUS_AUTOCOMMIT.to_le_bytes());
+    packet.extend_from_slice(&((capabilities >> 16) as u16).to_le_bytes());
+    packet.push(21);
+    packet.extend_from_slice(&[0; 10]);
+    packet.extend_from_slice(b"ijklmnopqrst\0");
+    packet.extend_from_slice(b"mysql_native_password\0");
+    packet
+}
+
+fn status(in_transaction: bool) -> u16 {
+    match in_transaction {
+        true => SERVER_STATUS_AUTOCOMMIT | SERVER_STATUS_IN_TRANS,
+        false => SERVER_STATUS_AUTOCOMMIT,
+    }
+}
+
+fn ok(in_transaction: bool) -> Vec<u8> {
+    let mut packet = vec![0x00, 0, 0];
+    packet.extend_from_slice(&status(in_transaction).to_le_bytes());
+    packet.extend_from_slice(&0u16.to_le_bytes());
+    packet
+}
+
+fn eof(in_transaction: bool) -> Vec<u8> {
+    let mut packet = vec![0xfe, 0, 0];
+    packet.extend_from_slice(&status(in_transaction).to_le_bytes());
+    packet
+}
+
+/// Statement without parameters nor result columns.
+fn prepare_ok(id: u32) -> Vec<u8> {
+    let mut packet = vec![0x00];
+    packet.extend_from_slice(&id.to_le_bytes());
+    packet.extend_from_slice(&[0, 0, 0, 0, 0, 0, 0]);
+    packet
+}
+
+fn column_definition(name: &str, column_type: u8) -> Vec<u8> {
+    let mut packet = Vec::new();
+    for part in ["def", "app", "user_view", "user_view", name, name] {
+        lenenc(&mut packet, part.as_bytes());
+    }
+    packet.push(0x0c);
+    let collation = if column_type == VARCHAR { COLLATION_UTF8MB4 } else { COLLATION_BINARY };
+    packet.extend_from_slice(&collation.to_le_bytes());
+    packet.extend_from_slice(&1024u32.to_le_bytes());
+    packet.push(column_type);
+    packet.extend_from_slice(&[0, 0, 0, 0, 0]);
+    packet
+}
+
+fn err(number: u16) -> Vec<u8> {
+    let mut packet = vec![0xff];
+    packet.extend_from_slice(&number.to_le_bytes());
+    packet.extend_from_slice(b"#40001");
+    packet.extend_from_slice(format!("Scripted error {}", number).as_bytes());
+    packet
+}
+
+/// Length-encoded string, shorter than 251 bytes.
+fn lenenc(packet: &mut Vec<u8>, value: &[u8]) {
+    packet.push(value.len() as u8);
+    packet.extend_from_slice(value);
+}
@@ -0,0 +1,97 @@
+mod support;
+
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use anyhow::Result;
+
+use e_commerce_system::shared::database::mysql::{map_duplicate_key, DuplicateKey};
+use e_commerce_system::shared::database::mysql_pools::MySqlPools;
+use e_commerce_system::shared::database::unit_of_work::{is_retryable, transactional, UnitOfWork, MAX_TRANSACTION_ATTEMPTS};
+
+use support::mysql::MySqlStub;
+
+
+const UPDATE: &str = "UPDATE user SET version = version + 1 WHERE id = 1";
+
+const ER_LOCK_DEADLOCK: u16 = 1213;
+const ER_LOCK_WAIT_TIMEOUT: u16 = 1205;
+const ER_DUP_ENTRY: u16 = 1062;
+
+
+/// Runs `UPDATE` in a unit of work, counting the attempts.
+async fn update(pools: &MySqlPools, attempts: &AtomicU32) -> Result<()> {
+    transactional(pools, |unit_of_work| async move {
+        attempts.fetch_add(1, Ordering::SeqCst);
+        unit_of_work.execute(sqlx::query(UPDATE)).await?;
+        Ok(())
+    }).await
+}
+
+
+#[tokio::test]
+async fn deadlocked_unit_of_work_is_retried() {
+    let stub = MySqlStub::start().await;
+    stub.fail(UPDATE, &[ER_LOCK_DEADLOCK, ER_LOCK_DEADLOCK]);
+    let attempts = AtomicU32::new(0);
+
+    update(&stub.pools(), &attempts).await.unwrap();
+
+    assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    assert_eq!(stub.statements(), [
+        "BEGIN", UPDATE, "ROLLBACK",
+        "BEGIN", UPDATE, "ROLLBACK",
+        "BEGIN", UPDATE, "COMMIT",
+    ]);
+}
+
+#[tokio::test]
+async fn lock_wait_timeouts_are_reported_after_the_last_attempt() {
+    let stub = MySqlStub::start().await;
+    stub.fail(UPDATE, &[ER_LOCK_WAIT_TIMEOUT; 4]);
+    let attempts = AtomicU32::new(0);
+
+    let error = update(&stub.pools(), &attempts).await.unwrap_err();
+
+    assert!(is_retryable(&error));
+    assert_eq!(attempts.load(Ordering::SeqCst), MAX_TRANSACTION_ATTEMPTS);
+    assert_eq!(stub.statements().iter().filter(|statement| *statement == "ROLLBACK").count(), MAX_TRANSACTION_ATTEMPTS as usize);
+    assert!(!stub.statements().iter().any(|statement| statement == "COMMIT"));
+}
+
+#[tokio::test]
+async fn other_errors_are_not_retried() {
+    let stub = MySqlStub::start().await;
+    stub.fail(UPDATE, &[ER_DUP_ENTRY]);
+    let attempts = AtomicU32::new(0);
+
+    let error = update(&stub.pools(), &attempts).await.unwrap_err();
+
+    assert!(!is_retryable(&error));
+    assert!(map_duplicate_key(error).is::<DuplicateKey>());
+    assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    assert_eq!(stub.statements(), ["BEGIN", UPDATE, "ROLLBACK"]);
+}
+
+#[tokio::test]
+async fn savepoints_roll_back_part_of_the_unit_of_work() {
+    let stub = MySqlStub::start().await;
+    let pools = stub.pools();
+
+    let unit_of_work = UnitOfWork::begin(&pools).await.unwrap();
+    let savepoint = unit_of_work.savepoint().await.unwrap();
+    unit_of_work.execute(sqlx::query(UPDATE)).await.unwrap();
+    unit_of_work.rollback_to(&savepoint).await.unwrap();
+    let savepoint = unit_of_work.savepoint().await.unwrap();
+    unit_of_work.execute(sqlx::query(UPDATE)).await.unwrap();
+    unit_of_work.release(savepoint).await.unwrap();
+    unit_of_work.clone().commit().await.unwrap();
+
+    assert_eq!(stub.statements(), [
+        "BEGIN",
+        "SAVEPOINT sp_1", UPDATE, "ROLLBACK TO SAVEPOINT sp_1",
+        "SAVEPOINT sp_2", UPDATE, "RELEASE SAVEPOINT sp_2",
+        "COMMIT",
+    ]);
+    // clones share the transaction, it cannot be used once committed
+    assert!(unit_of_work.execute(sqlx::query(UPDATE)).await.is_err());
+}
@@ -0,0 +1,302 @@
+mod support;
+
+use axum::http::StatusCode;
+use serde_json::json;
+
+use e_commerce_system::services::user::model::user_model::{USER_AUTH_CUSTOMER, USER_STATUS_ACTIVE, USER_STATUS_DELETED};
+use e_commerce_system::shared::security::password::{is_password_hash, verify_password};
+use support::app::TestApp;
+
+
+#[tokio::test]
+async fn created_user_is_read_back_with_its_etag_and_cached() {
+    let app = TestApp::new().await;
+    let user = app.create_user("jdoe", app.seed.customer_auth).await;
+    let user_id = user["id"].as_i64().unwrap();
+
+    let response = app.get(&format!("/api/user/{}", user_id)).send().await;
+
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.etag(), "\"1\"");
+    assert_eq!(response.body["username"], "jdoe");
+    assert_eq!(response.body["auth"]["name"], USER_AUTH_CUSTOMER);
+    assert_eq!(response.body["status"]["name"], USER_STATUS_ACTIVE);
+    assert!(response.body.get("password").is_none());
+    assert!(app.cache.keys().iter().any(|key| key.ends_with(&format!(":user:{}", user_id))));
+}
+
+#[tokio::test]
+async fn unknown_user_is_not_found() {
+    let app = TestApp::new().await;
+
+    let response = app.get("/api/user/404").send().await;
+
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn put_requires_the_current_etag() {
+    let app = TestApp::new().await;
+    let user_id = app.create_user("jdoe", app.seed.customer_auth).await["id"].as_i64().unwrap();
+    let uri = format!("/api/user/{}", user_id);
+    let body = json!({ "first_name": "Jane", "last_name": "Doe", "title": "Lead" });
+
+    let missing = app.put(&uri).json(&body).send().await;
+    let stale = app.put(&uri).if_match("\"7\"").json(&body).send().await;
+    let current = app.put(&uri).if_match("\"1\"").json(&body).send().await;
+
+    assert_eq!(missing.status, StatusCode::PRECONDITION_REQUIRED);
+    assert_eq!(stale.status, StatusCode::PRECONDITION_FAILED);
+    assert_eq!(current.status, StatusCode::OK);
+    assert_eq!(current.etag(), "\"2\"");
+    // the cached copy was refreshed with the update
+    let read = app.get(&uri).send().await;
+    assert_eq!(read.body["first_name"], "Jane");
+    assert_eq!(read.etag(), "\"2\"");
+}
+
+#[tokio::test]
+async fn patch_only_changes_the_given_fields() {
+    let app = TestApp::new().await;
+    let user_id = app.create_user("jdoe", app.seed.customer_auth).await["id"].as_i64().unwrap();
+    let uri = format!("/api/user/{}", user_id);
+    let body = json!({ "last_name": "Smith", "title": null });
+
+    let missing = app.patch(&uri).json(&body).send().await;
+    let response = app.patch(&uri).if_match("\"1\"").json(&body).send().await;
+
+    assert_eq!(missing.status, StatusCode::PRECONDITION_REQUIRED);
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["first_name"], "First");
+    assert_eq!(response.body["last_name"], "Smith");
+    let user = app.database.user(user_id).unwrap();
+    assert_eq!(user.title, None);
+    assert_eq!(user.country.as_deref(), Some("CM"));
+}
+
+#[tokio::test]
+async fn deleted_user_is_evicted_from_the_cache() {
+    let app = TestApp::new().await;
+    let user_id = app.create_user("jdoe", app.seed.customer_auth).await["id"].as_i64().unwrap();
+    let uri = format!("/api/user/{}", user_id);
+    assert_eq!(app.get(&uri).send().await.status, StatusCode::OK);
+
+    let response = app.delete(&uri).if_match("\"1\"").send().await;
+
+    assert_eq!(response.status, StatusCode::OK);
+    let deleted = app.get(&uri).send().await;
+    assert_eq!(deleted.body["status"]["name"], USER_STATUS_DELETED);
+    assert_eq!(deleted.headers["etag"], "\"2\"");
+}
+
+#[tokio::test]
+async fn delete_racing_another_write_is_a_precondition_failure() {
+    let app = TestApp::new().await;
+    let user_id = app.create_user("jdoe", app.seed.customer_auth).await["id"].as_i64().unwrap();
+    app.database.write_concurrently(user_id);
+
+    let response = app.delete(&format!("/api/user/{}", user_id)).if_match("\"1\"").send().await;
+
+    assert_eq!(response.status, StatusCode::PRECONDITION_FAILED);
+    assert_eq!(app.database.user(user_id).unwrap().version, Some(2));
+}
+
+#[tokio::test]
+async fn users_are_listed_by_title_and_page() {
+    let app = TestApp::new().await;
+    for username in ["a", "b", "c"] {
+        app.create_user(username, app.seed.customer_auth).await;
+    }
+
+    let all = app.get("/api/user").send().await;
+    let page = app.get("/api/user?page=1&page_size=2").send().await;
+    let by_title = app.get("/api/user?title=Engineer").send().await;
+    let by_other_title = app.get("/api/user?title=Chef").send().await;
+
+    assert_eq!(all.body.as_array().unwrap().len(), 3);
+    assert_eq!(page.body.as_array().unwrap().len(), 1);
+    assert_eq!(page.body[0]["username"], "c");
+    assert_eq!(by_title.body.as_array().unwrap().len(), 3);
+    assert!(by_other_title.body.as_array().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn only_managers_change_the_status_of_a_user() {
+    let app = TestApp::new().await;
+    let user_id = app.create_user("jdoe", app.seed.customer_auth).await["id"].as_i64().unwrap();
+    let uri = format!("/api/user/{}/status", user_id);
+    let suspended = app.database.insert_user_status("suspended");
+    let body = json!({ "status": suspended });
+
+    let anonymous = app.put(&uri).json(&body).send().await;
+    let customer = app.put(&uri).bearer(&app.token(user_id, USER_AUTH_CUSTOMER)).json(&body).send().await;
+    let manager = app.put(&uri).bearer(&app.manager_token()).json(&body).send().await;
+
+    assert_eq!(anonymous.status, StatusCode::UNAUTHORIZED);
+    assert_eq!(customer.status, StatusCode::FORBIDDEN);
+    assert_eq!(manager.status, StatusCode::OK);
+    assert_eq!(manager.body["status"]["name"], "suspended");
+}
+
+#[tokio::test]
+async fn users_are_not_moved_to_the_deleted_status() {
+    let app = TestApp::new().await;
+    let user_id = app.create_user("jdoe", app.seed.customer_auth).await["id"].as_i64().unwrap();
+
+    let response = app.put(&format!("/api/user/{}/status", user_id))
+        .bearer(&app.manager_token())
+        .json(&json!({ "status": app.seed.deleted_status }))
+        .send()
+        .await;
+
+    assert_eq!(response.status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(app.database.user(user_id).unwrap().status, app.seed.active_status);
+}
+
+#[tokio::test]
+async fn deleted_status_is_left_through_a_restore() {
+    let app = TestApp::new().await;
+    let user_id = app.create_user("jdoe", app.seed.customer_auth).await["id"].as_i64().unwrap();
+    let token = app.manager_token();
+
+    let deleted = app.delete(&format!("/api/user/{}", user_id)).if_match("\"1\"").send().await;
+    let delete_again = app.delete(&format!("/api/user/{}", user_id)).if_match("\"2\"").send().await;
+    assert_eq!(deleted.status, StatusCode::OK);
+    assert_eq!(delete_again.status, StatusCode::CONFLICT);
+    assert_eq!(app.database.user(user_id).unwrap().status, app.seed.deleted_status);
+
+    let reactivate = app.put(&format!("/api/user/{}/status", user_id)).bearer(&token).json(&json!({ "status": app.seed.active_status })).send().await;
+    let restore = app.post(&format!("/api/user/{}/restore", user_id)).bearer(&token).send().await;
+    let restore_again = app.post(&format!("/api/user/{}/restore", user_id)).bearer(&token).send().await;
+
+    assert_eq!(reactivate.status, StatusCode::CONFLICT);
+    assert_eq!(restore.status, StatusCode::OK);
+    assert_eq!(restore.body["status"]["name"], USER_STATUS_ACTIVE);
+    assert_eq!(restore_again.status, StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+async fn users_prove_their_current_password_to_change_it() {
+    let app = TestApp::new().await;
+    let user_id = app.create_user("jdoe", app.seed.customer_auth).await["id"].as_i64().unwrap();
+    let uri = format!("/api/user/{}/password", user_id);
+    let token = app.token(user_id, USER_AUTH_CUSTOMER);
+
+    let missing = app.put(&uri).bearer(&token).json(&json!({ "password": "new-password" })).send().await;
+    let wrong = app.put(&uri).bearer(&token).json(&json!({ "current_password": "nope", "password": "new-password" })).send().await;
+    let other = app.put(&uri).bearer(&app.token(user_id + 1, USER_AUTH_CUSTOMER)).json(&json!({ "password": "new-password" })).send().await;
+    let changed = app.put(&uri).bearer(&token).json(&json!({ "current_password": "secret-password", "password": "new-password" })).send().await;
+
+    assert_eq!(missing.status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(wrong.status, StatusCode::FORBIDDEN);
+    assert_eq!(other.status, StatusCode::FORBIDDEN);
+    assert_eq!(changed.status, StatusCode::NO_CONTENT);
+    assert!(verify_password("new-password", &app.database.user(user_id).unwrap().password));
+}
+
+#[tokio::test]
+async fn legacy_plain_text_password_is_hashed_on_change() {
+    let app = TestApp::new().await;
+    let user_id = app.create_user("jdoe", app.seed.customer_auth).await["id"].as_i64().unwrap();
+    app.database.update_user(user_id, |user| user.password = "legacy-password".to_string());
+
+    let response = app.put(&format!("/api/user/{}/password", user_id))
+        .bearer(&app.token(user_id, USER_AUTH_CUSTOMER))
+        .json(&json!({ "current_password": "legacy-password", "password": "new-password" }))
+        .send()
+        .await;
+
+    assert_eq!(response.status, StatusCode::NO_CONTENT);
+    let password = app.database.user(user_id).unwrap().password;
+    assert!(is_password_hash(&password));
+    assert!(verify_password("new-password", &password));
+    assert!(!verify_password("", ""));
+}
+
+#[tokio::test]
+async fn users_are_created_with_a_password() {
+    let app = TestApp::new().await;
+    let user = |password: serde_json::Value| json!({
+        "first_name": "First",
+        "last_name": "Last",
+        "username": "jdoe",
+        "password": password,
+        "auth": app.seed.customer_auth,
+        "status": app.seed.active_status,
+    });
+
+    let missing = app.post("/api/user").json(&user(serde_json::Value::Null)).send().await;
+    let short = app.post("/api/user").json(&user(json!("short"))).send().await;
+
+    assert_eq!(missing.status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(short.status, StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[tokio::test]
+async fn taken_username_is_a_conflict() {
+    let app = TestApp::new().await;
+    let user_id = app.create_user("jdoe", app.seed.customer_auth).await["id"].as_i64().unwrap();
+
+    let duplicate = app.post("/api/user")
+        .json(&json!({
+            "first_name": "Other",
+            "last_name": "User",
+            "username": "jdoe",
+            "password": "secret-password",
+            "auth": app.seed.customer_auth,
+            "status": app.seed.active_status,
+        }))
+        .send()
+        .await;
+
+    assert_eq!(duplicate.status, StatusCode::CONFLICT);
+    assert_eq!(app.database.user(user_id).unwrap().first_name, "First");
+}
+
+#[tokio::test]
+async fn profile_pic_url_is_changed_by_its_owner() {
+    let app = TestApp::new().await;
+    let user_id = app.create_user("jdoe", app.seed.customer_auth).await["id"].as_i64().unwrap();
+
+    let response = app.put(&format!("/api/user/{}/profile-pic", user_id))
+        .bearer(&app.token(user_id, USER_AUTH_CUSTOMER))
+        .json(&json!({ "profile_pic_url": "http://localhost/media/me.png" }))
+        .send()
+        .await;
+
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["profile_pic_url"], "http://localhost/media/me.png");
+}
+
+#[tokio::test]
+async fn batch_lookup_queries_only_the_users_missing_from_the_cache() {
+    let app = TestApp::new().await;
+    let alice = app.create_user("alice", app.seed.customer_auth).await["id"].as_i64().unwrap();
+    let bob = app.create_user("bob", app.seed.customer_auth).await["id"].as_i64().unwrap();
+    let bob_key = app.cache.keys().into_iter().find(|key| key.ends_with(&format!(":user:{}", bob))).unwrap();
+    app.cache.remove(&bob_key);
+
+    let response = app.post("/api/user/batch").json(&json!({ "ids": [bob, 404, alice, bob] })).send().await;
+
+    assert_eq!(response.status, StatusCode::OK);
+    let usernames: Vec<&str> = response.body.as_array().unwrap().iter().map(|user| user["username"].as_str().unwrap()).collect();
+    assert_eq!(usernames, ["bob", "alice"]);
+    assert_eq!(app.database.batch_lookups(), [vec![bob, 404]]);
+    assert!(app.cache.contains(&bob_key));
+
+    // Found and unknown users are now both cached
+    app.post("/api/user/batch").json(&json!({ "ids": [404, bob] })).send().await;
+    assert_eq!(app.database.batch_lookups().len(), 1);
+}
+
+#[tokio::test]
+async fn batch_lookup_is_bounded() {
+    let app = TestApp::new().await;
+    let ids: Vec<i64> = (1..=101).collect();
+
+    let response = app.post("/api/user/batch").json(&json!({ "ids": ids })).send().await;
+    let empty = app.post("/api/user/batch").json(&json!({ "ids": [] })).send().await;
+
+    assert_eq!(response.status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(empty.body, json!([]));
+}
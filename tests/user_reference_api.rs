@@ -0,0 +1,70 @@
+mod support;
+
+use axum::http::StatusCode;
+use serde_json::json;
+
+use support::app::TestApp;
+
+
+#[tokio::test]
+async fn user_auth_lifecycle() {
+    let app = TestApp::new().await;
+
+    let created = app.post("/api/user/auth").json(&json!({ "name": "supplier", "description": "Sells goods" })).send().await;
+    assert_eq!(created.status, StatusCode::OK);
+    let uri = format!("/api/user/auth/{}", created.body["id"]);
+
+    let read = app.get(&uri).send().await;
+    assert_eq!(read.status, StatusCode::OK);
+    assert_eq!(read.body["name"], "supplier");
+
+    let updated = app.put(&uri).if_match(&read.etag()).json(&json!({ "name": "vendor", "description": null })).send().await;
+    assert_eq!(updated.status, StatusCode::OK);
+    assert_eq!(updated.body["name"], "vendor");
+
+    let unconditional = app.patch(&uri).json(&json!({ "description": "Sells goods" })).send().await;
+    assert_eq!(unconditional.status, StatusCode::PRECONDITION_REQUIRED);
+
+    let patched = app.patch(&uri).if_match(&updated.etag()).json(&json!({ "description": "Sells goods" })).send().await;
+    assert_eq!(patched.status, StatusCode::OK);
+    assert_eq!(patched.body["name"], "vendor");
+
+    let stale = app.delete(&uri).if_match(&read.etag()).send().await;
+    assert_eq!(stale.status, StatusCode::PRECONDITION_FAILED);
+    let deleted = app.delete(&uri).if_match(&patched.etag()).send().await;
+    assert_eq!(deleted.status, StatusCode::OK);
+    assert_eq!(app.get(&uri).send().await.status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn user_auths_are_listed() {
+    let app = TestApp::new().await;
+
+    let response = app.get("/api/user/auth").send().await;
+
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body.as_array().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn user_status_lifecycle() {
+    let app = TestApp::new().await;
+
+    let created = app.post("/api/user/status").json(&json!({ "name": "locked", "description": null })).send().await;
+    assert_eq!(created.status, StatusCode::OK);
+    let uri = format!("/api/user/status/{}", created.body["id"]);
+
+    let read = app.get(&uri).send().await;
+    assert_eq!(read.status, StatusCode::OK);
+
+    let updated = app.put(&uri).if_match(&read.etag()).json(&json!({ "name": "suspended", "description": "Temporarily" })).send().await;
+    assert_eq!(updated.status, StatusCode::OK);
+    assert_eq!(app.get(&uri).send().await.body["name"], "suspended");
+
+    let listed = app.get("/api/user/status").send().await;
+    assert_eq!(listed.body.as_array().unwrap().len(), 3);
+
+    let deleted = app.delete(&uri).if_match(&updated.etag()).send().await;
+    assert_eq!(deleted.status, StatusCode::OK);
+    assert_eq!(app.get(&uri).send().await.status, StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,125 @@
+mod support;
+
+use axum::http::StatusCode;
+use serde_json::json;
+
+use e_commerce_system::services::user::model::user_model::{USER_AUTH_CUSTOMER, USER_AUTH_MANAGER, USER_STATUS_ACTIVE};
+use support::app::{TestApp, STRICT_RATE_LIMIT};
+
+
+#[tokio::test]
+async fn unversioned_routes_are_v1_with_deprecation_headers() {
+    let app = TestApp::new().await;
+    let user_id = app.create_user("jdoe", app.seed.customer_auth).await["id"].as_i64().unwrap();
+
+    let unversioned = app.get(&format!("/api/user/{}", user_id)).send().await;
+    let v1 = app.get(&format!("/api/v1/user/{}", user_id)).send().await;
+
+    assert_eq!(unversioned.status, StatusCode::OK);
+    assert_eq!(unversioned.body, v1.body);
+    assert_eq!(unversioned.headers["deprecation"], "@1792368000");
+    assert_eq!(unversioned.headers["sunset"], "Fri, 30 Apr 2027 00:00:00 GMT");
+    assert_eq!(unversioned.headers["link"], format!("</api/v1/user/{}>; rel=\"successor-version\"", user_id));
+    assert!(!v1.headers.contains_key("deprecation"));
+    assert!(!v1.headers.contains_key("sunset"));
+}
+
+#[tokio::test]
+async fn v2_returns_auth_and_status_by_name() {
+    let app = TestApp::new().await;
+    let user_id = app.create_user("jdoe", app.seed.customer_auth).await["id"].as_i64().unwrap();
+    let uri = format!("/api/v2/user/{}", user_id);
+
+    let user = app.get(&uri).send().await;
+    assert_eq!(user.status, StatusCode::OK);
+    assert_eq!(user.body["auth"], USER_AUTH_CUSTOMER);
+    assert_eq!(user.body["status"], USER_STATUS_ACTIVE);
+    assert!(user.body.get("version").is_none());
+
+    let etag = user.headers["etag"].to_str().unwrap().to_string();
+    let patched = app.patch(&uri).if_match(&etag).json(&json!({ "first_name": "John" })).send().await;
+    assert_eq!(patched.status, StatusCode::OK);
+    assert_eq!(patched.body["first_name"], "John");
+    assert_ne!(patched.headers["etag"], etag.as_str());
+
+    let users = app.get("/api/v2/user").send().await;
+    assert_eq!(users.body[0]["auth"], USER_AUTH_CUSTOMER);
+
+    // Routes without users in their responses are the v1 handlers
+    let etag = patched.headers["etag"].to_str().unwrap().to_string();
+    assert_eq!(app.delete(&uri).if_match(&etag).send().await.status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn v2_returns_managers_and_customers_with_users_in_the_v2_shape() {
+    let app = TestApp::new().await;
+    let manager = json!({
+        "first_name": "Grace",
+        "last_name": "Hopper",
+        "username": "grace",
+        "password": "secret-password",
+        "hired_date": "2024-01-01T00:00:00Z",
+        "title": "Director",
+    });
+    let customer = json!({
+        "first_name": "Ada",
+        "last_name": "Lovelace",
+        "username": "ada",
+        "password": "secret-password",
+        "address": "1 Main Street",
+        "country": "CM",
+        "phone": "+237000000",
+    });
+
+    let created = app.post("/api/v2/manager").bearer(&app.manager_token()).json(&manager).send().await;
+    assert_eq!(created.status, StatusCode::OK);
+    assert_eq!(created.body["user"]["auth"], USER_AUTH_MANAGER);
+    assert!(created.body["user"].get("version").is_none());
+    assert_eq!(created.body["title"], "Director");
+    let managers = app.get("/api/v2/manager").bearer(&app.manager_token()).send().await;
+    assert_eq!(managers.body[0]["user"]["status"], USER_STATUS_ACTIVE);
+
+    let signed_up = app.post("/api/v2/customer").json(&customer).send().await;
+    assert_eq!(signed_up.status, StatusCode::OK);
+    assert_eq!(signed_up.body["user"]["auth"], USER_AUTH_CUSTOMER);
+    let uri = format!("/api/v2/customer/{}", signed_up.body["user"]["id"]);
+    let read = app.get(&uri).bearer(&app.manager_token()).send().await;
+    assert_eq!((read.body["user"]["status"].as_str(), read.body["country"].as_str()), (Some(USER_STATUS_ACTIVE), Some("CM")));
+    assert_eq!(app.get(&uri).send().await.status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn each_version_has_its_own_openapi_document() {
+    let app = TestApp::new().await;
+
+    let v1 = app.get("/apidoc/v1/openapi.json").send().await;
+    let v2 = app.get("/apidoc/v2/openapi.json").send().await;
+
+    assert_eq!(v1.body["info"]["version"], "1.0.0");
+    assert_eq!(v2.body["info"]["version"], "2.0.0");
+    assert!(v1.body["paths"].get("/api/user/{user_id}").is_none());
+    assert!(v2.body["paths"].get("/api/v1/user/{user_id}").is_none());
+
+    let response_schema = |doc: &serde_json::Value, path: &str| {
+        doc["paths"][path]["get"]["responses"]["200"]["content"]["application/json"]["schema"]["$ref"].clone()
+    };
+    assert_eq!(response_schema(&v1.body, "/api/v1/user/{user_id}"), "#/components/schemas/UserResponse");
+    assert_eq!(response_schema(&v2.body, "/api/v2/user/{user_id}"), "#/components/schemas/UserV2Response");
+    assert_eq!(response_schema(&v2.body, "/api/v2/manager/{user_id}"), "#/components/schemas/ManagerUserV2Response");
+    assert_eq!(response_schema(&v2.body, "/api/v2/customer/{user_id}"), "#/components/schemas/CustomerUserV2Response");
+
+    // Operations v2 does not override are kept
+    assert!(v2.body["paths"]["/api/v2/user/{user_id}"]["delete"].is_object());
+    assert!(v2.body["paths"]["/api/v2/manager"].is_object());
+    assert!(v2.body["paths"].get("/readyz").is_some());
+}
+
+#[tokio::test]
+async fn rate_limit_rules_apply_to_every_version() {
+    let app = TestApp::new().await;
+
+    for uri in ["/api/user/1/password", "/api/v1/user/1/password", "/api/v2/user/1/password"] {
+        let response = app.put(uri).json(&json!({ "password": "new-password" })).send().await;
+        assert_eq!(response.headers["ratelimit-limit"], STRICT_RATE_LIMIT.to_string().as_str());
+    }
+}